@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum JobRunStatus {
+    InProgress,
+    Complete,
+    Failed,
+    /// Superseded by a newer run of a job whose concurrency policy is `Replace`.
+    Replaced,
+    /// Intentionally not run, its deadlines are not checked.
+    Skipped,
+    /// Stopped on purpose, its deadlines are no longer checked.
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum JobRunStageStatus {
+    Occurred,
+    Failed,
+    Missed,
+    /// The attempt failed and the stage is retried.
+    Retrying,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRunStage {
+    pub name: String,
+    pub start_status: Option<JobRunStageStatus>,
+    pub start_date_time: Option<DateTime<Utc>>,
+    pub complete_status: Option<JobRunStageStatus>,
+    pub complete_date_time: Option<DateTime<Utc>>,
+    /// Which attempt of the stage the event belongs to, counting from 1. Absent on events recorded before retries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempt: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRunDto {
+    pub id: String,
+    pub app_name: String,
+    pub job_name: String,
+    pub triggered_at: DateTime<Utc>,
+    pub status: JobRunStatus,
+    pub stages: Vec<JobRunStage>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// The schedule occurrence the run belongs to, absent for unscheduled jobs.
+    #[serde(default)]
+    pub scheduled_for: Option<DateTime<Utc>>,
+    /// The key the run was reported with, see [`TriggerPayload::run_key`].
+    #[serde(default)]
+    pub run_key: Option<String>,
+    /// Why the run was skipped or cancelled.
+    #[serde(default)]
+    pub status_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum JobRunStageEventType {
+    #[serde(rename = "started")]
+    Started,
+    #[serde(rename = "completed")]
+    Completed,
+    #[serde(rename = "failed")]
+    Failed,
+    /// The attempt failed and the orchestrator retries the stage.
+    #[serde(rename = "retrying")]
+    Retrying,
+}
+
+/// Body of `POST /applications/{app_name}/jobs/{job_name}/stage-update` and `POST /job-runs/{job_run_id}/stage-update`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageUpdatePayload {
+    pub stage_name: String,
+    pub event_type: JobRunStageEventType,
+    pub message: Option<String>, // Optional: Good for error messages on failure
+    /// Which attempt of the stage the event is for, counting from 1. When absent a start after a failed attempt
+    /// begins the next attempt, and other events belong to the current one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempt: Option<u32>,
+    /// Reports to the run with this key instead of guessing the run from the current time, starting it when needed.
+    /// Ignored when the run is addressed by id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_key: Option<String>,
+    /// The schedule occurrence a run started by a keyed event is for, see [`TriggerPayload::logical_date`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logical_date: Option<DateTime<Utc>>,
+}
+
+/// Optional body of `POST /applications/{app_name}/jobs/{job_name}/trigger`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TriggerPayload {
+    /// Identifies the run within its job, e.g. an Airflow logical date or a batch id. Unique per job, triggering
+    /// a key again returns the run it started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_key: Option<String>,
+    /// The schedule occurrence the run is for when it isn't the current one, e.g. for a backfill.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logical_date: Option<DateTime<Utc>>,
+}
+
+/// Body of `POST /job-runs/{job_run_id}/skip` and `POST /job-runs/{job_run_id}/cancel`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloseRunPayload {
+    pub reason: String,
+}
+
+/// Body of `POST /applications/{app_name}/jobs/{job_name}/occurrences/skip` and `.../occurrences/cancel`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloseOccurrencePayload {
+    /// The schedule occurrence, which may still be ahead; its run is created when there is none yet.
+    pub scheduled_for: DateTime<Utc>,
+    pub reason: String,
+}
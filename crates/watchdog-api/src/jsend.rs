@@ -1,8 +1,5 @@
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
-use axum::http::StatusCode;
-use axum::Json;
-use axum::response::{IntoResponse, Response};
 
 /// JSend-style status enum
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
@@ -170,11 +167,15 @@ where
 }
 
 /// Allow direct return from Axum handlers
-impl<T> IntoResponse for AppResponse<T>
+#[cfg(feature = "axum")]
+impl<T> axum::response::IntoResponse for AppResponse<T>
 where
     T: Serialize,
 {
-    fn into_response(self) -> Response {
+    fn into_response(self) -> axum::response::Response {
+        use axum::http::StatusCode;
+        use axum::Json;
+
         let status_code = match self.status {
             Status::Success => StatusCode::OK,
             Status::Fail => StatusCode::BAD_REQUEST,
@@ -0,0 +1,4 @@
+//! Request and response types of the watchdog API, shared by the server and its clients.
+
+pub mod jsend;
+pub mod job_run;
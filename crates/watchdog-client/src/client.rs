@@ -0,0 +1,187 @@
+use chrono::{DateTime, Utc};
+use reqwest::{Client, RequestBuilder};
+use watchdog_api::jsend::AppResponse;
+use watchdog_api::job_run::{CloseOccurrencePayload, CloseRunPayload, JobRunDto, JobRunStageEventType, StageUpdatePayload, TriggerPayload};
+use crate::error::ClientError;
+use crate::guard::StageGuard;
+
+const JOB_RUN_KEY: &str = "job-run";
+
+/// Client for the watchdog API. Cloning is cheap and clones share the connection pool.
+#[derive(Debug, Clone)]
+pub struct WatchdogClient {
+    http: Client,
+    base_url: String,
+}
+
+/// The run a stage event is reported to.
+#[derive(Debug, Clone)]
+pub enum RunTarget {
+    /// The latest run of the job; the server starts a new run when the event opens one.
+    Latest { app_name: String, job_name: String },
+    /// A run by id, as returned by [`WatchdogClient::trigger`].
+    Run(String),
+    /// The run with the key, e.g. an Airflow logical date or a batch id; the server starts it when the key is new.
+    Keyed { app_name: String, job_name: String, run_key: String, logical_date: Option<DateTime<Utc>> },
+}
+
+impl RunTarget {
+    pub fn latest(app_name: impl Into<String>, job_name: impl Into<String>) -> Self {
+        RunTarget::Latest { app_name: app_name.into(), job_name: job_name.into() }
+    }
+
+    pub fn keyed(app_name: impl Into<String>, job_name: impl Into<String>, run_key: impl Into<String>) -> Self {
+        RunTarget::Keyed { app_name: app_name.into(), job_name: job_name.into(), run_key: run_key.into(), logical_date: None }
+    }
+
+    fn stage_update_path(&self) -> String {
+        match self {
+            RunTarget::Latest { app_name, job_name } | RunTarget::Keyed { app_name, job_name, .. } => {
+                format!("/applications/{}/jobs/{}/stage-update", app_name, job_name)
+            }
+            RunTarget::Run(run_id) => format!("/job-runs/{}/stage-update", run_id),
+        }
+    }
+}
+
+impl WatchdogClient {
+    /// `base_url` is the api root, e.g. `http://localhost:8080/api`.
+    pub fn new(base_url: &str) -> Self {
+        Self::with_http_client(base_url, Client::new())
+    }
+
+    /// Uses a preconfigured http client, e.g. one with timeouts or default headers.
+    pub fn with_http_client(base_url: &str, http: Client) -> Self {
+        Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Starts a new run of the job.
+    pub async fn trigger(&self, app_name: &str, job_name: &str) -> Result<JobRunDto, ClientError> {
+        let request = self.http.post(self.url(&format!("/applications/{}/jobs/{}/trigger", app_name, job_name)));
+        send(request).await
+    }
+
+    /// Starts the run with the key, or returns it when the key was seen before.
+    pub async fn trigger_keyed(&self, app_name: &str, job_name: &str, payload: &TriggerPayload) -> Result<JobRunDto, ClientError> {
+        let request = self.http.post(self.url(&format!("/applications/{}/jobs/{}/trigger", app_name, job_name)));
+        send(request.json(payload)).await
+    }
+
+    pub async fn get_run(&self, run_id: &str) -> Result<JobRunDto, ClientError> {
+        send(self.http.get(self.url(&format!("/job-runs/{}", run_id)))).await
+    }
+
+    pub async fn get_run_by_key(&self, app_name: &str, job_name: &str, run_key: &str) -> Result<JobRunDto, ClientError> {
+        let path = format!("/applications/{}/jobs/{}/runs/{}", app_name, job_name, encode_path_segment(run_key));
+        send(self.http.get(self.url(&path))).await
+    }
+
+    /// Marks the run as intentionally not run; its deadlines are no longer checked.
+    pub async fn skip_run(&self, run_id: &str, reason: &str) -> Result<JobRunDto, ClientError> {
+        let request = self.http.post(self.url(&format!("/job-runs/{}/skip", run_id)));
+        send(request.json(&CloseRunPayload { reason: reason.to_string() })).await
+    }
+
+    pub async fn cancel_run(&self, run_id: &str, reason: &str) -> Result<JobRunDto, ClientError> {
+        let request = self.http.post(self.url(&format!("/job-runs/{}/cancel", run_id)));
+        send(request.json(&CloseRunPayload { reason: reason.to_string() })).await
+    }
+
+    /// Skips the occurrence of a scheduled job, e.g. a holiday, before or after its run started.
+    pub async fn skip_occurrence(&self, app_name: &str, job_name: &str, scheduled_for: DateTime<Utc>, reason: &str) -> Result<JobRunDto, ClientError> {
+        let request = self.http.post(self.url(&format!("/applications/{}/jobs/{}/occurrences/skip", app_name, job_name)));
+        send(request.json(&CloseOccurrencePayload { scheduled_for, reason: reason.to_string() })).await
+    }
+
+    pub async fn cancel_occurrence(&self, app_name: &str, job_name: &str, scheduled_for: DateTime<Utc>, reason: &str) -> Result<JobRunDto, ClientError> {
+        let request = self.http.post(self.url(&format!("/applications/{}/jobs/{}/occurrences/cancel", app_name, job_name)));
+        send(request.json(&CloseOccurrencePayload { scheduled_for, reason: reason.to_string() })).await
+    }
+
+    /// Keyed targets fill in the payload's run key and logical date.
+    pub async fn update_stage(&self, target: &RunTarget, payload: &StageUpdatePayload) -> Result<JobRunDto, ClientError> {
+        let mut payload = payload.clone();
+        if let RunTarget::Keyed { run_key, logical_date, .. } = target {
+            payload.run_key = Some(run_key.clone());
+            payload.logical_date = *logical_date;
+        }
+        send(self.http.post(self.url(&target.stage_update_path())).json(&payload)).await
+    }
+
+    pub async fn stage_started(&self, target: &RunTarget, stage_name: &str) -> Result<JobRunDto, ClientError> {
+        self.update_stage(target, &stage_payload(stage_name, JobRunStageEventType::Started, None)).await
+    }
+
+    pub async fn stage_completed(&self, target: &RunTarget, stage_name: &str) -> Result<JobRunDto, ClientError> {
+        self.update_stage(target, &stage_payload(stage_name, JobRunStageEventType::Completed, None)).await
+    }
+
+    pub async fn stage_failed(&self, target: &RunTarget, stage_name: &str, message: Option<String>) -> Result<JobRunDto, ClientError> {
+        self.update_stage(target, &stage_payload(stage_name, JobRunStageEventType::Failed, message)).await
+    }
+
+    /// Reports that the attempt failed and the stage is retried; the run only fails when it was the stage's last attempt.
+    pub async fn stage_retrying(&self, target: &RunTarget, stage_name: &str, message: Option<String>) -> Result<JobRunDto, ClientError> {
+        self.update_stage(target, &stage_payload(stage_name, JobRunStageEventType::Retrying, message)).await
+    }
+
+    /// Reports the stage as started and returns a guard that reports it failed unless
+    /// [`StageGuard::complete`] or [`StageGuard::fail`] is called. Later events go to the run the start was recorded on.
+    pub async fn start_stage(&self, target: RunTarget, stage_name: &str) -> Result<StageGuard, ClientError> {
+        let job_run = self.stage_started(&target, stage_name).await?;
+        Ok(StageGuard::new(self.clone(), RunTarget::Run(job_run.id), stage_name.to_string()))
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+}
+
+fn stage_payload(stage_name: &str, event_type: JobRunStageEventType, message: Option<String>) -> StageUpdatePayload {
+    StageUpdatePayload {
+        stage_name: stage_name.to_string(),
+        event_type,
+        message,
+        run_key: None,
+        logical_date: None,
+        attempt: None,
+    }
+}
+
+// Keys such as logical dates carry characters that are not allowed in a path segment
+fn encode_path_segment(segment: &str) -> String {
+    segment.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+async fn send(request: RequestBuilder) -> Result<JobRunDto, ClientError> {
+    let res = request.send().await?;
+    let status = res.status();
+    let body = res.text().await?;
+
+    let response: AppResponse<JobRunDto> = serde_json::from_str(&body).map_err(|e| {
+        if status.is_success() {
+            ClientError::InvalidResponse(e.to_string())
+        } else {
+            ClientError::Api { status: status.as_u16(), message: body.clone() }
+        }
+    })?;
+
+    if !status.is_success() || !response.is_success() {
+        return Err(ClientError::Api {
+            status: status.as_u16(),
+            message: response.message.unwrap_or_else(|| status.to_string()),
+        });
+    }
+
+    response.data
+        .and_then(|mut data| data.remove(JOB_RUN_KEY))
+        .ok_or_else(|| ClientError::InvalidResponse(format!("response has no '{}'", JOB_RUN_KEY)))
+}
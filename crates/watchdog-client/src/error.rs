@@ -0,0 +1,36 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The request could not be sent, or the response could not be read.
+    Http(reqwest::Error),
+    /// The server refused the request.
+    Api { status: u16, message: String },
+    /// The server answered with a body that is not the expected JSend response.
+    InvalidResponse(String),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Http(err) => write!(f, "request to watchdog failed: {}", err),
+            ClientError::Api { status, message } => write!(f, "watchdog responded with {}: {}", status, message),
+            ClientError::InvalidResponse(message) => write!(f, "invalid response from watchdog: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientError::Http(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(err: reqwest::Error) -> Self {
+        ClientError::Http(err)
+    }
+}
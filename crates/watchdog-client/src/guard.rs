@@ -0,0 +1,67 @@
+use tokio::runtime::Handle;
+use tracing::warn;
+use watchdog_api::job_run::JobRunDto;
+use crate::client::{RunTarget, WatchdogClient};
+use crate::error::ClientError;
+
+/// A started stage. Dropping it without calling [`complete`](Self::complete) or [`fail`](Self::fail),
+/// on an early return, `?` or a panic, reports the stage as failed.
+///
+/// The failure is sent from a task spawned on the current tokio runtime, so it is lost when
+/// the guard is dropped outside a runtime or the runtime shuts down before the task runs.
+#[must_use = "dropping the guard right away reports the stage as failed"]
+pub struct StageGuard {
+    client: WatchdogClient,
+    target: RunTarget,
+    stage_name: String,
+    finished: bool,
+}
+
+impl StageGuard {
+    pub(crate) fn new(client: WatchdogClient, target: RunTarget, stage_name: String) -> Self {
+        Self { client, target, stage_name, finished: false }
+    }
+
+    /// The run the stage is reported to.
+    pub fn target(&self) -> &RunTarget {
+        &self.target
+    }
+
+    pub async fn complete(mut self) -> Result<JobRunDto, ClientError> {
+        self.finished = true;
+        self.client.stage_completed(&self.target, &self.stage_name).await
+    }
+
+    pub async fn fail(mut self, message: impl Into<String>) -> Result<JobRunDto, ClientError> {
+        self.finished = true;
+        self.client.stage_failed(&self.target, &self.stage_name, Some(message.into())).await
+    }
+}
+
+impl Drop for StageGuard {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        let message = if std::thread::panicking() {
+            "stage panicked"
+        } else {
+            "stage ended without being completed"
+        };
+
+        let Ok(handle) = Handle::try_current() else {
+            warn!("unable to report stage {} as failed, no tokio runtime is running", self.stage_name);
+            return;
+        };
+
+        let client = self.client.clone();
+        let target = self.target.clone();
+        let stage_name = self.stage_name.clone();
+        handle.spawn(async move {
+            if let Err(err) = client.stage_failed(&target, &stage_name, Some(message.to_string())).await {
+                warn!("failed to report stage {} as failed: {}", stage_name, err);
+            }
+        });
+    }
+}
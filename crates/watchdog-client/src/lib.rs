@@ -0,0 +1,24 @@
+//! Typed client for the watchdog API, for services that report their job runs.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), watchdog_client::ClientError> {
+//! use watchdog_client::{RunTarget, WatchdogClient};
+//!
+//! let client = WatchdogClient::new("http://localhost:8080/api");
+//! let run = client.trigger("billing", "nightly-export").await?;
+//!
+//! let stage = client.start_stage(RunTarget::Run(run.id), "load").await?;
+//! // ... do the work; returning early or panicking reports the stage as failed
+//! stage.complete().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod client;
+mod error;
+mod guard;
+
+pub use client::{RunTarget, WatchdogClient};
+pub use error::ClientError;
+pub use guard::StageGuard;
+pub use watchdog_api::job_run::{CloseOccurrencePayload, CloseRunPayload, JobRunDto, JobRunStage, JobRunStageEventType, JobRunStageStatus, JobRunStatus, StageUpdatePayload, TriggerPayload};
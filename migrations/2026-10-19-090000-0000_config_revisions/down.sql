@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS channel_revisions;
+DROP TABLE IF EXISTS job_config_revisions;
+
+DROP TYPE IF EXISTS revision_change_type;
@@ -0,0 +1,53 @@
+CREATE TYPE revision_change_type AS ENUM ('initial', 'created', 'updated', 'enabled', 'rolled_back');
+
+CREATE TABLE job_config_revisions (
+                                      id BIGSERIAL PRIMARY KEY,
+                                      app_name VARCHAR(255) NOT NULL,
+                                      job_name VARCHAR(255) NOT NULL,
+                                      revision INT NOT NULL,
+                                      change_type revision_change_type NOT NULL,
+                                      changed_by VARCHAR,
+                                      snapshot JSONB NOT NULL,
+                                      created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                                      UNIQUE (app_name, job_name, revision)
+);
+
+CREATE TABLE channel_revisions (
+                                   id BIGSERIAL PRIMARY KEY,
+                                   channel_name VARCHAR NOT NULL,
+                                   revision INT NOT NULL,
+                                   change_type revision_change_type NOT NULL,
+                                   changed_by VARCHAR,
+                                   snapshot JSONB NOT NULL,
+                                   created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                                   UNIQUE (channel_name, revision)
+);
+
+-- Seed the current state so the first change can be diffed against it
+INSERT INTO job_config_revisions (app_name, job_name, revision, change_type, snapshot)
+SELECT app_name,
+       job_name,
+       1,
+       'initial',
+       json_build_object(
+           'app_name', app_name,
+           'job_name', job_name,
+           'schedule', schedule,
+           'zone_id', zone_id,
+           'enabled', enabled,
+           'stages', stages,
+           'channel_ids', channel_ids
+       )
+FROM job_configs;
+
+INSERT INTO channel_revisions (channel_name, revision, change_type, snapshot)
+SELECT name,
+       1,
+       'initial',
+       json_build_object(
+           'name', name,
+           -- serde names of ProviderType variants, e.g. gchat_webhook -> GchatWebhook
+           'provider_type', replace(initcap(replace(provider_type::text, '_', ' ')), ' ', ''),
+           'configuration', configuration
+       )
+FROM channels;
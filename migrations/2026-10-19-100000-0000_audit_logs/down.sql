@@ -0,0 +1,25 @@
+CREATE OR REPLACE FUNCTION notify_settings_changed()
+RETURNS trigger AS $$
+DECLARE
+payload JSON;
+BEGIN
+  payload = json_build_object(
+    'id', NEW.id,
+    'success_retention_days', NEW.success_retention_days,
+    'failure_retention_days', NEW.failure_retention_days,
+    'maintenance_mode', NEW.maintenance_mode,
+    'default_channels', NEW.default_channels,
+    'error_channels', NEW.error_channels,
+    'max_stage_duration_hours', NEW.max_stage_duration_hours,
+    'action', TG_OP
+  );
+
+  PERFORM pg_notify('settings_update', payload::text);
+
+RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+ALTER TABLE global_settings DROP COLUMN IF EXISTS audit_retention_days;
+
+DROP TABLE IF EXISTS audit_logs;
@@ -0,0 +1,42 @@
+CREATE TABLE audit_logs (
+                            id BIGSERIAL PRIMARY KEY,
+                            occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                            actor VARCHAR,
+                            client_ip VARCHAR,
+                            user_agent VARCHAR,
+                            method VARCHAR(10) NOT NULL,
+                            route VARCHAR NOT NULL,
+                            path VARCHAR NOT NULL,
+                            target VARCHAR,
+                            request_summary JSONB,
+                            status_code INT NOT NULL
+);
+
+CREATE INDEX idx_audit_logs_occurred_at ON audit_logs (occurred_at);
+CREATE INDEX idx_audit_logs_target ON audit_logs (target);
+
+ALTER TABLE global_settings ADD COLUMN audit_retention_days INT NOT NULL DEFAULT 90;
+
+-- Keep the settings_update payload in sync with the columns of global_settings
+CREATE OR REPLACE FUNCTION notify_settings_changed()
+RETURNS trigger AS $$
+DECLARE
+payload JSON;
+BEGIN
+  payload = json_build_object(
+    'id', NEW.id,
+    'success_retention_days', NEW.success_retention_days,
+    'failure_retention_days', NEW.failure_retention_days,
+    'maintenance_mode', NEW.maintenance_mode,
+    'default_channels', NEW.default_channels,
+    'error_channels', NEW.error_channels,
+    'max_stage_duration_hours', NEW.max_stage_duration_hours,
+    'audit_retention_days', NEW.audit_retention_days,
+    'action', TG_OP
+  );
+
+  PERFORM pg_notify('settings_update', payload::text);
+
+RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,7 @@
+-- Postgres cannot drop a single enum value, so the type is recreated without it
+DELETE FROM channels WHERE provider_type = 'webhook';
+
+ALTER TYPE provider_type RENAME TO provider_type_old;
+CREATE TYPE provider_type AS ENUM ('gchat_webhook', 'email_smtp', 'slack_webhook');
+ALTER TABLE channels ALTER COLUMN provider_type TYPE provider_type USING provider_type::text::provider_type;
+DROP TYPE provider_type_old;
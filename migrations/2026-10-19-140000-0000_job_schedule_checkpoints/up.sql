@@ -0,0 +1,12 @@
+-- Scheduled occurrences up to evaluated_until have been checked for timeouts. Occurrences after it
+-- whose window has closed were missed while watchdog was down and get evaluated late.
+CREATE TABLE job_schedule_checkpoints (
+                                          app_name VARCHAR(255) NOT NULL,
+                                          job_name VARCHAR(255) NOT NULL,
+                                          evaluated_until TIMESTAMPTZ NOT NULL,
+                                          updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                                          PRIMARY KEY (app_name, job_name),
+                                          FOREIGN KEY (app_name, job_name) REFERENCES job_configs (app_name, job_name) ON DELETE CASCADE
+);
+
+SELECT diesel_manage_updated_at('job_schedule_checkpoints');
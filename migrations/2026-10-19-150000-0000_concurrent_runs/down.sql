@@ -0,0 +1,15 @@
+DROP INDEX IF EXISTS idx_job_runs_job_scheduled_for;
+ALTER TABLE job_runs DROP COLUMN IF EXISTS scheduled_for;
+
+-- Postgres cannot drop a single enum value, so the type is recreated without it
+UPDATE job_runs SET status = 'failed' WHERE status = 'replaced';
+
+ALTER TYPE job_run_status RENAME TO job_run_status_old;
+CREATE TYPE job_run_status AS ENUM ('in_progress', 'complete', 'failed');
+ALTER TABLE job_runs ALTER COLUMN status DROP DEFAULT;
+ALTER TABLE job_runs ALTER COLUMN status TYPE job_run_status USING status::text::job_run_status;
+ALTER TABLE job_runs ALTER COLUMN status SET DEFAULT 'in_progress';
+DROP TYPE job_run_status_old;
+
+ALTER TABLE job_configs DROP COLUMN IF EXISTS concurrency_policy;
+DROP TYPE IF EXISTS concurrency_policy;
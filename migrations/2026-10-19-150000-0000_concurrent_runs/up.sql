@@ -0,0 +1,11 @@
+-- What happens when a run starts while earlier runs of the same job are still in progress
+CREATE TYPE concurrency_policy AS ENUM ('allow', 'forbid', 'replace');
+
+ALTER TABLE job_configs ADD COLUMN concurrency_policy concurrency_policy NOT NULL DEFAULT 'allow';
+
+ALTER TYPE job_run_status ADD VALUE IF NOT EXISTS 'replaced';
+
+-- The schedule occurrence a run belongs to, NULL for runs of unscheduled jobs
+ALTER TABLE job_runs ADD COLUMN scheduled_for TIMESTAMPTZ;
+
+CREATE INDEX idx_job_runs_job_scheduled_for ON job_runs (app_name, job_name, scheduled_for);
@@ -0,0 +1,4 @@
+-- Identifies a run within its job for the system that runs it, e.g. an Airflow logical date or a batch id
+ALTER TABLE job_runs ADD COLUMN run_key VARCHAR(255);
+
+CREATE UNIQUE INDEX idx_job_runs_job_run_key ON job_runs (app_name, job_name, run_key);
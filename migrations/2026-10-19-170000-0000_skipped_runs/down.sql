@@ -0,0 +1,11 @@
+ALTER TABLE job_runs DROP COLUMN IF EXISTS status_reason;
+
+-- Postgres cannot drop a single enum value, so the type is recreated without them
+UPDATE job_runs SET status = 'failed' WHERE status IN ('skipped', 'cancelled');
+
+ALTER TYPE job_run_status RENAME TO job_run_status_old;
+CREATE TYPE job_run_status AS ENUM ('in_progress', 'complete', 'failed', 'replaced');
+ALTER TABLE job_runs ALTER COLUMN status DROP DEFAULT;
+ALTER TABLE job_runs ALTER COLUMN status TYPE job_run_status USING status::text::job_run_status;
+ALTER TABLE job_runs ALTER COLUMN status SET DEFAULT 'in_progress';
+DROP TYPE job_run_status_old;
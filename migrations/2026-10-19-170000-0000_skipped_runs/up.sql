@@ -0,0 +1,6 @@
+-- Runs that were intentionally not run, or stopped while in progress
+ALTER TYPE job_run_status ADD VALUE IF NOT EXISTS 'skipped';
+ALTER TYPE job_run_status ADD VALUE IF NOT EXISTS 'cancelled';
+
+-- Why the run was skipped or cancelled
+ALTER TABLE job_runs ADD COLUMN status_reason TEXT;
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS snoozes;
+
+ALTER TABLE job_configs DROP COLUMN IF EXISTS blackout_windows;
@@ -0,0 +1,15 @@
+-- Recurring windows during which timeouts of the job are recorded but not alerted on
+ALTER TABLE job_configs ADD COLUMN blackout_windows JSONB NOT NULL DEFAULT '[]';
+
+-- Alerts of a job, or of every job of an application, silenced until a point in time
+CREATE TABLE snoozes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    app_name VARCHAR(255) NOT NULL,
+    -- NULL snoozes the whole application
+    job_name VARCHAR(255),
+    snoozed_until TIMESTAMPTZ NOT NULL,
+    reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX idx_snoozes_target ON snoozes (app_name, COALESCE(job_name, ''));
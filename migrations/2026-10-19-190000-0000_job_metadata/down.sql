@@ -0,0 +1,3 @@
+DROP TABLE application_configs;
+
+ALTER TABLE job_configs DROP COLUMN metadata;
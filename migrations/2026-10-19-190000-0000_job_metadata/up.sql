@@ -0,0 +1,12 @@
+-- Owner, contact, tags and runbook of the job, shown in its alerts
+ALTER TABLE job_configs ADD COLUMN metadata JSONB NOT NULL DEFAULT '{}';
+
+-- The same metadata for a whole application, applied to its jobs where they don't set their own
+CREATE TABLE application_configs (
+    app_name VARCHAR(255) PRIMARY KEY,
+    metadata JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+SELECT diesel_manage_updated_at('application_configs');
@@ -0,0 +1,17 @@
+-- Rules sending alerts to channels by what they are about, evaluated before the job's own channels
+CREATE TABLE routing_rules (
+    name VARCHAR(255) PRIMARY KEY,
+    -- Lower priorities are evaluated first
+    priority INTEGER NOT NULL DEFAULT 0,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    matcher JSONB NOT NULL DEFAULT '{}',
+    channel_ids VARCHAR NOT NULL,
+    -- Whether rules after a match are still evaluated
+    continue_matching BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+SELECT diesel_manage_updated_at('routing_rules');
+
+CREATE INDEX idx_routing_rules_priority ON routing_rules (priority);
@@ -0,0 +1,3 @@
+DROP TABLE job_run_events;
+DROP TYPE run_event_source;
+DROP TYPE run_event_type;
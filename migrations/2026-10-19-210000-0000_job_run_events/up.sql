@@ -0,0 +1,58 @@
+-- Every event received for or synthesized on a run, in the order it was recorded; never updated. The stages of a run
+-- are derived from its events.
+CREATE TYPE run_event_type AS ENUM ('run_created', 'stage_started', 'stage_completed', 'stage_failed', 'stage_retrying', 'start_missed', 'completion_missed', 'status_changed');
+CREATE TYPE run_event_source AS ENUM ('api', 'scheduler');
+
+CREATE TABLE job_run_events (
+    id BIGSERIAL PRIMARY KEY,
+    job_run_id UUID NOT NULL REFERENCES job_runs (id) ON DELETE CASCADE,
+    event_type run_event_type NOT NULL,
+    stage_name VARCHAR(255),
+    attempt INTEGER,
+    -- The status of the run once the event was applied
+    status job_run_status NOT NULL,
+    message TEXT,
+    source run_event_source NOT NULL,
+    occurred_at TIMESTAMPTZ NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_job_run_events_job_run_id ON job_run_events (job_run_id, id);
+
+-- The stages recorded so far become the events of their runs, a start before a completion recorded with it
+INSERT INTO job_run_events (job_run_id, event_type, stage_name, attempt, status, source, occurred_at)
+SELECT job_run_id, event_type, stage_name, attempt, status, source, occurred_at
+FROM (
+    SELECT job_runs.id AS job_run_id,
+           stage.position,
+           0 AS checkpoint,
+           CASE stage.value ->> 'start_status'
+               WHEN 'Missed' THEN 'start_missed'
+               ELSE 'stage_started'
+           END::run_event_type AS event_type,
+           stage.value ->> 'name' AS stage_name,
+           (stage.value ->> 'attempt')::INTEGER AS attempt,
+           job_runs.status,
+           CASE stage.value ->> 'start_status' WHEN 'Missed' THEN 'scheduler' ELSE 'api' END::run_event_source AS source,
+           COALESCE((stage.value ->> 'start_date_time')::TIMESTAMPTZ, job_runs.updated_at) AS occurred_at
+    FROM job_runs, jsonb_array_elements(job_runs.stages) WITH ORDINALITY AS stage(value, position)
+    WHERE stage.value ->> 'start_status' IS NOT NULL
+    UNION ALL
+    SELECT job_runs.id,
+           stage.position,
+           1,
+           CASE stage.value ->> 'complete_status'
+               WHEN 'Missed' THEN 'completion_missed'
+               WHEN 'Failed' THEN 'stage_failed'
+               WHEN 'Retrying' THEN 'stage_retrying'
+               ELSE 'stage_completed'
+           END::run_event_type,
+           stage.value ->> 'name',
+           (stage.value ->> 'attempt')::INTEGER,
+           job_runs.status,
+           CASE stage.value ->> 'complete_status' WHEN 'Missed' THEN 'scheduler' ELSE 'api' END::run_event_source,
+           COALESCE((stage.value ->> 'complete_date_time')::TIMESTAMPTZ, job_runs.updated_at)
+    FROM job_runs, jsonb_array_elements(job_runs.stages) WITH ORDINALITY AS stage(value, position)
+    WHERE stage.value ->> 'complete_status' IS NOT NULL
+) AS stage_events
+ORDER BY job_run_id, position, checkpoint;
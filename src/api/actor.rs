@@ -0,0 +1,26 @@
+use std::convert::Infallible;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+
+pub const ACTOR_HEADER: &str = "x-watchdog-actor";
+
+/// Identifies who made a change, taken from the `X-Watchdog-Actor` header until authentication exists.
+#[derive(Debug, Clone)]
+pub struct Actor(pub Option<String>);
+
+impl<S> FromRequestParts<S> for Actor
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let actor = parts.headers
+            .get(ACTOR_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+
+        Ok(Actor(actor))
+    }
+}
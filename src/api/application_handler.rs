@@ -0,0 +1,57 @@
+use axum::extract::{Path, State};
+use axum::Json;
+use tracing::info;
+use validator::Validate;
+use crate::{SharedState};
+use crate::db::application_repository::{get_application_config, save_application_config};
+use crate::db::config_repository::get_jobs_by_application;
+use crate::db::connection::DbConnection;
+use crate::dtos::application::{ApplicationDto, ApplicationUpdateRequest};
+use crate::errors::AppError;
+use watchdog_api::jsend::AppResponse;
+use crate::models::NewApplicationConfig;
+
+pub async fn get_application_handler(
+    State(state): State<SharedState>,
+    Path(app_name): Path<String>,
+) -> Result<AppResponse<ApplicationDto>, AppError> {
+    let mut conn = state.pool.get().await?;
+
+    // Applications exist through their jobs, their config is only stored once metadata is set
+    let application_dto = match get_application_config(&mut conn, &app_name).await? {
+        Some(application_config) => application_config.into(),
+        None => {
+            check_application_exists(&mut conn, &app_name).await?;
+            ApplicationDto { app_name, metadata: Default::default() }
+        }
+    };
+
+    Ok(AppResponse::success_one("application", application_dto))
+}
+
+pub async fn update_application_handler(
+    State(state): State<SharedState>,
+    Path(app_name): Path<String>,
+    Json(_update_request): Json<ApplicationUpdateRequest>,
+) -> Result<AppResponse<ApplicationDto>, AppError> {
+    _update_request.validate()?;
+    info!("Updating metadata of application: {}", app_name);
+
+    let mut conn = state.pool.get().await?;
+
+    check_application_exists(&mut conn, &app_name).await?;
+
+    let saved = save_application_config(&mut conn, NewApplicationConfig {
+        app_name,
+        metadata: _update_request.metadata,
+    }).await?;
+
+    Ok(AppResponse::success_one("application", saved.into()))
+}
+
+async fn check_application_exists(conn: &mut DbConnection<'_>, app_name: &str) -> Result<(), AppError> {
+    if get_jobs_by_application(conn, app_name.to_string()).await?.is_empty() {
+        return Err(AppError::NotFound(format!("No jobs configured for application '{}'", app_name)));
+    }
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use axum::extract::{Query, State};
+use validator::Validate;
+use crate::{SharedState};
+use crate::db::audit_repository::find_audit_logs;
+use crate::dtos::audit::{AuditLogDto, AuditLogQuery};
+use crate::errors::AppError;
+use watchdog_api::jsend::AppResponse;
+
+const DEFAULT_AUDIT_LOG_LIMIT: i64 = 100;
+
+pub async fn get_audit_logs_handler(
+    State(state): State<SharedState>,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<AppResponse<Vec<AuditLogDto>>, AppError> {
+    query.validate()?;
+
+    let mut conn = state.pool.get().await?;
+
+    let audit_logs = find_audit_logs(&mut conn, &query, query.limit.unwrap_or(DEFAULT_AUDIT_LOG_LIMIT)).await?;
+
+    Ok(AppResponse::success_one("audit-logs", audit_logs.into_iter().map(Into::into).collect()))
+}
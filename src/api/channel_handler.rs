@@ -5,11 +5,14 @@ use strum::IntoEnumIterator;
 use tracing::info;
 use validator::Validate;
 use crate::{SharedState};
-use crate::db::channel_repository::{get_all_channels, get_channel_by_name, insert_channel, save_channel};
-use crate::dtos::channel::{ChannelCreateRequest, ChannelResponseDto, ChannelUpdateRequest};
+use crate::api::actor::Actor;
+use crate::db::channel_repository::{get_all_channels, get_channel_by_name};
+use crate::db::revision_repository::{insert_channel_with_revision, save_channel_with_revision};
+use crate::dtos::channel::{ChannelCreateRequest, ChannelResponseDto, ChannelTestResultDto, ChannelUpdateRequest};
 use crate::errors::AppError;
-use crate::jsend::AppResponse;
-use crate::models::{NewChannel, ProviderType};
+use watchdog_api::jsend::AppResponse;
+use crate::models::{NewChannel, ProviderType, RevisionChangeType};
+use crate::time_utils::get_utc_now;
 
 pub async fn get_channel_by_id_handler(
     State(state): State<SharedState>,
@@ -20,7 +23,7 @@ pub async fn get_channel_by_id_handler(
     let channel_option = get_channel_by_name(&mut conn, &_name).await?;
 
     if let Some(_channel) = channel_option {
-        Ok(AppResponse::success_one("channel", _channel.into()))
+        Ok(AppResponse::success_one("channel", state.dispatcher.redact_channel(_channel).into()))
     } else {
         Err(AppError::NotFound(format!("Channel doesn't exists for id '{}'", _name)))
     }
@@ -28,13 +31,14 @@ pub async fn get_channel_by_id_handler(
 
 pub async fn create_channel_handler(
     State(state): State<SharedState>,
+    Actor(actor): Actor,
     Json(_create_request): Json<ChannelCreateRequest>,
 ) -> Result<AppResponse<ChannelResponseDto>, AppError> {
     _create_request.validate()?;
     let _config: Value = serde_json::from_str(&*_create_request.configuration)
         .map_err(|e| AppError::BadRequest(e.to_string()))?;
 
-    state.dispatcher.validate(&_create_request.provider_type, &_config).await?;
+    let _config = state.dispatcher.prepare_config(&_create_request.provider_type, _config, None).await?;
 
     info!("Creating channel with name: {}", _create_request.name);
 
@@ -52,22 +56,21 @@ pub async fn create_channel_handler(
         configuration: _config,
     };
 
-    let inserted = insert_channel(&mut conn, _new_channel).await?;
+    let inserted = insert_channel_with_revision(&mut conn, _new_channel, actor).await?;
 
-    Ok(AppResponse::success_one("channel", inserted.into()))
+    Ok(AppResponse::success_one("channel", state.dispatcher.redact_channel(inserted).into()))
 }
 
 pub async fn update_channel_handler(
     State(state): State<SharedState>,
     Path(_id): Path<String>,
+    Actor(actor): Actor,
     Json(_update_request): Json<ChannelUpdateRequest>,
 ) -> Result<AppResponse<ChannelResponseDto>, AppError> {
     _update_request.validate()?;
     let _config: Value = serde_json::from_str(&*_update_request.configuration)
         .map_err(|e| AppError::BadRequest(e.to_string()))?;
 
-    state.dispatcher.validate(&_update_request.provider_type, &_config).await?;
-
     info!("Updating channel with id: {}", _id);
 
     let mut conn = state.pool.get().await?;
@@ -76,14 +79,33 @@ pub async fn update_channel_handler(
         .await?
         .ok_or(AppError::NotFound(format!("Channel doesn't exists for id '{}'", _id)))?;
 
-    info!("{}", &_config);
+    let _config = state.dispatcher.prepare_config(&_update_request.provider_type, _config, Some(&_channel)).await?;
 
     _channel.provider_type = _update_request.provider_type;
     _channel.configuration = _config;
 
 
-    let updated = save_channel(&mut conn, _channel).await?;
-    Ok(AppResponse::success_one("channel", updated.into()))
+    let updated = save_channel_with_revision(&mut conn, _channel, RevisionChangeType::Updated, actor).await?;
+    Ok(AppResponse::success_one("channel", state.dispatcher.redact_channel(updated).into()))
+}
+
+/// Delivery failures are part of the result rather than an error response, so callers always see what the provider said.
+pub async fn test_channel_handler(
+    State(state): State<SharedState>,
+    Path(_id): Path<String>,
+) -> Result<AppResponse<ChannelTestResultDto>, AppError> {
+    let mut conn = state.pool.get().await?;
+
+    let _channel = get_channel_by_name(&mut conn, &_id)
+        .await?
+        .ok_or(AppError::NotFound(format!("Channel doesn't exists for id '{}'", _id)))?;
+
+    info!("Sending test alert via channel: {}", _id);
+
+    let tested_at = get_utc_now();
+    let result = state.dispatcher.send_test(&_channel).await;
+
+    Ok(AppResponse::success_one("test_result", ChannelTestResultDto::new(&_channel, result, tested_at)))
 }
 
 pub async fn get_all_channels_handler(
@@ -94,7 +116,7 @@ pub async fn get_all_channels_handler(
 
     let channels = get_all_channels(&mut conn).await?;
 
-    Ok(AppResponse::success_one("channels", channels.into_iter().map(Into::into).collect()))
+    Ok(AppResponse::success_one("channels", channels.into_iter().map(|c| state.dispatcher.redact_channel(c).into()).collect()))
 }
 
 pub async fn get_all_channel_providers_handler(
@@ -1,13 +1,27 @@
-use axum::extract::{Path, State};
+use std::collections::HashMap;
+use axum::extract::{Path, Query, State};
 use axum::Json;
 use tracing::info;
 use validator::Validate;
 use crate::{SharedState};
-use crate::db::config_repository::{get_all_applications, get_all_job_configs, get_job_config_by_app_name_and_job_name, get_jobs_by_application, insert_config, save_config};
-use crate::dtos::job_config::{JobConfigCreateRequest, JobConfigDto, JobConfigUpdateRequest};
+use crate::api::actor::Actor;
+use crate::core::job_stage_validations::check_stage_offsets;
+use crate::cron_utils::get_next_execution_times;
+use crate::db::connection::DbConnection;
+use crate::db::config_repository::{get_all_applications, get_all_job_configs, get_job_config_by_app_name_and_job_name, get_jobs_by_application};
+use crate::core::alert_silencing::get_effective_snooze;
+use crate::core::job_metadata::merge_metadata;
+use crate::db::application_repository::get_all_application_configs;
+use crate::db::revision_repository::{insert_config_with_revision, save_config_with_revision};
+use crate::db::snooze_repository::get_active_snoozes;
+use crate::dtos::job_config::{JobConfigCreateRequest, JobConfigDto, JobConfigQuery, JobConfigUpdateRequest};
+use crate::dtos::schedule::{ScheduledOccurrenceDto, SchedulePreviewRequest};
 use crate::errors::AppError;
-use crate::jsend::AppResponse;
-use crate::models::{NewJobConfig};
+use watchdog_api::jsend::AppResponse;
+use crate::models::{JobConfig, JobMetadata, NewJobConfig, RevisionChangeType};
+use crate::time_utils::{change_timezone, get_utc_now};
+
+const DEFAULT_PREVIEW_COUNT: usize = 5;
 
 pub async fn get_config_by_app_name_and_job_name_handler(
     State(state): State<SharedState>,
@@ -18,7 +32,8 @@ pub async fn get_config_by_app_name_and_job_name_handler(
     let job_config = get_job_config_by_app_name_and_job_name(&mut conn, &app_name, &job_name).await?;
 
     if let Some(_job_config) = job_config {
-        Ok(AppResponse::success_one("config", _job_config.into()))
+        let job_config_dto = with_snoozes(&mut conn, vec![_job_config]).await?.remove(0);
+        Ok(AppResponse::success_one("config", job_config_dto))
     } else {
         Err(AppError::NotFound(format!("Configuration doesn't exists for application '{}' and job '{}'", app_name, job_name)))
     }
@@ -26,9 +41,11 @@ pub async fn get_config_by_app_name_and_job_name_handler(
 
 pub async fn create_config_handler(
     State(state): State<SharedState>,
+    Actor(actor): Actor,
     Json(_create_request): Json<JobConfigCreateRequest>,
 ) -> Result<AppResponse<JobConfigDto>, AppError> {
     _create_request.validate()?;
+    check_stage_offsets(&_create_request.stages, max_stage_duration_hours(&state))?;
     info!("Creating config for job: {}-{}", _create_request.app_name, _create_request.job_name);
 
     let mut conn = state.pool.get().await?;
@@ -54,18 +71,23 @@ pub async fn create_config_handler(
         zone_id: _create_request.zone_id,
         stages: _create_request.stages,
         channel_ids: _create_request.channel_ids,
+        concurrency_policy: _create_request.concurrency_policy,
+        blackout_windows: _create_request.blackout_windows,
+        metadata: _create_request.metadata,
     };
     
-    let inserted = insert_config(&mut conn, _new_job_config).await?;
+    let inserted = insert_config_with_revision(&mut conn, _new_job_config, actor).await?;
     Ok(AppResponse::success_one("job-config", inserted.into()))
 }
 
 pub async fn update_config_handler(
     State(state): State<SharedState>,
     Path((app_name, job_name)): Path<(String, String)>,
+    Actor(actor): Actor,
     Json(_update_request): Json<JobConfigUpdateRequest>,
 ) -> Result<AppResponse<JobConfigDto>, AppError> {
     _update_request.validate()?;
+    check_stage_offsets(&_update_request.stages, max_stage_duration_hours(&state))?;
 
     info!("Updating config for job: {}-{}", app_name, job_name);
 
@@ -79,20 +101,31 @@ pub async fn update_config_handler(
     _job_config.zone_id = _update_request.zone_id;
     _job_config.stages = _update_request.stages;
     _job_config.channel_ids = _update_request.channel_ids;
+    _job_config.concurrency_policy = _update_request.concurrency_policy;
+    _job_config.blackout_windows = _update_request.blackout_windows;
+    _job_config.metadata = _update_request.metadata;
     
-    let updated = save_config(&mut conn, _job_config).await?;
+    let updated = save_config_with_revision(&mut conn, _job_config, RevisionChangeType::Updated, actor).await?;
     Ok(AppResponse::success_one("job-config", updated.into()))
 }
 
 pub async fn get_all_configs_handler(
     State(state): State<SharedState>,
+    Query(query): Query<JobConfigQuery>,
 ) -> Result<AppResponse<Vec<JobConfigDto>>, AppError> {
 
     let mut conn = state.pool.get().await?;
 
-    let jobs = get_all_job_configs(&mut conn).await?;
+    let application_metadata: HashMap<String, JobMetadata> = get_all_application_configs(&mut conn).await?
+        .into_iter()
+        .map(|application_config| (application_config.app_name, application_config.metadata.0))
+        .collect();
+    let jobs = get_all_job_configs(&mut conn).await?
+        .into_iter()
+        .filter(|job| query.matches(&merge_metadata(job.metadata.0.clone(), application_metadata.get(&job.app_name))))
+        .collect();
 
-    Ok(AppResponse::success_one("job-configs", jobs.into_iter().map(Into::into).collect()))
+    Ok(AppResponse::success_one("job-configs", with_snoozes(&mut conn, jobs).await?))
 }
 
 pub async fn get_all_applications_handler(
@@ -115,5 +148,48 @@ pub async fn list_jobs_by_app_handler(
 
     let jobs = get_jobs_by_application(&mut conn, app_name).await?;
 
-    Ok(AppResponse::success_one("job-configs", jobs.into_iter().map(Into::into).collect()))
-}
\ No newline at end of file
+    Ok(AppResponse::success_one("job-configs", with_snoozes(&mut conn, jobs).await?))
+}
+
+/// Converts the configs, adding the snooze currently silencing each job, its own or its application's.
+async fn with_snoozes(
+    conn: &mut DbConnection<'_>,
+    job_configs: Vec<JobConfig>,
+) -> Result<Vec<JobConfigDto>, AppError> {
+    let snoozes = get_active_snoozes(conn, get_utc_now()).await?;
+
+    Ok(job_configs.into_iter().map(|job_config| {
+        let job_snoozes = snoozes.iter()
+            .filter(|snooze| snooze.app_name == job_config.app_name)
+            .filter(|snooze| snooze.job_name.as_ref().is_none_or(|_job_name| *_job_name == job_config.job_name))
+            .cloned()
+            .collect();
+        let mut job_config_dto = JobConfigDto::from(job_config);
+        job_config_dto.snooze = get_effective_snooze(job_snoozes).map(Into::into);
+        job_config_dto
+    }).collect())
+}
+
+pub async fn preview_config_handler(
+    State(_state): State<SharedState>,
+    Json(_preview_request): Json<SchedulePreviewRequest>,
+) -> Result<AppResponse<Vec<ScheduledOccurrenceDto>>, AppError> {
+    _preview_request.validate()?;
+    check_stage_offsets(&_preview_request.stages, max_stage_duration_hours(&_state))?;
+
+    let from = _preview_request.from.unwrap_or_else(get_utc_now);
+    let tz_from = change_timezone(&from, &_preview_request.zone_id)?;
+    let count = _preview_request.count.unwrap_or(DEFAULT_PREVIEW_COUNT);
+
+    let occurrences = get_next_execution_times(&_preview_request.schedule, &tz_from, count)?
+        .iter()
+        .map(|start_time| ScheduledOccurrenceDto::new(start_time, &_preview_request.stages))
+        .collect();
+
+    Ok(AppResponse::success_one("occurrences", occurrences))
+}
+
+fn max_stage_duration_hours(state: &SharedState) -> i32 {
+    let _settings = state.settings.read().expect("Lock poisoned");
+    _settings.max_stage_duration_hours
+}
@@ -1,6 +1,61 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use axum::extract::State;
 use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use diesel_async::RunQueryDsl;
+use crate::SharedState;
+use crate::db::connection::PgPool;
+use crate::dtos::health::{DatabaseHealthDto, HealthDto, HealthStatus};
+use crate::errors::AppError;
+use watchdog_api::jsend::AppResponse;
+use crate::time_utils::get_utc_now;
 
-pub async fn health_check_handler() -> impl IntoResponse {
-    (StatusCode::OK, ())
+const DATABASE_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Responds with 503 when the database is unreachable or the scheduler has stalled, so load balancers and
+/// orchestrators can act on it; the details are returned either way.
+pub async fn health_check_handler(
+    State(state): State<SharedState>,
+) -> AppResponse<HealthDto> {
+    let database = check_database(&state.pool).await;
+
+    let checked_at = get_utc_now();
+    let health = state.health.read().expect("Lock poisoned").clone();
+    let scheduler_stalled = health.is_scheduler_stalled(
+        state.config.scheduler_initial_delay_seconds,
+        state.config.scheduler_fixed_delay_seconds,
+        checked_at,
+    );
+
+    let health = HealthDto::new(health, scheduler_stalled, database, checked_at);
+    if health.status != HealthStatus::Unhealthy {
+        return AppResponse::success_one("health", health);
+    }
+
+    let mut error_data = HashMap::new();
+    error_data.insert("health".to_string(), serde_json::to_value(&health).unwrap_or_default());
+    AppResponse::error_with_data("service is unhealthy", Some(StatusCode::SERVICE_UNAVAILABLE.as_u16() as i32), error_data)
+}
+
+async fn check_database(pool: &PgPool) -> DatabaseHealthDto {
+    let started_at = get_utc_now();
+    let result = tokio::time::timeout(DATABASE_CHECK_TIMEOUT, async {
+        let mut conn = pool.get().await?;
+        diesel::sql_query("SELECT 1").execute(&mut conn).await?;
+        Ok::<(), AppError>(())
+    }).await;
+
+    match result {
+        Ok(Ok(())) => DatabaseHealthDto {
+            connected: true,
+            latency_ms: Some((get_utc_now() - started_at).num_milliseconds()),
+            error: None,
+        },
+        Ok(Err(e)) => DatabaseHealthDto { connected: false, latency_ms: None, error: Some(e.message().to_string()) },
+        Err(_) => DatabaseHealthDto {
+            connected: false,
+            latency_ms: None,
+            error: Some(format!("no response within {}secs", DATABASE_CHECK_TIMEOUT.as_secs())),
+        },
+    }
 }
@@ -3,4 +3,12 @@ pub mod config_handler;
 pub mod event_handler;
 pub mod run_handler;
 pub mod channel_handler;
-pub mod settings_handler;
\ No newline at end of file
+pub mod settings_handler;
+pub mod schedule_handler;
+pub mod actor;
+pub mod revision_handler;
+pub mod audit_handler;
+pub mod run_event_handler;
+pub mod snooze_handler;
+pub mod application_handler;
+pub mod routing_rule_handler;
\ No newline at end of file
@@ -0,0 +1,218 @@
+use axum::extract::{Path, Query, State};
+use serde_json::Value;
+use tracing::info;
+use validator::Validate;
+use crate::{SharedState};
+use crate::api::actor::Actor;
+use crate::core::channel_secrets::redact_snapshot;
+use crate::core::job_stage_validations::check_stage_offsets;
+use crate::db::channel_repository::get_channel_by_name;
+use crate::db::config_repository::get_job_config_by_app_name_and_job_name;
+use crate::db::connection::DbConnection;
+use crate::db::revision_repository::{get_channel_revision, get_channel_revisions, get_job_config_revision, get_job_config_revisions, save_channel_with_revision, save_config_with_revision};
+use crate::diff_utils::{diff_json, FieldChange};
+use crate::dtos::channel::{ChannelResponseDto, ChannelSnapshot};
+use crate::dtos::job_config::{JobConfigDto, JobConfigUpdateRequest};
+use crate::dtos::revision::{RevisionDiffDto, RevisionDiffQuery, RevisionDto};
+use crate::errors::AppError;
+use watchdog_api::jsend::AppResponse;
+use crate::models::{ChannelRevision, JobConfigRevision, RevisionChangeType};
+use crate::secrets::REDACTED;
+
+pub async fn get_config_revisions_handler(
+    State(state): State<SharedState>,
+    Path((app_name, job_name)): Path<(String, String)>,
+) -> Result<AppResponse<Vec<RevisionDto>>, AppError> {
+    let mut conn = state.pool.get().await?;
+
+    let revisions = get_job_config_revisions(&mut conn, &app_name, &job_name).await?;
+
+    Ok(AppResponse::success_one("revisions", revisions.into_iter().map(Into::into).collect()))
+}
+
+pub async fn get_config_revision_handler(
+    State(state): State<SharedState>,
+    Path((app_name, job_name, revision)): Path<(String, String, i32)>,
+) -> Result<AppResponse<RevisionDto>, AppError> {
+    let mut conn = state.pool.get().await?;
+
+    let _revision = _get_job_config_revision(&mut conn, &app_name, &job_name, revision).await?;
+
+    Ok(AppResponse::success_one("revision", _revision.into()))
+}
+
+pub async fn diff_config_revisions_handler(
+    State(state): State<SharedState>,
+    Path((app_name, job_name)): Path<(String, String)>,
+    Query(query): Query<RevisionDiffQuery>,
+) -> Result<AppResponse<RevisionDiffDto>, AppError> {
+    let mut conn = state.pool.get().await?;
+
+    let from = _get_job_config_revision(&mut conn, &app_name, &job_name, query.from).await?;
+    let to = _get_job_config_revision(&mut conn, &app_name, &job_name, query.to).await?;
+
+    Ok(AppResponse::success_one("diff", RevisionDiffDto {
+        from_revision: from.revision,
+        to_revision: to.revision,
+        changes: diff_json(&from.snapshot, &to.snapshot),
+    }))
+}
+
+pub async fn rollback_config_handler(
+    State(state): State<SharedState>,
+    Path((app_name, job_name, revision)): Path<(String, String, i32)>,
+    Actor(actor): Actor,
+) -> Result<AppResponse<JobConfigDto>, AppError> {
+    info!("Rolling back config for job: {}-{} to revision {}", app_name, job_name, revision);
+
+    let mut conn = state.pool.get().await?;
+
+    let _revision = _get_job_config_revision(&mut conn, &app_name, &job_name, revision).await?;
+    let snapshot: JobConfigDto = serde_json::from_value(_revision.snapshot)
+        .map_err(|e| AppError::InternalError(format!("invalid snapshot for revision {}: {}", revision, e)))?;
+
+    // The snapshot has to satisfy today's rules, not just the ones in place when it was taken
+    let _update_request = JobConfigUpdateRequest {
+        schedule: snapshot.schedule,
+        zone_id: snapshot.zone_id,
+        stages: snapshot.stages,
+        channel_ids: snapshot.channel_ids,
+        concurrency_policy: snapshot.concurrency_policy,
+        blackout_windows: snapshot.blackout_windows,
+        metadata: snapshot.metadata,
+    };
+    _update_request.validate()?;
+    let max_stage_duration_hours = {
+        let _settings = state.settings.read().expect("Lock poisoned");
+        _settings.max_stage_duration_hours
+    };
+    check_stage_offsets(&_update_request.stages, max_stage_duration_hours)?;
+
+    let mut _job_config = get_job_config_by_app_name_and_job_name(&mut conn, &app_name, &job_name)
+        .await?
+        .ok_or(AppError::NotFound(format!("JobConfig doesn't exists for app_name '{}' and job name '{}'", app_name, job_name)))?;
+
+    _job_config.schedule = _update_request.schedule;
+    _job_config.zone_id = _update_request.zone_id;
+    _job_config.enabled = snapshot.enabled;
+    _job_config.stages = _update_request.stages;
+    _job_config.channel_ids = _update_request.channel_ids;
+    _job_config.concurrency_policy = _update_request.concurrency_policy;
+    _job_config.blackout_windows = _update_request.blackout_windows;
+    _job_config.metadata = _update_request.metadata;
+
+    let updated = save_config_with_revision(&mut conn, _job_config, RevisionChangeType::RolledBack, actor).await?;
+
+    Ok(AppResponse::success_one("job-config", updated.into()))
+}
+
+pub async fn get_channel_revisions_handler(
+    State(state): State<SharedState>,
+    Path(_name): Path<String>,
+) -> Result<AppResponse<Vec<RevisionDto>>, AppError> {
+    let mut conn = state.pool.get().await?;
+
+    let revisions = get_channel_revisions(&mut conn, &_name).await?;
+
+    Ok(AppResponse::success_one("revisions", revisions.into_iter().map(|r| _redacted_channel_revision(&state, r)).collect()))
+}
+
+pub async fn get_channel_revision_handler(
+    State(state): State<SharedState>,
+    Path((_name, revision)): Path<(String, i32)>,
+) -> Result<AppResponse<RevisionDto>, AppError> {
+    let mut conn = state.pool.get().await?;
+
+    let _revision = _get_channel_revision(&mut conn, &_name, revision).await?;
+
+    Ok(AppResponse::success_one("revision", _redacted_channel_revision(&state, _revision)))
+}
+
+pub async fn diff_channel_revisions_handler(
+    State(state): State<SharedState>,
+    Path(_name): Path<String>,
+    Query(query): Query<RevisionDiffQuery>,
+) -> Result<AppResponse<RevisionDiffDto>, AppError> {
+    let mut conn = state.pool.get().await?;
+
+    let from = _get_channel_revision(&mut conn, &_name, query.from).await?;
+    let to = _get_channel_revision(&mut conn, &_name, query.to).await?;
+
+    Ok(AppResponse::success_one("diff", RevisionDiffDto {
+        from_revision: from.revision,
+        to_revision: to.revision,
+        changes: _diff_channel_snapshots(&state, &from.snapshot, &to.snapshot)?,
+    }))
+}
+
+pub async fn rollback_channel_handler(
+    State(state): State<SharedState>,
+    Path((_name, revision)): Path<(String, i32)>,
+    Actor(actor): Actor,
+) -> Result<AppResponse<ChannelResponseDto>, AppError> {
+    info!("Rolling back channel {} to revision {}", _name, revision);
+
+    let mut conn = state.pool.get().await?;
+
+    let _revision = _get_channel_revision(&mut conn, &_name, revision).await?;
+    let snapshot: ChannelSnapshot = serde_json::from_value(_revision.snapshot)
+        .map_err(|e| AppError::InternalError(format!("invalid snapshot for revision {}: {}", revision, e)))?;
+
+    let configuration = state.dispatcher.prepare_config(&snapshot.provider_type, snapshot.configuration, None).await?;
+
+    let mut _channel = get_channel_by_name(&mut conn, &_name)
+        .await?
+        .ok_or(AppError::NotFound(format!("Channel doesn't exists for id '{}'", _name)))?;
+
+    _channel.provider_type = snapshot.provider_type;
+    _channel.configuration = configuration;
+
+    let updated = save_channel_with_revision(&mut conn, _channel, RevisionChangeType::RolledBack, actor).await?;
+
+    Ok(AppResponse::success_one("channel", state.dispatcher.redact_channel(updated).into()))
+}
+
+async fn _get_job_config_revision(conn: &mut DbConnection<'_>, app_name: &str, job_name: &str, revision: i32) -> Result<JobConfigRevision, AppError> {
+    get_job_config_revision(conn, app_name, job_name, revision)
+        .await?
+        .ok_or(AppError::NotFound(format!("revision {} not found for: {}-{}", revision, app_name, job_name)))
+}
+
+async fn _get_channel_revision(conn: &mut DbConnection<'_>, channel_name: &str, revision: i32) -> Result<ChannelRevision, AppError> {
+    get_channel_revision(conn, channel_name, revision)
+        .await?
+        .ok_or(AppError::NotFound(format!("revision {} not found for channel: {}", revision, channel_name)))
+}
+
+fn _redacted_channel_revision(state: &SharedState, revision: ChannelRevision) -> RevisionDto {
+    let snapshot = redact_snapshot(&state.dispatcher, &revision.snapshot);
+    RevisionDto { snapshot, ..revision.into() }
+}
+
+/// Diffs the decrypted snapshots, so re-encrypting an unchanged secret is not reported,
+/// then redacts the values of any secret that did change.
+fn _diff_channel_snapshots(state: &SharedState, from: &Value, to: &Value) -> Result<Vec<FieldChange>, AppError> {
+    let mut secret_paths = vec![];
+    let mut unsealed = vec![];
+    for snapshot in [from, to] {
+        let mut channel_snapshot: ChannelSnapshot = serde_json::from_value(snapshot.clone())
+            .map_err(|e| AppError::InternalError(format!("invalid channel snapshot: {}", e)))?;
+        channel_snapshot.configuration = state.dispatcher.unseal_config(&channel_snapshot.provider_type, &channel_snapshot.configuration)?;
+        secret_paths.extend(state.dispatcher.secret_fields(&channel_snapshot.provider_type).iter().map(|field| format!("configuration.{}", field)));
+        unsealed.push(serde_json::to_value(channel_snapshot).unwrap_or(Value::Null));
+    }
+
+    let is_secret = |path: &str| secret_paths.iter().any(|secret_path| {
+        path.strip_prefix(secret_path.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with('.') || rest.starts_with('['))
+    });
+    let redacted = |value: Option<Value>| value.filter(|v| !v.is_null()).map(|_| Value::String(REDACTED.to_string()));
+
+    Ok(diff_json(&unsealed[0], &unsealed[1])
+        .into_iter()
+        .map(|change| if is_secret(&change.path) {
+            FieldChange { from: redacted(change.from), to: redacted(change.to), ..change }
+        } else {
+            change
+        })
+        .collect())
+}
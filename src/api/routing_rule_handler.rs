@@ -0,0 +1,150 @@
+use axum::extract::{Path, State};
+use axum::Json;
+use tracing::info;
+use validator::Validate;
+use crate::{SharedState};
+use crate::core::alert_routing::{route_alert, RoutedAlert};
+use crate::core::job_metadata::get_job_metadata;
+use crate::db::config_repository::get_job_config_by_app_name_and_job_name;
+use crate::db::routing_rule_repository::{delete_routing_rule, get_all_routing_rules, get_enabled_routing_rules, get_routing_rule_by_name, insert_routing_rule, save_routing_rule};
+use crate::dtos::routing_rule::{RoutingRuleCreateRequest, RoutingRuleDto, RoutingRuleUpdateRequest, RoutingTestRequest, RoutingTestResultDto};
+use crate::errors::AppError;
+use watchdog_api::jsend::AppResponse;
+use crate::models::{NewRoutingRule, Severity};
+use crate::notification::core::{get_stage_severity, AlertType};
+use crate::time_utils::get_utc_now;
+
+pub async fn get_all_routing_rules_handler(
+    State(state): State<SharedState>,
+) -> Result<AppResponse<Vec<RoutingRuleDto>>, AppError> {
+    let mut conn = state.pool.get().await?;
+
+    let routing_rules = get_all_routing_rules(&mut conn).await?;
+
+    Ok(AppResponse::success_one("routing-rules", routing_rules.into_iter().map(Into::into).collect()))
+}
+
+pub async fn get_routing_rule_handler(
+    State(state): State<SharedState>,
+    Path(_name): Path<String>,
+) -> Result<AppResponse<RoutingRuleDto>, AppError> {
+    let mut conn = state.pool.get().await?;
+
+    let routing_rule = get_routing_rule_by_name(&mut conn, &_name)
+        .await?
+        .ok_or(AppError::NotFound(format!("Routing rule doesn't exists for name '{}'", _name)))?;
+
+    Ok(AppResponse::success_one("routing-rule", routing_rule.into()))
+}
+
+pub async fn create_routing_rule_handler(
+    State(state): State<SharedState>,
+    Json(_create_request): Json<RoutingRuleCreateRequest>,
+) -> Result<AppResponse<RoutingRuleDto>, AppError> {
+    _create_request.validate()?;
+
+    info!("Creating routing rule with name: {}", _create_request.name);
+
+    let mut conn = state.pool.get().await?;
+
+    if get_routing_rule_by_name(&mut conn, &_create_request.name).await?.is_some() {
+        return Err(AppError::Conflict(format!("Routing rule already exists for name '{}'", _create_request.name)));
+    }
+
+    let _new_routing_rule = NewRoutingRule {
+        name: _create_request.name,
+        priority: _create_request.priority,
+        enabled: _create_request.enabled.unwrap_or(true),
+        matcher: _create_request.matcher,
+        channel_ids: _create_request.channel_ids,
+        continue_matching: _create_request.continue_matching,
+    };
+
+    let inserted = insert_routing_rule(&mut conn, _new_routing_rule).await?;
+    Ok(AppResponse::success_one("routing-rule", inserted.into()))
+}
+
+pub async fn update_routing_rule_handler(
+    State(state): State<SharedState>,
+    Path(_name): Path<String>,
+    Json(_update_request): Json<RoutingRuleUpdateRequest>,
+) -> Result<AppResponse<RoutingRuleDto>, AppError> {
+    _update_request.validate()?;
+
+    info!("Updating routing rule with name: {}", _name);
+
+    let mut conn = state.pool.get().await?;
+
+    let mut _routing_rule = get_routing_rule_by_name(&mut conn, &_name)
+        .await?
+        .ok_or(AppError::NotFound(format!("Routing rule doesn't exists for name '{}'", _name)))?;
+
+    _routing_rule.priority = _update_request.priority;
+    _routing_rule.enabled = _update_request.enabled.unwrap_or(_routing_rule.enabled);
+    _routing_rule.matcher = _update_request.matcher;
+    _routing_rule.channel_ids = _update_request.channel_ids;
+    _routing_rule.continue_matching = _update_request.continue_matching;
+
+    let updated = save_routing_rule(&mut conn, _routing_rule).await?;
+    Ok(AppResponse::success_one("routing-rule", updated.into()))
+}
+
+pub async fn delete_routing_rule_handler(
+    State(state): State<SharedState>,
+    Path(_name): Path<String>,
+) -> Result<AppResponse<RoutingRuleDto>, AppError> {
+    info!("Deleting routing rule with name: {}", _name);
+
+    let mut conn = state.pool.get().await?;
+
+    let routing_rule = get_routing_rule_by_name(&mut conn, &_name)
+        .await?
+        .ok_or(AppError::NotFound(format!("Routing rule doesn't exists for name '{}'", _name)))?;
+    delete_routing_rule(&mut conn, &_name).await?;
+
+    Ok(AppResponse::success_one("routing-rule", routing_rule.into()))
+}
+
+/// Routes a hypothetical alert the way the dispatcher would, without sending anything.
+pub async fn test_routing_handler(
+    State(state): State<SharedState>,
+    Json(_test_request): Json<RoutingTestRequest>,
+) -> Result<AppResponse<RoutingTestResultDto>, AppError> {
+    let mut conn = state.pool.get().await?;
+
+    let job_config = get_job_config_by_app_name_and_job_name(&mut conn, &_test_request.app_name, &_test_request.job_name).await?;
+
+    // Errors go to the error channels unless a rule routes them, everything else to the job's channels
+    let default_channel_ids = if _test_request.alert_type == AlertType::Error {
+        let _settings = state.settings.read().expect("Lock poisoned");
+        _settings.error_channels.clone()
+    } else {
+        job_config.as_ref().map(|job_config| job_config.channel_ids.clone()).unwrap_or_default()
+    };
+    let severity = _test_request.severity.unwrap_or_else(|| match _test_request.alert_type {
+        AlertType::Error => Severity::Warning,
+        AlertType::Test => Severity::Info,
+        AlertType::Timeout | AlertType::Failed => job_config.as_ref()
+            .map(|job_config| get_stage_severity(job_config, &_test_request.stage_name))
+            .unwrap_or_default(),
+    });
+
+    let mut metadata = get_job_metadata(&mut conn, &_test_request.app_name, &_test_request.job_name).await?;
+    if let Some(tags) = _test_request.tags {
+        metadata.tags = tags;
+    }
+
+    let at = _test_request.at.unwrap_or_else(get_utc_now);
+    let alert = RoutedAlert {
+        app_name: &_test_request.app_name,
+        job_name: &_test_request.job_name,
+        stage_name: &_test_request.stage_name,
+        alert_type: &_test_request.alert_type,
+        severity,
+        metadata: &metadata,
+        at,
+    };
+    let rules = get_enabled_routing_rules(&mut conn).await?;
+
+    Ok(AppResponse::success_one("routing", RoutingTestResultDto::new(route_alert(&rules, &alert, &default_channel_ids), at)))
+}
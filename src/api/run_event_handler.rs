@@ -0,0 +1,69 @@
+use std::convert::Infallible;
+use axum::extract::{Path, Query, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures::{Stream, StreamExt};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{error, warn};
+use uuid::Uuid;
+use crate::{SharedState};
+use crate::core::run_timeline::get_stage_attempts;
+use crate::db::run_event_repository::get_job_run_events;
+use crate::db::run_repository::get_job_run_by_id;
+use crate::dtos::run_event::{JobRunEventDto, JobRunTimelineDto, RunEventFilter};
+use crate::errors::AppError;
+use watchdog_api::jsend::AppResponse;
+
+/// Streams run lifecycle events as server-sent events, optionally narrowed to one app or job.
+/// The stream ends when the server shuts down.
+pub async fn stream_run_events_handler(
+    State(state): State<SharedState>,
+    Query(filter): Query<RunEventFilter>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let receiver = state.run_events.subscribe();
+
+    let stream = futures::stream::unfold((receiver, filter), |(mut receiver, filter)| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(run_event) if filter.matches(&run_event) => {
+                    let event = match Event::default().event(run_event.event_type.to_string()).json_data(&run_event) {
+                        Ok(event) => event,
+                        Err(e) => {
+                            error!("Failed to serialize run event {:?}: {}", run_event, e);
+                            continue;
+                        }
+                    };
+                    return Some((Ok(event), (receiver, filter)));
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Run event subscriber lagged, skipped {} events", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+    // Open streams would otherwise hold up graceful shutdown forever
+    .take_until(state.shutdown.clone().cancelled_owned());
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// The events recorded for the run, oldest first, with the attempts of its stages derived from them.
+pub async fn get_run_events_handler(
+    State(state): State<SharedState>,
+    Path(_run_id): Path<Uuid>,
+) -> Result<AppResponse<JobRunTimelineDto>, AppError> {
+    let mut conn = state.pool.get().await?;
+
+    if get_job_run_by_id(&mut conn, &_run_id).await?.is_none() {
+        return Err(AppError::NotFound(format!("Run instance doesn't exists for id '{}'", _run_id)));
+    }
+
+    let events: Vec<JobRunEventDto> = get_job_run_events(&mut conn, &_run_id).await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    let stages = get_stage_attempts(&events);
+    Ok(AppResponse::success_one("timeline", JobRunTimelineDto { job_run_id: _run_id.to_string(), events, stages }))
+}
@@ -1,34 +1,43 @@
 use std::cmp::PartialEq;
 use axum::extract::{Path, State};
+use axum::body::Bytes;
 use axum::Json;
-use chrono::{DateTime, Utc};
-use serde::{Deserialize};
-use tracing::error;
+use chrono::{DateTime, Duration, Utc};
+use diesel_async::AsyncConnection;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use tracing::{error, info};
 use uuid::Uuid;
 use crate::{SharedState};
-use crate::core::job_run_matching::get_status;
+use crate::core::concurrency::{start_job_run, RunOrigin};
+use crate::core::job_run_matching::{find_run_for_stage_event, get_attempt, get_event_attempt, get_status, is_finished_by_latest_stage};
 use crate::core::job_stage_validations::check;
-use crate::cron_utils::get_job_start_time;
-use crate::db::config_repository::{get_job_config_by_app_name_and_job_name, save_config};
+use crate::core::run_closing::{close_job_run, close_occurrence};
+use crate::core::run_timeline::get_run_stage;
+use crate::cron_utils::{get_next_execution_times, get_run_occurrence};
+use crate::db::config_repository::get_job_config_by_app_name_and_job_name;
 use crate::db::connection::DbConnection;
-use crate::db::run_repository::{create_new_job_run, get_all_runs_top_100, get_job_run_by_id, get_latest_job_run_by_app_name_and_job_name, save_run};
+use crate::db::revision_repository::save_config_with_revision;
+use crate::db::run_repository::{get_all_runs_top_100, get_in_flight_job_runs, get_job_run_by_id, get_job_run_by_id_for_update, get_job_run_by_key, get_job_runs_of_occurrence, save_run, save_run_with_events};
 use crate::dtos::job_run::JobRunDto;
+use crate::dtos::run_event::RunEvent;
 use crate::errors::AppError;
-use crate::jsend::AppResponse;
-use crate::models::{JobConfig, JobRun, JobRunStage, JobRunStageStatus};
-use crate::notification::core::{_handle_error, send_failed};
+use watchdog_api::jsend::AppResponse;
+use watchdog_api::job_run::{CloseOccurrencePayload, CloseRunPayload, JobRunStageEventType, StageUpdatePayload, TriggerPayload};
+use crate::models::{JobConfig, JobRun, JobRunStageStatus, JobRunStatus, RevisionChangeType, RunEventSource, RunEventType};
+use crate::notification::core::{_handle_error, send_failed, send_resolved};
+use crate::pubsub::publish_run_event;
 use crate::time_utils::{change_timezone, change_to_utc, get_utc_now};
 
 pub async fn get_run_by_id_handler(
     State(state): State<SharedState>,
     Path(_run_id): Path<Uuid>,
-) -> Result<AppResponse<JobRun>, AppError> {
+) -> Result<AppResponse<JobRunDto>, AppError> {
     let mut conn = state.pool.get().await?;
 
     let job_run_option = get_job_run_by_id(&mut conn, &_run_id).await?;
 
     if let Some(_job_run) = job_run_option {
-        Ok(AppResponse::success_one("job-run", _job_run))
+        Ok(AppResponse::success_one("job-run", _job_run.into()))
     } else {
         Err(AppError::NotFound(format!("Run instance doesn't exists for id '{}'", _run_id)))
     }
@@ -45,12 +54,33 @@ pub async fn get_all_runs_handler(
     Ok(AppResponse::success_one("job-runs", job_runs.into_iter().map(Into::into).collect()))
 }
 
+pub async fn get_run_by_key_handler(
+    State(state): State<SharedState>,
+    Path((app_name, job_name, _run_key)): Path<(String, String, String)>,
+) -> Result<AppResponse<JobRunDto>, AppError> {
+    let mut conn = state.pool.get().await?;
+
+    let job_run_option = get_job_run_by_key(&mut conn, &app_name, &job_name, &_run_key).await?;
+
+    if let Some(_job_run) = job_run_option {
+        Ok(AppResponse::success_one("job-run", _job_run.into()))
+    } else {
+        Err(AppError::NotFound(format!("Run instance doesn't exists for key '{}' of job {}-{}", _run_key, app_name, job_name)))
+    }
+}
+
 pub async fn trigger_job_handler(
     State(state): State<SharedState>,
     Path((app_name, job_name)): Path<(String, String)>,
-    // Optional: Trigger might need a body too (e.g., manual inputs)
-    // Json(params): Json<TriggerParams>,
-) -> Result<AppResponse<JobRun>, AppError> {
+    body: Bytes,
+) -> Result<AppResponse<JobRunDto>, AppError> {
+    // The body is optional, triggers without a key don't need one
+    let payload: TriggerPayload = if body.is_empty() {
+        TriggerPayload::default()
+    } else {
+        serde_json::from_slice(&body).map_err(|e| AppError::BadRequest(format!("invalid trigger payload: {}", e)))?
+    };
+    let run_key_option = RunKey::from_payload(payload.run_key, payload.logical_date)?;
 
     let mut conn = state.pool.get().await?;
 
@@ -60,42 +90,109 @@ pub async fn trigger_job_handler(
         return Err(AppError::NotFound(format!("resource not found for -{} {}", &app_name, &job_name)))
     }
 
-    // let job_config = job_config_option.unwrap();
+    let job_config = job_config_option.unwrap();
+    let utc_now = get_utc_now();
 
-    let new_job_run = create_new_job_run(&mut conn, &app_name, &job_name).await?;
+    if let Some(run_key) = run_key_option {
+        let job_run = _get_or_start_keyed_run(&state, &mut conn, &job_config, run_key, utc_now).await?;
+        return Ok(AppResponse::success_one("job-run", job_run.into()));
+    }
+
+    let scheduled_for = get_scheduled_for(&state, &job_config, &utc_now)?;
+    let in_flight_runs = get_in_flight_job_runs(&mut conn, &app_name, &job_name, in_flight_since(&state, &utc_now)).await?;
+
+    // The scheduler may already expect a run for the occurrence, which this trigger starts
+    if let Some(expected_run) = find_expected_run(&in_flight_runs, scheduled_for) {
+        return Ok(AppResponse::success_one("job-run", expected_run.clone().into()));
+    }
+
+    let new_job_run = start_job_run(&mut conn, &state.dispatcher, &job_config, &in_flight_runs, scheduled_for, None, RunOrigin::Api).await?;
 
-    Ok(AppResponse::success_one("job-run", new_job_run))
+    Ok(AppResponse::success_one("job-run", new_job_run.into()))
 }
 
-#[derive(Deserialize, Debug)] // Add Clone/Serialize if needed
-pub enum JobRunStageEventType {
-    #[serde(rename = "started")]
-    Started,
-    #[serde(rename = "completed")]
-    Completed,
-    #[serde(rename = "failed")]
-    Failed,
+pub async fn skip_run_handler(
+    State(state): State<SharedState>,
+    Path(job_run_id): Path<Uuid>,
+    Json(payload): Json<CloseRunPayload>,
+) -> Result<AppResponse<JobRunDto>, AppError> {
+    _close_run_by_id(state, job_run_id, JobRunStatus::Skipped, payload.reason).await
 }
-#[derive(Deserialize, Debug)]
-pub struct StageUpdatePayload {
-    pub stage_name: String,
-    pub event_type: JobRunStageEventType,
-    pub message: Option<String>, // Optional: Good for error messages on failure
+
+pub async fn cancel_run_handler(
+    State(state): State<SharedState>,
+    Path(job_run_id): Path<Uuid>,
+    Json(payload): Json<CloseRunPayload>,
+) -> Result<AppResponse<JobRunDto>, AppError> {
+    _close_run_by_id(state, job_run_id, JobRunStatus::Cancelled, payload.reason).await
 }
+
+pub async fn skip_occurrence_handler(
+    State(state): State<SharedState>,
+    Path((app_name, job_name)): Path<(String, String)>,
+    Json(payload): Json<CloseOccurrencePayload>,
+) -> Result<AppResponse<JobRunDto>, AppError> {
+    _close_occurrence(state, (app_name, job_name), JobRunStatus::Skipped, payload).await
+}
+
+pub async fn cancel_occurrence_handler(
+    State(state): State<SharedState>,
+    Path((app_name, job_name)): Path<(String, String)>,
+    Json(payload): Json<CloseOccurrencePayload>,
+) -> Result<AppResponse<JobRunDto>, AppError> {
+    _close_occurrence(state, (app_name, job_name), JobRunStatus::Cancelled, payload).await
+}
+
+async fn _close_run_by_id(state: SharedState, job_run_id: Uuid, status: JobRunStatus, reason: String) -> Result<AppResponse<JobRunDto>, AppError> {
+    let reason = check_reason(reason)?;
+    let mut conn = state.pool.get().await?;
+
+    let job_run = _get_job_run_by_id(&mut conn, &job_run_id).await?;
+    let closed = close_job_run(&mut conn, job_run, status, &reason).await?;
+
+    Ok(AppResponse::success_one("job-run", closed.into()))
+}
+
+async fn _close_occurrence(
+    state: SharedState,
+    (app_name, job_name): (String, String),
+    status: JobRunStatus,
+    payload: CloseOccurrencePayload,
+) -> Result<AppResponse<JobRunDto>, AppError> {
+    let reason = check_reason(payload.reason)?;
+    let mut conn = state.pool.get().await?;
+
+    let job_config = _get_job_config_by_app_name_and_job_name(&mut conn, &app_name, &job_name).await?;
+    let closed = close_occurrence(&mut conn, &job_config, payload.scheduled_for, state.config.grace_time_seconds, status, &reason).await?;
+
+    Ok(AppResponse::success_one("job-run", closed.into()))
+}
+
+fn check_reason(reason: String) -> Result<String, AppError> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::BadRequest("a reason is required to skip or cancel a run".to_string()));
+    }
+    Ok(reason.to_string())
+}
+
 pub async fn update_stage_by_id_handler(
     State(state): State<SharedState>,
-    Path(job_run_id): Path<String>,
+    Path(job_run_id): Path<Uuid>,
     Json(payload): Json<StageUpdatePayload>,
-) -> Result<AppResponse<JobRun>, AppError> {
+) -> Result<AppResponse<JobRunDto>, AppError> {
     match payload.event_type {
         JobRunStageEventType::Started => {
-            _job_run_start_handler(state, (None, Some(job_run_id.parse().unwrap()), payload.stage_name)).await
+            _job_run_start_handler(state, (None, Some(job_run_id), payload.stage_name), None, payload.attempt, payload.message).await
         },
         JobRunStageEventType::Completed => {
-            _job_run_complete_handler(state, (None, Some(job_run_id.parse().unwrap()), payload.stage_name)).await
+            _job_run_complete_handler(state, (None, Some(job_run_id), payload.stage_name), None, payload.attempt, payload.message).await
         },
         JobRunStageEventType::Failed => {
-            _job_run_failed_handler(state, (None, Some(job_run_id.parse().unwrap()), payload.stage_name), payload.message).await
+            _job_run_failed_handler(state, (None, Some(job_run_id), payload.stage_name), None, payload.attempt, JobRunStageType::Failed, payload.message).await
+        },
+        JobRunStageEventType::Retrying => {
+            _job_run_failed_handler(state, (None, Some(job_run_id), payload.stage_name), None, payload.attempt, JobRunStageType::Retrying, payload.message).await
         },
     }
 }
@@ -104,44 +201,65 @@ pub async fn update_stage_by_context_handler(
     State(state): State<SharedState>,
     Path((app_name, job_name)): Path<(String, String)>,
     Json(payload): Json<StageUpdatePayload>,
-) -> Result<AppResponse<JobRun>, AppError> {
+) -> Result<AppResponse<JobRunDto>, AppError> {
+    let run_key_option = RunKey::from_payload(payload.run_key, payload.logical_date)?;
     match payload.event_type {
         JobRunStageEventType::Started => {
-            _job_run_start_handler(state, (Some((app_name, job_name)), None, payload.stage_name)).await
+            _job_run_start_handler(state, (Some((app_name, job_name)), None, payload.stage_name), run_key_option, payload.attempt, payload.message).await
         },
         JobRunStageEventType::Completed => {
-            _job_run_complete_handler(state, (Some((app_name, job_name)), None, payload.stage_name)).await
+            _job_run_complete_handler(state, (Some((app_name, job_name)), None, payload.stage_name), run_key_option, payload.attempt, payload.message).await
         },
         JobRunStageEventType::Failed => {
-            _job_run_failed_handler(state, (Some((app_name, job_name)), None, payload.stage_name), payload.message).await
+            _job_run_failed_handler(state, (Some((app_name, job_name)), None, payload.stage_name), run_key_option, payload.attempt, JobRunStageType::Failed, payload.message).await
+        },
+        JobRunStageEventType::Retrying => {
+            _job_run_failed_handler(state, (Some((app_name, job_name)), None, payload.stage_name), run_key_option, payload.attempt, JobRunStageType::Retrying, payload.message).await
         },
     }
 }
 async fn _job_run_start_handler(
     state: SharedState,
     (app_name_and_job_name_option, job_run_id_option, stage_name): (Option<(String, String)>, Option<Uuid>, String),
-) -> Result<AppResponse<JobRun>, AppError> {
+    run_key_option: Option<RunKey>,
+    attempt_option: Option<u32>,
+    message: Option<String>,
+) -> Result<AppResponse<JobRunDto>, AppError> {
     let mut conn = state.pool.get().await?;
-    let (_job_config, job_run) = job_run_update_stage(&state, &mut conn, app_name_and_job_name_option, job_run_id_option, &stage_name, JobRunStageType::Start, JobRunStageStatus::Occurred).await?;
-    Ok(AppResponse::success_one("job-run", job_run))
+    let (_job_config, job_run) = job_run_update_stage(&state, &mut conn, (app_name_and_job_name_option, job_run_id_option, run_key_option), &stage_name, JobRunStageType::Start, (attempt_option, message.as_deref())).await?;
+    Ok(AppResponse::success_one("job-run", job_run.into()))
 }
 
 async fn _job_run_complete_handler(
     state: SharedState,
     (app_name_and_job_name_option, job_run_id_option, stage_name): (Option<(String, String)>, Option<Uuid>, String),
-) -> Result<AppResponse<JobRun>, AppError> {
+    run_key_option: Option<RunKey>,
+    attempt_option: Option<u32>,
+    message: Option<String>,
+) -> Result<AppResponse<JobRunDto>, AppError> {
     let mut conn = state.pool.get().await?;
-    let (_job_config, job_run) = job_run_update_stage(&state, &mut conn, app_name_and_job_name_option, job_run_id_option, &stage_name, JobRunStageType::Complete, JobRunStageStatus::Occurred).await?;
-    Ok(AppResponse::success_one("job-run", job_run))
+    let (_job_config, job_run) = job_run_update_stage(&state, &mut conn, (app_name_and_job_name_option, job_run_id_option, run_key_option), &stage_name, JobRunStageType::Complete, (attempt_option, message.as_deref())).await?;
+    Ok(AppResponse::success_one("job-run", job_run.into()))
 }
 
 async fn _job_run_failed_handler(
     state: SharedState,
     (app_name_and_job_name_option, job_run_id_option, stage_name): (Option<(String, String)>, Option<Uuid>, String),
+    run_key_option: Option<RunKey>,
+    attempt_option: Option<u32>,
+    stage_type: JobRunStageType,
     message: Option<String>,
-) -> Result<AppResponse<JobRun>, AppError> {
+) -> Result<AppResponse<JobRunDto>, AppError> {
     let mut conn = state.pool.get().await?;
-    let (job_config, job_run) = job_run_update_stage(&state, &mut conn, app_name_and_job_name_option.clone(), job_run_id_option, &stage_name, JobRunStageType::Failed, JobRunStageStatus::Failed).await?;
+    let (job_config, job_run) = job_run_update_stage(&state, &mut conn, (app_name_and_job_name_option.clone(), job_run_id_option, run_key_option), &stage_name, stage_type, (attempt_option, message.as_deref())).await?;
+
+    // Only the failure of the last attempt is alerted on, earlier ones are retried
+    let last_stage = job_run.stages.last();
+    if last_stage.is_some_and(|stage| stage.complete_status == Some(JobRunStageStatus::Retrying)) {
+        info!("stage {} of run {} failed on attempt {}, retrying", stage_name, job_run.id, last_stage.map(get_attempt).unwrap_or(1));
+        return Ok(AppResponse::success_one("job-run", job_run.into()));
+    }
+
     let res = send_failed(&state.dispatcher, &job_config, &job_run, &stage_name, &message.unwrap_or("".to_string()), &job_config.channel_ids).await;
     if let Err(err) = res {
         error!("failed to send failed notification: {:?} - {} - {} - {}", app_name_and_job_name_option, stage_name, job_run_id_option.map(|uuid| uuid.to_string()).unwrap_or_else(|| "None".to_string()), err.to_string());
@@ -162,7 +280,7 @@ async fn _job_run_failed_handler(
             &error_channels // Pass the clone
         ).await;
     }
-    Ok(AppResponse::success_one("job-run", job_run))
+    Ok(AppResponse::success_one("job-run", job_run.into()))
 }
 
 #[derive(Clone, PartialEq)]
@@ -170,27 +288,29 @@ pub enum JobRunStageType {
     Start,
     Complete,
     Failed,
+    Retrying,
 }
 
 async fn job_run_update_stage(
     state: &SharedState,
     conn: &mut DbConnection<'_>,
-    app_name_and_job_name_option: Option<(String, String)>,
-    job_run_id_option: Option<Uuid>,
+    (app_name_and_job_name_option, job_run_id_option, run_key_option): (Option<(String, String)>, Option<Uuid>, Option<RunKey>),
     stage_name: &String,
     stage_type: JobRunStageType,
-    stage_status: JobRunStageStatus
+    (attempt_option, message_option): (Option<u32>, Option<&str>),
 ) -> Result<(JobConfig, JobRun), AppError> {
     if app_name_and_job_name_option.is_none() && job_run_id_option.is_none() {
         return Err(AppError::BadRequest("Either (app_name and job_name) or job_run_id should be provided".to_string()))
     }
 
+    let is_failure = matches!(stage_type, JobRunStageType::Failed | JobRunStageType::Retrying);
+
     let result;
     if let Some(job_run_id) = job_run_id_option {
-        result = _job_run_update_stage_with_run_id(conn, job_run_id, stage_name, stage_type, stage_status).await;
+        result = _job_run_update_stage_with_run_id(conn, job_run_id, stage_name, stage_type, (attempt_option, message_option)).await;
     } else {
         let (app_name, job_name) = app_name_and_job_name_option.clone().unwrap();
-        result = _job_run_update_stage_with_app_name_and_job_name(conn, app_name, job_name, stage_name, stage_type, stage_status).await;
+        result = _job_run_update_stage_with_app_name_and_job_name(state, conn, (app_name, job_name, run_key_option), stage_name, stage_type, (attempt_option, message_option)).await;
     }
 
     /*
@@ -226,6 +346,12 @@ async fn job_run_update_stage(
         ).await;
         Err(err)
     } else {
+        if let Ok((job_config, job_run)) = &result
+            && !is_failure
+            && is_finished_by_latest_stage(job_config, job_run)
+            && let Err(err) = send_resolved(&state.dispatcher, job_config, job_run).await {
+            error!("failed to send resolve notification: {} - {} - {}", job_run.app_name, job_run.job_name, err);
+        }
         result
     }
 }
@@ -234,7 +360,7 @@ async fn _job_run_update_stage_with_run_id(
     job_run_id: Uuid,
     stage_name: &String,
     stage_type: JobRunStageType,
-    stage_status: JobRunStageStatus
+    (attempt_option, message_option): (Option<u32>, Option<&str>),
 ) -> Result<(JobConfig, JobRun), AppError> {
     let job_run = _get_job_run_by_id(conn, &job_run_id).await?;
 
@@ -244,80 +370,156 @@ async fn _job_run_update_stage_with_run_id(
 
     let utc_now = get_utc_now();
 
-    _job_run_update_stage_internal(conn, job_config, job_run, utc_now, stage_name, stage_type, stage_status).await
+    _job_run_update_stage_internal(conn, job_config, job_run, utc_now, stage_name, stage_type, (attempt_option, message_option)).await
 }
 
 async fn _job_run_update_stage_with_app_name_and_job_name(
+    state: &SharedState,
     conn: &mut DbConnection<'_>,
-    app_name: String,
-    job_name: String,
+    (app_name, job_name, run_key_option): (String, String, Option<RunKey>),
     stage_name: &String,
     stage_type: JobRunStageType,
-    stage_status: JobRunStageStatus
+    (attempt_option, message_option): (Option<u32>, Option<&str>),
 ) -> Result<(JobConfig, JobRun), AppError> {
     let job_config = _get_job_config_by_app_name_and_job_name(conn, &app_name, &job_name).await?;
+    let utc_now = get_utc_now();
+
+    // A keyed event names its run, whatever the time
+    if let Some(run_key) = run_key_option {
+        let job_run = _get_or_start_keyed_run(state, conn, &job_config, run_key, utc_now).await?;
+        return _job_run_update_stage_internal(conn, job_config, job_run, utc_now, stage_name, stage_type, (attempt_option, message_option)).await;
+    }
 
     if job_config.zone_id.is_none() || job_config.schedule.is_none() {
         return Err(AppError::InternalError(format!("zone or schedule should not be empty {}-{}", &app_name, &job_name)))
     }
     let zone_id = job_config.zone_id.as_ref().unwrap();
-    let utc_now = get_utc_now();
     let tz_now = change_timezone(&utc_now, zone_id)?;
 
-    let job_start_time = get_job_start_time(&job_config, &tz_now)?;
+    let in_flight_runs = get_in_flight_job_runs(conn, &app_name, &job_name, in_flight_since(state, &utc_now)).await?;
+
+    let job_run = match find_run_for_stage_event(&in_flight_runs, stage_name, &stage_type) {
+        Some(run) => run.clone(),
+        None => {
+            // No run is waiting for the event, so it belongs to the run of the current occurrence
+            let occurrence = get_run_occurrence(&job_config, &tz_now, state.config.grace_time_seconds)?;
+            let next_occurrence = get_next_execution_times(job_config.schedule.as_ref().unwrap(), &occurrence, 1)?
+                .first()
+                .map(change_to_utc)
+                .transpose()?
+                .unwrap_or(utc_now);
+            let scheduled_for = change_to_utc(&occurrence)?;
+            let grace = Duration::seconds(state.config.grace_time_seconds);
+
+            let existing = get_job_runs_of_occurrence(conn, &app_name, &job_name, scheduled_for, scheduled_for - grace, next_occurrence - grace).await?
+                .into_iter()
+                .next();
+            match existing {
+                Some(run) => run,
+                None => start_job_run(conn, &state.dispatcher, &job_config, &in_flight_runs, Some(scheduled_for), None, RunOrigin::Api).await?,
+            }
+        }
+    };
 
-    let job_run_option = get_latest_job_run_by_app_name_and_job_name(conn, &app_name, &job_name, &change_to_utc(&job_start_time)?).await?;
+    _job_run_update_stage_internal(conn, job_config, job_run, utc_now, stage_name, stage_type, (attempt_option, message_option)).await
+}
+
+/// The run with the key, started when this is the first time the key is seen. A new keyed run belongs to its logical
+/// date, or for scheduled jobs to the current occurrence, taking over the run the scheduler started for it.
+async fn _get_or_start_keyed_run(
+    state: &SharedState,
+    conn: &mut DbConnection<'_>,
+    job_config: &JobConfig,
+    run_key: RunKey,
+    utc_now: DateTime<Utc>,
+) -> Result<JobRun, AppError> {
+    let (app_name, job_name) = (&job_config.app_name, &job_config.job_name);
+    if let Some(job_run) = get_job_run_by_key(conn, app_name, job_name, &run_key.key).await? {
+        return Ok(job_run);
+    }
 
-    let job_run = match job_run_option {
-        Some(run) => run,
-        None => create_new_job_run(
-            conn,
-            &job_config.app_name,
-            &job_config.job_name
-        ).await?,
+    let scheduled_for = match run_key.logical_date {
+        Some(logical_date) => Some(logical_date),
+        None => get_scheduled_for(state, job_config, &utc_now)?,
     };
+    let in_flight_runs = get_in_flight_job_runs(conn, app_name, job_name, in_flight_since(state, &utc_now)).await?;
 
-    _job_run_update_stage_internal(conn, job_config, job_run, utc_now, stage_name, stage_type, stage_status).await
+    if let Some(expected_run) = find_expected_run(&in_flight_runs, scheduled_for).filter(|run| run.run_key.is_none()) {
+        let mut job_run = expected_run.clone();
+        job_run.run_key = Some(run_key.key);
+        return save_run(conn, job_run).await;
+    }
+
+    match start_job_run(conn, &state.dispatcher, job_config, &in_flight_runs, scheduled_for, Some(run_key.key.clone()), RunOrigin::Api).await {
+        Ok(job_run) => Ok(job_run),
+        // Another event with the same key may have started the run meanwhile
+        Err(err) => get_job_run_by_key(conn, app_name, job_name, &run_key.key).await?.ok_or(err),
+    }
 }
 
 async fn _job_run_update_stage_internal(
     conn: &mut DbConnection<'_>,
     mut job_config: JobConfig,
-    mut job_run: JobRun,
+    job_run: JobRun,
     utc_now: DateTime<Utc>,
     stage_name: &String,
     stage_type: JobRunStageType,
-    stage_status: JobRunStageStatus
+    (attempt_option, message_option): (Option<u32>, Option<&str>),
 ) -> Result<(JobConfig, JobRun), AppError> {
 
     // Enable the job if paused
     if !job_config.enabled {
         job_config.enabled = true;
-        job_config = save_config(conn, job_config).await?;
+        job_config = save_config_with_revision(conn, job_config, RevisionChangeType::Enabled, None).await?;
     }
 
     check(&stage_type, &job_config, stage_name)?;
 
-    let mut new_stage = JobRunStage {
-        name: stage_name.clone(),
-        start_status: None,
-        start_date_time: None,
-        complete_status: None,
-        complete_date_time: None,
-    };
-    if stage_type == JobRunStageType::Start {
-        new_stage.start_status = Some(stage_status);
-        new_stage.start_date_time = Some(utc_now);
-    } else {
-        new_stage.complete_status = Some(stage_status);
-        new_stage.complete_date_time = Some(utc_now);
+    if attempt_option == Some(0) {
+        return Err(AppError::BadRequest(format!("attempts of stage {} are counted from 1", stage_name)));
     }
+    let max_attempts = job_config.stages.iter()
+        .find(|stage| stage.name == *stage_name)
+        .and_then(|stage| stage.max_attempts)
+        .unwrap_or(1);
+
+    let (job_config_clone, stage_name, message) = (job_config.clone(), stage_name.clone(), message_option.map(str::to_string));
+    let (updated, events) = conn.transaction(|conn| async move {
+        // The event is applied to the stages its run's earlier events left, one event of the run at a time
+        let mut job_run = get_job_run_by_id_for_update(conn, &job_run.id).await?
+            .ok_or_else(|| AppError::NotFound(format!("job run not found for id: {}", job_run.id)))?;
+
+        let attempt = attempt_option.unwrap_or_else(|| get_event_attempt(&job_run.stages, &stage_name, &stage_type));
+
+        let event_type = match stage_type {
+            JobRunStageType::Start => RunEventType::StageStarted,
+            JobRunStageType::Complete => RunEventType::StageCompleted,
+            JobRunStageType::Failed | JobRunStageType::Retrying if attempt < max_attempts => RunEventType::StageRetrying,
+            JobRunStageType::Failed | JobRunStageType::Retrying => RunEventType::StageFailed,
+        };
+
+        let mut event = RunEvent::new(event_type, &job_run, Some(&stage_name), RunEventSource::Api)
+            .with_attempt(Some(attempt))
+            .with_message(message);
+        event.occurred_at = utc_now;
+
+        job_run.stages.extend(get_run_stage(&event.event_type, event.stage_name.as_ref(), event.attempt, event.occurred_at));
+        let previous_status = job_run.status.clone();
+        job_run.status = get_status(&job_config_clone, &job_run);
+        event.status = job_run.status.clone();
+
+        let mut events = vec![event];
+        if job_run.status != previous_status {
+            events.push(RunEvent::new(RunEventType::StatusChanged, &job_run, None, RunEventSource::Api));
+        }
+        let updated = save_run_with_events(conn, job_run, &events).await?;
+        Ok::<_, AppError>((updated, events))
+    }.scope_boxed()).await?;
 
-    job_run.stages.push(new_stage);
-
-    job_run.status = get_status(&job_config, &job_run);
+    for event in events {
+        publish_run_event(conn, event).await;
+    }
 
-    let updated = save_run(conn, job_run).await?;
     Ok((job_config, updated))
 }
 
@@ -337,3 +539,46 @@ async fn _get_job_run_by_id(conn: &mut DbConnection<'_>, job_run_id: &Uuid) -> R
     Ok(job_run_option.unwrap())
 }
 
+
+// Runs are only matched against events for as long as their stages may take
+fn in_flight_since(state: &SharedState, utc_now: &DateTime<Utc>) -> DateTime<Utc> {
+    let _settings = state.settings.read().expect("Lock poisoned");
+    *utc_now - Duration::hours(_settings.max_stage_duration_hours as i64)
+}
+
+// Runs of scheduled jobs belong to the occurrence they were triggered for
+fn get_scheduled_for(state: &SharedState, job_config: &JobConfig, utc_now: &DateTime<Utc>) -> Result<Option<DateTime<Utc>>, AppError> {
+    match (&job_config.schedule, &job_config.zone_id) {
+        (Some(_), Some(zone_id)) => {
+            let tz_now = change_timezone(utc_now, zone_id)?;
+            Ok(Some(change_to_utc(&get_run_occurrence(job_config, &tz_now, state.config.grace_time_seconds)?)?))
+        }
+        _ => Ok(None),
+    }
+}
+
+// A run the scheduler started for the occurrence that no event has reached yet
+fn find_expected_run(in_flight_runs: &[JobRun], scheduled_for: Option<DateTime<Utc>>) -> Option<&JobRun> {
+    in_flight_runs.iter()
+        .find(|run| scheduled_for.is_some() && run.scheduled_for == scheduled_for && run.stages.is_empty())
+}
+
+/// A run addressed by the key of the system that runs the job, see `TriggerPayload::run_key`.
+#[derive(Debug, Clone)]
+pub struct RunKey {
+    pub key: String,
+    pub logical_date: Option<DateTime<Utc>>,
+}
+
+impl RunKey {
+    fn from_payload(run_key: Option<String>, logical_date: Option<DateTime<Utc>>) -> Result<Option<Self>, AppError> {
+        match run_key {
+            Some(key) if key.trim().is_empty() || key.len() > 255 => {
+                Err(AppError::BadRequest("run_key must be between 1 and 255 characters long".to_string()))
+            }
+            Some(key) => Ok(Some(RunKey { key, logical_date })),
+            None if logical_date.is_some() => Err(AppError::BadRequest("logical_date requires a run_key".to_string())),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use axum::extract::{Query, State};
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz::UTC;
+use crate::{SharedState};
+use crate::core::schedule_forecast::build_forecast;
+use crate::db::config_repository::get_all_job_configs;
+use crate::db::run_repository::get_job_runs_between;
+use crate::dtos::schedule::{ForecastEntryDto, ScheduleForecastQuery};
+use crate::errors::AppError;
+use watchdog_api::jsend::AppResponse;
+use crate::time_utils::{get_tz, get_utc_now};
+
+const DEFAULT_FORECAST_WINDOW_HOURS: i64 = 24;
+const MAX_FORECAST_WINDOW_DAYS: i64 = 7;
+
+pub async fn get_schedule_forecast_handler(
+    State(state): State<SharedState>,
+    Query(query): Query<ScheduleForecastQuery>,
+) -> Result<AppResponse<Vec<ForecastEntryDto>>, AppError> {
+    let utc_now = get_utc_now();
+    let from = match query.from {
+        Some(from) => from,
+        None => get_start_of_day(&utc_now, query.zone_id.as_ref())?,
+    };
+    let to = query.to.unwrap_or(from + Duration::hours(DEFAULT_FORECAST_WINDOW_HOURS));
+
+    if to <= from {
+        return Err(AppError::BadRequest(format!("'to' ({}) must be after 'from' ({})", to, from)));
+    }
+    if to - from > Duration::days(MAX_FORECAST_WINDOW_DAYS) {
+        return Err(AppError::BadRequest(format!("forecast window must not exceed {} days", MAX_FORECAST_WINDOW_DAYS)));
+    }
+
+    let mut conn = state.pool.get().await?;
+
+    let job_configs = get_all_job_configs(&mut conn).await?;
+    let grace_time_seconds = state.config.grace_time_seconds;
+    // Runs of an occurrence in the window may have been triggered long after it, e.g. for a backfill
+    let job_runs = get_job_runs_between(&mut conn, from, to, from - Duration::seconds(grace_time_seconds)).await?;
+
+    let forecast = build_forecast(&job_configs, job_runs, &from, &to, &utc_now, grace_time_seconds);
+
+    Ok(AppResponse::success_one("schedule", forecast.into_iter().map(Into::into).collect()))
+}
+
+// The default window is the current day, so runs that already started or finished show up next to the pending ones
+fn get_start_of_day(utc_now: &DateTime<Utc>, zone_id: Option<&String>) -> Result<DateTime<Utc>, AppError> {
+    let tz = match zone_id {
+        Some(zone_id) => get_tz(zone_id)?,
+        None => UTC,
+    };
+    let midnight = utc_now.with_timezone(&tz).date_naive().and_time(NaiveTime::MIN);
+    // Days starting in a DST gap have no midnight, they start with the window ending now instead
+    Ok(tz.from_local_datetime(&midnight)
+        .earliest()
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or(*utc_now - Duration::hours(DEFAULT_FORECAST_WINDOW_HOURS)))
+}
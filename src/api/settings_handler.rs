@@ -5,7 +5,7 @@ use crate::{SharedState};
 use crate::db::settings_repository::{get_settings, save_settings};
 use crate::dtos::settings::{SettingsResponseDto, SettingsUpdateRequest};
 use crate::errors::AppError;
-use crate::jsend::AppResponse;
+use watchdog_api::jsend::AppResponse;
 
 pub async fn get_settings_handler(
     State(state): State<SharedState>,
@@ -32,6 +32,7 @@ pub async fn update_settings_handler(
     _settings.default_channels = _update_request.default_channels.unwrap();
     _settings.error_channels = _update_request.error_channels.unwrap();
     _settings.max_stage_duration_hours = _update_request.max_stage_duration_hours.unwrap();
+    _settings.audit_retention_days = _update_request.audit_retention_days.unwrap_or(_settings.audit_retention_days);
 
     let updated = save_settings(&mut conn, _settings).await?;
     Ok(AppResponse::success_one("settings", updated.into()))
@@ -0,0 +1,78 @@
+use axum::extract::{Path, State};
+use axum::Json;
+use chrono::Duration;
+use tracing::info;
+use validator::Validate;
+use crate::{SharedState};
+use crate::db::config_repository::{get_job_config_by_app_name_and_job_name, get_jobs_by_application};
+use crate::db::snooze_repository::{delete_snooze, save_snooze};
+use crate::dtos::snooze::{SnoozeDto, SnoozeRequest};
+use crate::errors::AppError;
+use watchdog_api::jsend::AppResponse;
+use crate::models::NewSnooze;
+use crate::time_utils::get_utc_now;
+
+pub async fn snooze_job_handler(
+    State(state): State<SharedState>,
+    Path((app_name, job_name)): Path<(String, String)>,
+    Json(_snooze_request): Json<SnoozeRequest>,
+) -> Result<AppResponse<SnoozeDto>, AppError> {
+    _snooze_request.validate()?;
+    let mut conn = state.pool.get().await?;
+
+    if get_job_config_by_app_name_and_job_name(&mut conn, &app_name, &job_name).await?.is_none() {
+        return Err(AppError::NotFound(format!("Configuration doesn't exists for application '{}' and job '{}'", app_name, job_name)));
+    }
+
+    info!("Snoozing job {}-{} for {}secs", app_name, job_name, _snooze_request.duration_seconds);
+    let saved = save_snooze(&mut conn, new_snooze(app_name, Some(job_name), _snooze_request)).await?;
+    Ok(AppResponse::success_one("snooze", saved.into()))
+}
+
+pub async fn unsnooze_job_handler(
+    State(state): State<SharedState>,
+    Path((app_name, job_name)): Path<(String, String)>,
+) -> Result<AppResponse<SnoozeDto>, AppError> {
+    let mut conn = state.pool.get().await?;
+
+    let deleted = delete_snooze(&mut conn, &app_name, Some(&job_name)).await?
+        .ok_or_else(|| AppError::NotFound(format!("Job {}-{} is not snoozed", app_name, job_name)))?;
+    Ok(AppResponse::success_one("snooze", deleted.into()))
+}
+
+pub async fn snooze_application_handler(
+    State(state): State<SharedState>,
+    Path(app_name): Path<String>,
+    Json(_snooze_request): Json<SnoozeRequest>,
+) -> Result<AppResponse<SnoozeDto>, AppError> {
+    _snooze_request.validate()?;
+    let mut conn = state.pool.get().await?;
+
+    if get_jobs_by_application(&mut conn, app_name.clone()).await?.is_empty() {
+        return Err(AppError::NotFound(format!("No jobs configured for application '{}'", app_name)));
+    }
+
+    info!("Snoozing application {} for {}secs", app_name, _snooze_request.duration_seconds);
+    let saved = save_snooze(&mut conn, new_snooze(app_name, None, _snooze_request)).await?;
+    Ok(AppResponse::success_one("snooze", saved.into()))
+}
+
+pub async fn unsnooze_application_handler(
+    State(state): State<SharedState>,
+    Path(app_name): Path<String>,
+) -> Result<AppResponse<SnoozeDto>, AppError> {
+    let mut conn = state.pool.get().await?;
+
+    let deleted = delete_snooze(&mut conn, &app_name, None).await?
+        .ok_or_else(|| AppError::NotFound(format!("Application {} is not snoozed", app_name)))?;
+    Ok(AppResponse::success_one("snooze", deleted.into()))
+}
+
+fn new_snooze(app_name: String, job_name: Option<String>, request: SnoozeRequest) -> NewSnooze {
+    NewSnooze {
+        app_name,
+        job_name,
+        snoozed_until: get_utc_now() + Duration::seconds(request.duration_seconds as i64),
+        reason: request.reason.map(|reason| reason.trim().to_string()).filter(|reason| !reason.is_empty()),
+    }
+}
@@ -0,0 +1,160 @@
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use axum::body::{to_bytes, Body};
+use axum::extract::{ConnectInfo, FromRequestParts, MatchedPath, OriginalUri, RawPathParams, Request, State};
+use axum::http::{HeaderMap, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde_json::{Map, Value};
+use tracing::error;
+use crate::SharedState;
+use crate::api::actor::ACTOR_HEADER;
+use crate::db::audit_repository::insert_audit_log;
+use watchdog_api::jsend::AppResponse;
+use crate::models::NewAuditLog;
+
+const MAX_AUDITED_BODY_BYTES: usize = 2 * 1024 * 1024;
+const MAX_SUMMARY_STRING_CHARS: usize = 256;
+const REDACTED: &str = "[redacted]";
+
+// Channel configurations carry credentials and webhook tokens; revisions keep their history instead
+const SENSITIVE_KEYS: [&str; 6] = ["configuration", "password", "secret", "token", "webhook_url", "routing_key"];
+
+/// Records every mutating request in `audit_logs` once it has been handled. Failing to write the audit
+/// row is logged but never fails the request itself.
+pub async fn audit_middleware(
+    State(state): State<SharedState>,
+    matched_path: Option<MatchedPath>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !is_mutating(request.method()) {
+        return next.run(request).await;
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, MAX_AUDITED_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            let message = format!("Request body could not be read: {}", e);
+            return (StatusCode::PAYLOAD_TOO_LARGE, AppResponse::<()>::fail_message(message)).into_response();
+        }
+    };
+
+    let request_summary = summarize_body(&bytes);
+    let path_values: Vec<String> = RawPathParams::from_request_parts(&mut parts, &state)
+        .await
+        .map(|params| params.iter().map(|(_, value)| value.to_string()).collect())
+        .unwrap_or_default();
+
+    let path = parts.extensions.get::<OriginalUri>()
+        .map(|OriginalUri(uri)| uri.path().to_string())
+        .unwrap_or_else(|| parts.uri.path().to_string());
+
+    let mut new_audit_log = NewAuditLog {
+        actor: header_value(&parts.headers, ACTOR_HEADER),
+        client_ip: client_ip(&parts.headers, parts.extensions.get::<ConnectInfo<SocketAddr>>(), &state.config.trusted_proxies),
+        user_agent: header_value(&parts.headers, "user-agent"),
+        method: parts.method.to_string(),
+        route: matched_path.map(|matched| matched.as_str().to_string()).unwrap_or_else(|| path.clone()),
+        path,
+        target: get_target(&path_values, request_summary.as_ref()),
+        request_summary,
+        status_code: 0,
+    };
+
+    let response = next.run(Request::from_parts(parts, Body::from(bytes))).await;
+    new_audit_log.status_code = response.status().as_u16() as i32;
+
+    match state.pool.get().await {
+        Ok(mut conn) => {
+            if let Err(e) = insert_audit_log(&mut conn, new_audit_log).await {
+                error!("Failed to write audit log: {}", e);
+            }
+        }
+        Err(e) => error!("Failed to get connection for audit log: {}", e),
+    }
+
+    response
+}
+
+fn is_mutating(method: &Method) -> bool {
+    matches!(*method, Method::POST | Method::PUT | Method::PATCH | Method::DELETE)
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers.get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+// X-Forwarded-For is only believed when a trusted proxy sent it, and then only up to the first entry a trusted
+// proxy didn't add, since anything before it is whatever the client claimed
+fn client_ip(headers: &HeaderMap, connect_info: Option<&ConnectInfo<SocketAddr>>, trusted_proxies: &[IpAddr]) -> Option<String> {
+    let peer_ip = connect_info.map(|ConnectInfo(addr)| addr.ip())?;
+    if !trusted_proxies.contains(&peer_ip) {
+        return Some(peer_ip.to_string());
+    }
+
+    let mut client_ip = peer_ip;
+    if let Some(forwarded_for) = header_value(headers, "x-forwarded-for") {
+        for forwarded_ip in forwarded_for.split(',').rev().map(|ip| IpAddr::from_str(ip.trim())) {
+            match forwarded_ip {
+                Ok(ip) => {
+                    client_ip = ip;
+                    if !trusted_proxies.contains(&ip) {
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+    }
+    Some(client_ip.to_string())
+}
+
+fn get_target(path_values: &[String], request_summary: Option<&Value>) -> Option<String> {
+    if !path_values.is_empty() {
+        return Some(path_values.join("/"));
+    }
+
+    // Creations carry their identity in the body rather than the path
+    let body = request_summary?;
+    match (body.get("app_name"), body.get("job_name"), body.get("name")) {
+        (Some(Value::String(app_name)), Some(Value::String(job_name)), _) => Some(format!("{}/{}", app_name, job_name)),
+        (_, _, Some(Value::String(name))) => Some(name.clone()),
+        _ => None,
+    }
+}
+
+fn summarize_body(bytes: &[u8]) -> Option<Value> {
+    if bytes.is_empty() {
+        return None;
+    }
+    match serde_json::from_slice::<Value>(bytes) {
+        Ok(value) => Some(redact(value)),
+        Err(_) => Some(Value::String(format!("<{} bytes of non-JSON body>", bytes.len()))),
+    }
+}
+
+fn redact(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    if SENSITIVE_KEYS.iter().any(|sensitive| key.to_lowercase().contains(sensitive)) {
+                        (key, Value::String(REDACTED.to_string()))
+                    } else {
+                        (key, redact(value))
+                    }
+                })
+                .collect::<Map<String, Value>>(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(redact).collect()),
+        Value::String(text) if text.chars().count() > MAX_SUMMARY_STRING_CHARS => {
+            Value::String(format!("{}...", text.chars().take(MAX_SUMMARY_STRING_CHARS).collect::<String>()))
+        }
+        other => other,
+    }
+}
@@ -0,0 +1,144 @@
+use std::time::Duration;
+use reqwest::Client;
+use serde_json::Value;
+use crate::spool::{Spool, SpooledEvent};
+
+pub enum SendError {
+    /// The server answered with a client error; sending the event again will not help.
+    Rejected(u16, String),
+    /// The server could not be reached, or failed with a server error.
+    Unreachable(String),
+}
+
+impl SendError {
+    pub fn message(&self) -> String {
+        match self {
+            SendError::Rejected(status, message) => format!("rejected with {}: {}", status, message),
+            SendError::Unreachable(message) => format!("server unreachable: {}", message),
+        }
+    }
+}
+
+pub enum Delivery {
+    /// The server accepted the event, carrying the `data` of its JSend response.
+    Sent(Value),
+    /// The server is unreachable, the event was written to the spool to be sent later.
+    Spooled,
+}
+
+pub struct WatchdogClient {
+    client: Client,
+    server: String,
+    retries: u32,
+    retry_delay: Duration,
+    spool: Option<Spool>,
+}
+
+impl WatchdogClient {
+    pub fn new(server: &str, timeout: Duration, retries: u32, retry_delay: Duration, spool: Option<Spool>) -> Result<Self, String> {
+        let client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| format!("failed to build http client: {}", e))?;
+        Ok(Self {
+            client,
+            server: server.trim_end_matches('/').to_string(),
+            retries,
+            retry_delay,
+            spool,
+        })
+    }
+
+    /// POSTs `body` to `path`. Events already waiting in the spool are sent first so the server sees them in order;
+    /// when the server stays unreachable the event joins the spool instead of failing.
+    pub async fn deliver(&self, path: &str, body: Value) -> Result<Delivery, SendError> {
+        let Some(spool) = self.spool.as_ref() else {
+            return self.post_with_retries(path, &body).await.map(Delivery::Sent);
+        };
+
+        let event = SpooledEvent::new(path, body);
+        if self.flush().await? > 0 {
+            spool.push(&event).map_err(SendError::Unreachable)?;
+            return Ok(Delivery::Spooled);
+        }
+
+        match self.post_with_retries(path, &event.body).await {
+            Ok(data) => Ok(Delivery::Sent(data)),
+            Err(SendError::Unreachable(message)) => {
+                eprintln!("watchdog: {}, spooling event for later", message);
+                spool.push(&event).map_err(SendError::Unreachable)?;
+                Ok(Delivery::Spooled)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Sends the spooled events oldest first, stopping at the first one the server can't take yet.
+    /// Returns the number of events still waiting.
+    pub async fn flush(&self) -> Result<usize, SendError> {
+        let Some(spool) = self.spool.as_ref() else {
+            return Ok(0);
+        };
+
+        let pending = spool.pending().map_err(SendError::Unreachable)?;
+        for (index, entry) in pending.iter().enumerate() {
+            // Another reporter may be flushing the same spool
+            let Some(claimed) = spool.claim(entry) else {
+                continue;
+            };
+            match self.post_with_retries(&claimed.event.path, &claimed.event.body).await {
+                Ok(_) => spool.remove(claimed),
+                Err(SendError::Rejected(status, message)) => {
+                    eprintln!("watchdog: spooled event for {} rejected with {}: {}", claimed.event.path, status, message);
+                    spool.discard(claimed);
+                }
+                Err(SendError::Unreachable(_)) => {
+                    spool.release(claimed);
+                    return Ok(pending.len() - index);
+                }
+            }
+        }
+        Ok(0)
+    }
+
+    async fn post_with_retries(&self, path: &str, body: &Value) -> Result<Value, SendError> {
+        let mut attempt = 0;
+        loop {
+            match self.post(path, body).await {
+                Err(SendError::Unreachable(message)) if attempt < self.retries => {
+                    attempt += 1;
+                    let delay = self.retry_delay * 2u32.saturating_pow(attempt - 1);
+                    eprintln!("watchdog: {}, retrying in {:?} ({}/{})", message, delay, attempt, self.retries);
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn post(&self, path: &str, body: &Value) -> Result<Value, SendError> {
+        let res = self.client.post(format!("{}{}", self.server, path))
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| SendError::Unreachable(e.to_string()))?;
+
+        let status = res.status();
+        let response: Value = res.json().await.unwrap_or(Value::Null);
+
+        if status.is_success() {
+            return Ok(response.get("data").cloned().unwrap_or(Value::Null));
+        }
+
+        let message = response.get("message")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| status.to_string());
+        // Server errors may pass, the event is kept to be sent again; only a refused request is dropped
+        if status.is_server_error() {
+            Err(SendError::Unreachable(message))
+        } else {
+            Err(SendError::Rejected(status.as_u16(), message))
+        }
+    }
+}
@@ -0,0 +1,311 @@
+mod client;
+mod spool;
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::process::{ExitCode, ExitStatus, Stdio};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+use watchdog_api::job_run::{JobRunStageEventType, StageUpdatePayload, TriggerPayload};
+use crate::client::{Delivery, SendError, WatchdogClient};
+use crate::spool::Spool;
+
+/// Longest message sent with a stage event; longer stderr tails keep their end.
+const MAX_MESSAGE_LENGTH: usize = 4000;
+
+/// Reports job runs and stages to a watchdog server.
+#[derive(Debug, Parser)]
+#[command(name = "watchdog-cli", version)]
+struct Cli {
+    /// Base url of the watchdog api.
+    #[arg(long, env = "WATCHDOG_URL", default_value = "http://localhost:8080/api", global = true)]
+    server: String,
+    #[arg(long, env = "WATCHDOG_TIMEOUT_SECONDS", default_value_t = 10, global = true)]
+    timeout_seconds: u64,
+    /// Retries when the server is unreachable, with exponential backoff.
+    #[arg(long, env = "WATCHDOG_RETRIES", default_value_t = 3, global = true)]
+    retries: u32,
+    /// Delay before the first retry; doubled for every following one.
+    #[arg(long, env = "WATCHDOG_RETRY_DELAY_MS", default_value_t = 500, global = true)]
+    retry_delay_ms: u64,
+    /// Where events are kept while the server is unreachable. They are sent before the next event, or by `flush`.
+    #[arg(long, env = "WATCHDOG_SPOOL_DIR", global = true)]
+    spool_dir: Option<PathBuf>,
+    /// Fail instead of spooling events the server can't take.
+    #[arg(long, global = true)]
+    no_spool: bool,
+    #[command(subcommand)]
+    command: CliCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum CliCommand {
+    /// Starts a new run of a job.
+    Trigger {
+        #[arg(long)]
+        app: String,
+        #[arg(long)]
+        job: String,
+        #[command(flatten)]
+        key: RunKeyArgs,
+    },
+    /// Sends a stage event.
+    Stage {
+        #[command(flatten)]
+        target: StageTarget,
+        #[arg(long, value_enum)]
+        event: StageEvent,
+        #[arg(long)]
+        message: Option<String>,
+    },
+    /// Runs a command between a `started` and a `completed` or `failed` event, and exits with its exit code.
+    Wrap {
+        #[command(flatten)]
+        target: StageTarget,
+        /// Number of trailing stderr lines sent with the final event.
+        #[arg(long, default_value_t = 20)]
+        tail_lines: usize,
+        #[arg(last = true, required = true, value_name = "COMMAND")]
+        command: Vec<String>,
+    },
+    /// Sends the spooled events.
+    Flush,
+}
+
+#[derive(Debug, Args)]
+struct StageTarget {
+    #[arg(long)]
+    app: String,
+    #[arg(long)]
+    job: String,
+    #[arg(long)]
+    stage: String,
+    /// Reports to this run instead of the job's latest one.
+    #[arg(long, conflicts_with = "run_key")]
+    run_id: Option<String>,
+    #[command(flatten)]
+    key: RunKeyArgs,
+    /// Which attempt of the stage this is, counting from 1; worked out by the server when absent.
+    #[arg(long)]
+    attempt: Option<u32>,
+}
+
+#[derive(Debug, Args)]
+struct RunKeyArgs {
+    /// Identifies the run within the job, e.g. an Airflow logical date or a batch id; the run is started when the key is new.
+    #[arg(long)]
+    run_key: Option<String>,
+    /// The schedule occurrence a new keyed run is for, e.g. 2026-10-18T00:00:00Z for a backfill.
+    #[arg(long, requires = "run_key")]
+    logical_date: Option<DateTime<Utc>>,
+}
+
+impl RunKeyArgs {
+    fn trigger_payload(&self) -> TriggerPayload {
+        TriggerPayload {
+            run_key: self.run_key.clone(),
+            logical_date: self.logical_date,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum StageEvent {
+    Started,
+    Completed,
+    Failed,
+    Retrying,
+}
+
+impl From<StageEvent> for JobRunStageEventType {
+    fn from(event: StageEvent) -> Self {
+        match event {
+            StageEvent::Started => JobRunStageEventType::Started,
+            StageEvent::Completed => JobRunStageEventType::Completed,
+            StageEvent::Failed => JobRunStageEventType::Failed,
+            StageEvent::Retrying => JobRunStageEventType::Retrying,
+        }
+    }
+}
+
+impl StageTarget {
+    fn path(&self) -> String {
+        match self.run_id.as_ref() {
+            Some(run_id) => format!("/job-runs/{}/stage-update", run_id),
+            None => format!("/applications/{}/jobs/{}/stage-update", self.app, self.job),
+        }
+    }
+
+    fn payload(&self, event: StageEvent, message: Option<String>) -> Value {
+        to_body(&StageUpdatePayload {
+            stage_name: self.stage.clone(),
+            event_type: event.into(),
+            message,
+            attempt: self.attempt,
+            run_key: self.key.run_key.clone(),
+            logical_date: self.key.logical_date,
+        })
+    }
+}
+
+/// The JSON body of a request, built from the payload types the server reads it with.
+fn to_body(payload: &impl Serialize) -> Value {
+    serde_json::to_value(payload).expect("api payloads serialize to JSON")
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let spool = (!cli.no_spool).then(|| {
+        Spool::new(cli.spool_dir.clone().unwrap_or_else(|| std::env::temp_dir().join("watchdog-spool")), longest_send(&cli))
+    });
+    let client = match WatchdogClient::new(
+        &cli.server,
+        Duration::from_secs(cli.timeout_seconds),
+        cli.retries,
+        Duration::from_millis(cli.retry_delay_ms),
+        spool,
+    ) {
+        Ok(client) => client,
+        Err(err) => {
+            eprintln!("watchdog: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match cli.command {
+        CliCommand::Trigger { app, job, key } => {
+            let result = client.deliver(&format!("/applications/{}/jobs/{}/trigger", app, job), to_body(&key.trigger_payload())).await;
+            report_result(result, |data| {
+                let run_id = data.pointer("/job-run/id").and_then(Value::as_str).unwrap_or_default();
+                println!("{}", run_id);
+            })
+        }
+        CliCommand::Stage { target, event, message } => {
+            let result = client.deliver(&target.path(), target.payload(event, message)).await;
+            report_result(result, |_| {})
+        }
+        CliCommand::Wrap { target, tail_lines, command } => wrap(&client, &target, tail_lines, &command).await,
+        CliCommand::Flush => match client.flush().await {
+            Ok(0) => ExitCode::SUCCESS,
+            Ok(remaining) => {
+                eprintln!("watchdog: server unreachable, {} events are still spooled", remaining);
+                ExitCode::FAILURE
+            }
+            Err(err) => {
+                eprintln!("watchdog: {}", err.message());
+                ExitCode::FAILURE
+            }
+        },
+    }
+}
+
+/// How long sending one event can take with all its retries; an event claimed longer ago was left by a killed reporter.
+fn longest_send(cli: &Cli) -> Duration {
+    let timeout = Duration::from_secs(cli.timeout_seconds);
+    let retry_delays = Duration::from_millis(cli.retry_delay_ms) * 2u32.saturating_pow(cli.retries).saturating_sub(1);
+    timeout * cli.retries.saturating_add(1) + retry_delays
+}
+
+fn report_result(result: Result<Delivery, SendError>, on_sent: impl FnOnce(&Value)) -> ExitCode {
+    match result {
+        Ok(Delivery::Sent(data)) => {
+            on_sent(&data);
+            ExitCode::SUCCESS
+        }
+        Ok(Delivery::Spooled) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("watchdog: {}", err.message());
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Reporting problems are printed but never change the outcome of the wrapped command.
+async fn wrap(client: &WatchdogClient, target: &StageTarget, tail_lines: usize, command: &[String]) -> ExitCode {
+    if let Err(err) = client.deliver(&target.path(), target.payload(StageEvent::Started, None)).await {
+        eprintln!("watchdog: failed to report start of stage {}: {}", target.stage, err.message());
+    }
+
+    let (event, message, exit_code) = match run_command(command, tail_lines).await {
+        Ok((status, tail)) => {
+            let (description, exit_code) = describe_exit(status);
+            let event = if status.success() { StageEvent::Completed } else { StageEvent::Failed };
+            let message = if tail.is_empty() { description } else { format!("{}\n{}", description, tail) };
+            (event, message, exit_code)
+        }
+        Err(err) => {
+            eprintln!("watchdog: {}", err);
+            (StageEvent::Failed, err, 127)
+        }
+    };
+
+    if let Err(err) = client.deliver(&target.path(), target.payload(event, Some(truncate_start(message, MAX_MESSAGE_LENGTH)))).await {
+        eprintln!("watchdog: failed to report end of stage {}: {}", target.stage, err.message());
+    }
+
+    ExitCode::from(exit_code)
+}
+
+/// Runs the command with stdout inherited and stderr passed through, keeping the last `tail_lines` lines of stderr.
+async fn run_command(command: &[String], tail_lines: usize) -> Result<(ExitStatus, String), String> {
+    let mut child = Command::new(&command[0])
+        .args(&command[1..])
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to start {}: {}", command[0], e))?;
+
+    let mut tail = VecDeque::with_capacity(tail_lines);
+    if let Some(stderr) = child.stderr.take() {
+        let mut reader = BufReader::new(stderr);
+        let mut own_stderr = tokio::io::stderr();
+        let mut line = vec![];
+        while reader.read_until(b'\n', &mut line).await.unwrap_or(0) > 0 {
+            let _ = own_stderr.write_all(&line).await;
+            if tail_lines > 0 {
+                if tail.len() == tail_lines {
+                    tail.pop_front();
+                }
+                tail.push_back(String::from_utf8_lossy(&line).trim_end().to_string());
+            }
+            line.clear();
+        }
+    }
+
+    let status = child.wait().await
+        .map_err(|e| format!("failed to wait for {}: {}", command[0], e))?;
+    Ok((status, tail.into_iter().collect::<Vec<_>>().join("\n")))
+}
+
+/// Describes how the command ended, and the exit code to pass on; killed commands exit with 128 + signal like in a shell.
+fn describe_exit(status: ExitStatus) -> (String, u8) {
+    if let Some(code) = status.code() {
+        return (format!("exit code {}", code), code as u8);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return (format!("terminated by signal {}", signal), 128u8.saturating_add(signal as u8));
+        }
+    }
+
+    ("terminated".to_string(), 1)
+}
+
+fn truncate_start(message: String, max_length: usize) -> String {
+    let length = message.chars().count();
+    if length <= max_length {
+        return message;
+    }
+    format!("...{}", message.chars().skip(length - max_length).collect::<String>())
+}
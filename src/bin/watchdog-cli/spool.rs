@@ -0,0 +1,135 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+const PENDING_EXTENSION: &str = "json";
+const SENDING_EXTENSION: &str = "sending";
+const REJECTED_EXTENSION: &str = "rejected";
+
+/// Events that could not be delivered, one JSON file each, named so that sorting by name gives the send order.
+/// Events the server refused when replayed are kept with a `.rejected` extension for inspection.
+pub struct Spool {
+    dir: PathBuf,
+    /// How long an event may stay claimed before it is taken for the leftover of a reporter that died while sending it.
+    claim_timeout: Duration,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpooledEvent {
+    pub path: String,
+    pub body: Value,
+    pub queued_at: DateTime<Utc>,
+}
+
+pub struct ClaimedEvent {
+    file: PathBuf,
+    pub event: SpooledEvent,
+}
+
+impl SpooledEvent {
+    pub fn new(path: &str, body: Value) -> Self {
+        Self {
+            path: path.to_string(),
+            body,
+            queued_at: Utc::now(),
+        }
+    }
+}
+
+impl Spool {
+    pub fn new(dir: PathBuf, claim_timeout: Duration) -> Self {
+        Self { dir, claim_timeout }
+    }
+
+    pub fn push(&self, event: &SpooledEvent) -> Result<(), String> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("unable to create spool directory {}: {}", self.dir.display(), e))?;
+
+        let name = format!("{:020}-{}", event.queued_at.timestamp_micros(), Uuid::new_v4());
+        let content = serde_json::to_vec(event)
+            .map_err(|e| format!("unable to serialize event: {}", e))?;
+
+        // Written under a temporary name first so a flush never reads a half written file
+        let temp_file = self.dir.join(format!(".{}.tmp", name));
+        let file = self.dir.join(name).with_extension(PENDING_EXTENSION);
+        fs::write(&temp_file, content)
+            .and_then(|_| fs::rename(&temp_file, &file))
+            .map_err(|e| format!("unable to write spool file {}: {}", file.display(), e))
+    }
+
+    /// Spooled event files, oldest first. Events claimed longer than the claim timeout ago are pending again.
+    pub fn pending(&self) -> Result<Vec<PathBuf>, String> {
+        if !self.dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let files = fs::read_dir(&self.dir)
+            .map_err(|e| format!("unable to read spool directory {}: {}", self.dir.display(), e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .collect::<Vec<_>>();
+
+        let mut pending = vec![];
+        for file in files {
+            if has_extension(&file, SENDING_EXTENSION) && self.is_stale_claim(&file) {
+                let pending_file = file.with_extension(PENDING_EXTENSION);
+                if fs::rename(&file, &pending_file).is_ok() {
+                    eprintln!("watchdog: requeuing spool file {} left by an interrupted send", pending_file.display());
+                    pending.push(pending_file);
+                }
+            } else if has_extension(&file, PENDING_EXTENSION) {
+                pending.push(file);
+            }
+        }
+        pending.sort();
+        Ok(pending)
+    }
+
+    /// Takes the event out of the pending set, or `None` when another process already did.
+    pub fn claim(&self, file: &Path) -> Option<ClaimedEvent> {
+        let claimed_file = file.with_extension(SENDING_EXTENSION);
+        fs::rename(file, &claimed_file).ok()?;
+        // A rename keeps the modification time, it's set to when the claim was made for the claim timeout
+        let _ = fs::File::options().write(true).open(&claimed_file)
+            .and_then(|claimed| claimed.set_modified(SystemTime::now()));
+
+        let event = fs::read(&claimed_file).ok()
+            .and_then(|content| serde_json::from_slice::<SpooledEvent>(&content).ok());
+        match event {
+            Some(event) => Some(ClaimedEvent { file: claimed_file, event }),
+            None => {
+                eprintln!("watchdog: ignoring unreadable spool file {}", claimed_file.display());
+                let _ = fs::rename(&claimed_file, claimed_file.with_extension(REJECTED_EXTENSION));
+                None
+            }
+        }
+    }
+
+    /// Puts a claimed event back to be sent by a later flush.
+    pub fn release(&self, claimed: ClaimedEvent) {
+        let _ = fs::rename(&claimed.file, claimed.file.with_extension(PENDING_EXTENSION));
+    }
+
+    pub fn remove(&self, claimed: ClaimedEvent) {
+        let _ = fs::remove_file(&claimed.file);
+    }
+
+    pub fn discard(&self, claimed: ClaimedEvent) {
+        let _ = fs::rename(&claimed.file, claimed.file.with_extension(REJECTED_EXTENSION));
+    }
+
+    fn is_stale_claim(&self, file: &Path) -> bool {
+        fs::metadata(file)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|claimed_at| claimed_at.elapsed().ok())
+            .is_some_and(|claimed_for| claimed_for > self.claim_timeout)
+    }
+}
+
+fn has_extension(file: &Path, extension: &str) -> bool {
+    file.extension().is_some_and(|ext| ext == extension)
+}
@@ -1,20 +1,237 @@
 use std::env;
+use std::fs;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::str::FromStr;
+use clap::{Parser, ValueEnum};
 use dotenvy::dotenv;
+use serde::Deserialize;
+use tracing::Level;
+use crate::secrets::SecretCipher;
+
+const DEFAULT_BIND_ADDRESS: &str = "0.0.0.0:8080";
+const DEFAULT_STATIC_DIR: &str = "./web/dist";
+const DEFAULT_SCHEDULER_INITIAL_DELAY_SECONDS: u64 = 2;
+const DEFAULT_SCHEDULER_FIXED_DELAY_SECONDS: u64 = 30;
+const DEFAULT_GRACE_TIME_SECONDS: i64 = 5;
+const DEFAULT_DB_POOL_MAX_SIZE: u32 = 10;
+const DEFAULT_DB_CONNECTION_TIMEOUT_SECONDS: u64 = 30;
+const DEFAULT_LOG_LEVEL: &str = "info";
+const DEFAULT_SHUTDOWN_TIMEOUT_SECONDS: u64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Full,
+    Compact,
+    Json,
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    pub max_size: u32,
+    pub min_idle: Option<u32>,
+    pub connection_timeout_seconds: u64,
+    pub idle_timeout_seconds: Option<u64>,
+}
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub postgres_url: String,
+    pub bind_address: SocketAddr,
+    pub static_dir: PathBuf,
     pub scheduler_initial_delay_seconds: u64,
     pub scheduler_fixed_delay_seconds: u64,
     pub grace_time_seconds: i64,
+    pub db_pool: PoolConfig,
+    pub log_level: Level,
+    pub log_format: LogFormat,
+    /// How long in-flight requests, the current scheduler pass and their notifications may take to finish on shutdown.
+    pub shutdown_timeout_seconds: u64,
+    /// Base64 encoded 32 byte key used to encrypt channel secrets.
+    pub secret_key: Option<String>,
+    /// Stores channel secrets unencrypted when no secret key is configured, instead of rejecting them.
+    pub allow_plaintext_secrets: bool,
+    /// Proxies whose `X-Forwarded-For` header is trusted to name the client.
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+/// Runtime options. Flags take precedence over their environment variables,
+/// which take precedence over the config file.
+#[derive(Debug, Parser)]
+#[command(name = "watchdog", version, about = "Monitors scheduled jobs and alerts when a stage is late or fails")]
+struct Cli {
+    /// Optional TOML file with any of the options below, using the flag names with underscores.
+    #[arg(long, env = "WATCHDOG_CONFIG")]
+    config: Option<PathBuf>,
+    #[arg(long, env = "DATABASE_URL", hide_env_values = true)]
+    database_url: Option<String>,
+    #[arg(long, env = "WATCHDOG_BIND_ADDRESS")]
+    bind_address: Option<String>,
+    /// Directory with the web UI build, served for every path outside /api.
+    #[arg(long, env = "WATCHDOG_STATIC_DIR")]
+    static_dir: Option<PathBuf>,
+    #[arg(long, env = "WATCHDOG_SCHEDULER_INITIAL_DELAY_SECONDS")]
+    scheduler_initial_delay_seconds: Option<u64>,
+    #[arg(long, env = "WATCHDOG_SCHEDULER_FIXED_DELAY_SECONDS")]
+    scheduler_fixed_delay_seconds: Option<u64>,
+    #[arg(long, env = "WATCHDOG_GRACE_TIME_SECONDS", allow_negative_numbers = true)]
+    grace_time_seconds: Option<i64>,
+    #[arg(long, env = "WATCHDOG_DB_POOL_MAX_SIZE")]
+    db_pool_max_size: Option<u32>,
+    #[arg(long, env = "WATCHDOG_DB_POOL_MIN_IDLE")]
+    db_pool_min_idle: Option<u32>,
+    #[arg(long, env = "WATCHDOG_DB_CONNECTION_TIMEOUT_SECONDS")]
+    db_connection_timeout_seconds: Option<u64>,
+    #[arg(long, env = "WATCHDOG_DB_IDLE_TIMEOUT_SECONDS")]
+    db_idle_timeout_seconds: Option<u64>,
+    /// One of trace, debug, info, warn, error.
+    #[arg(long, env = "WATCHDOG_LOG_LEVEL")]
+    log_level: Option<String>,
+    #[arg(long, env = "WATCHDOG_LOG_FORMAT")]
+    log_format: Option<LogFormat>,
+    #[arg(long, env = "WATCHDOG_SHUTDOWN_TIMEOUT_SECONDS")]
+    shutdown_timeout_seconds: Option<u64>,
+    /// Accepts channel secrets without WATCHDOG_SECRET_KEY and stores them unencrypted.
+    #[arg(long, env = "WATCHDOG_ALLOW_PLAINTEXT_SECRETS")]
+    allow_plaintext_secrets: Option<bool>,
+    /// Comma separated addresses of the reverse proxies in front of watchdog.
+    #[arg(long, env = "WATCHDOG_TRUSTED_PROXIES", value_delimiter = ',')]
+    trusted_proxies: Option<Vec<String>>,
 }
 
-pub fn from_env() -> Config {
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    database_url: Option<String>,
+    bind_address: Option<String>,
+    static_dir: Option<PathBuf>,
+    scheduler_initial_delay_seconds: Option<u64>,
+    scheduler_fixed_delay_seconds: Option<u64>,
+    grace_time_seconds: Option<i64>,
+    db_pool_max_size: Option<u32>,
+    db_pool_min_idle: Option<u32>,
+    db_connection_timeout_seconds: Option<u64>,
+    db_idle_timeout_seconds: Option<u64>,
+    log_level: Option<String>,
+    log_format: Option<LogFormat>,
+    shutdown_timeout_seconds: Option<u64>,
+    secret_key: Option<String>,
+    allow_plaintext_secrets: Option<bool>,
+    trusted_proxies: Option<Vec<String>>,
+}
+
+/// Loads the configuration from flags, environment (including `.env`) and the optional config file,
+/// and validates it. All problems are reported together.
+pub fn load() -> Result<Config, String> {
     dotenv().ok();
-    Config { 
-        postgres_url: env::var("DATABASE_URL").expect("DATABASE_URL must be set"),
-        scheduler_initial_delay_seconds: 2,
-        scheduler_fixed_delay_seconds: 30,
-        grace_time_seconds: 5
+    let cli = Cli::parse();
+
+    let file = match cli.config.as_ref() {
+        Some(path) => {
+            let content = fs::read_to_string(path)
+                .map_err(|e| format!("unable to read config file {}: {}", path.display(), e))?;
+            toml::from_str::<FileConfig>(&content)
+                .map_err(|e| format!("invalid config file {}: {}", path.display(), e))?
+        }
+        None => FileConfig::default(),
+    };
+
+    let mut errors = vec![];
+
+    let postgres_url = cli.database_url.or(file.database_url).unwrap_or_else(|| {
+        errors.push("database url is required (--database-url or DATABASE_URL)".to_string());
+        String::new()
+    });
+
+    let bind_address = cli.bind_address.or(file.bind_address).unwrap_or(DEFAULT_BIND_ADDRESS.to_string());
+    let bind_address = SocketAddr::from_str(&bind_address).unwrap_or_else(|e| {
+        errors.push(format!("bind_address '{}' is not a valid socket address: {}", bind_address, e));
+        SocketAddr::from(([0, 0, 0, 0], 0))
+    });
+
+    let scheduler_initial_delay_seconds = cli.scheduler_initial_delay_seconds
+        .or(file.scheduler_initial_delay_seconds)
+        .unwrap_or(DEFAULT_SCHEDULER_INITIAL_DELAY_SECONDS);
+
+    let scheduler_fixed_delay_seconds = cli.scheduler_fixed_delay_seconds
+        .or(file.scheduler_fixed_delay_seconds)
+        .unwrap_or(DEFAULT_SCHEDULER_FIXED_DELAY_SECONDS);
+    if scheduler_fixed_delay_seconds == 0 {
+        errors.push("scheduler_fixed_delay_seconds must be at least 1".to_string());
     }
-}
\ No newline at end of file
+
+    let grace_time_seconds = cli.grace_time_seconds.or(file.grace_time_seconds).unwrap_or(DEFAULT_GRACE_TIME_SECONDS);
+    if grace_time_seconds < 0 {
+        errors.push(format!("grace_time_seconds must not be negative, got {}", grace_time_seconds));
+    }
+
+    let db_pool = PoolConfig {
+        max_size: cli.db_pool_max_size.or(file.db_pool_max_size).unwrap_or(DEFAULT_DB_POOL_MAX_SIZE),
+        min_idle: cli.db_pool_min_idle.or(file.db_pool_min_idle),
+        connection_timeout_seconds: cli.db_connection_timeout_seconds
+            .or(file.db_connection_timeout_seconds)
+            .unwrap_or(DEFAULT_DB_CONNECTION_TIMEOUT_SECONDS),
+        idle_timeout_seconds: cli.db_idle_timeout_seconds.or(file.db_idle_timeout_seconds),
+    };
+    if db_pool.max_size == 0 {
+        errors.push("db_pool_max_size must be at least 1".to_string());
+    }
+    if db_pool.min_idle.is_some_and(|min_idle| min_idle > db_pool.max_size) {
+        errors.push(format!("db_pool_min_idle must not exceed db_pool_max_size ({})", db_pool.max_size));
+    }
+    if db_pool.connection_timeout_seconds == 0 {
+        errors.push("db_connection_timeout_seconds must be at least 1".to_string());
+    }
+    if db_pool.idle_timeout_seconds == Some(0) {
+        errors.push("db_idle_timeout_seconds must be at least 1".to_string());
+    }
+
+    let log_level = cli.log_level.or(file.log_level).unwrap_or(DEFAULT_LOG_LEVEL.to_string());
+    let log_level = Level::from_str(&log_level).unwrap_or_else(|_| {
+        errors.push(format!("log_level '{}' must be one of trace, debug, info, warn, error", log_level));
+        Level::INFO
+    });
+
+    let shutdown_timeout_seconds = cli.shutdown_timeout_seconds
+        .or(file.shutdown_timeout_seconds)
+        .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT_SECONDS);
+    if shutdown_timeout_seconds == 0 {
+        errors.push("shutdown_timeout_seconds must be at least 1".to_string());
+    }
+
+    // Only read from the environment or the file, so the key does not show up in process listings
+    let secret_key = env::var("WATCHDOG_SECRET_KEY").ok().or(file.secret_key).filter(|key| !key.is_empty());
+    if let Err(e) = SecretCipher::new(secret_key.as_deref()) {
+        errors.push(format!("WATCHDOG_SECRET_KEY is invalid: {}", e));
+    }
+
+    let trusted_proxies = cli.trusted_proxies.or(file.trusted_proxies).unwrap_or_default()
+        .iter()
+        .map(|proxy| proxy.trim())
+        .filter(|proxy| !proxy.is_empty())
+        .filter_map(|proxy| IpAddr::from_str(proxy).map_err(|e| {
+            errors.push(format!("trusted proxy '{}' is not a valid ip address: {}", proxy, e));
+        }).ok())
+        .collect();
+
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+
+    Ok(Config {
+        postgres_url,
+        bind_address,
+        static_dir: cli.static_dir.or(file.static_dir).unwrap_or(PathBuf::from(DEFAULT_STATIC_DIR)),
+        scheduler_initial_delay_seconds,
+        scheduler_fixed_delay_seconds,
+        grace_time_seconds,
+        db_pool,
+        log_level,
+        log_format: cli.log_format.or(file.log_format).unwrap_or(LogFormat::Full),
+        shutdown_timeout_seconds,
+        secret_key,
+        allow_plaintext_secrets: cli.allow_plaintext_secrets.or(file.allow_plaintext_secrets).unwrap_or(false),
+        trusted_proxies,
+    })
+}
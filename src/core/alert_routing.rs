@@ -0,0 +1,279 @@
+use chrono::{DateTime, Datelike, Utc};
+use chrono_tz::Tz::UTC;
+use crate::models::{JobMetadata, RoutingMatcher, RoutingRule, Severity, TimeWindow};
+use crate::notification::core::AlertType;
+use crate::time_utils::get_tz;
+
+/// What routing rules are matched against.
+#[derive(Debug, Clone)]
+pub struct RoutedAlert<'a> {
+    pub app_name: &'a str,
+    pub job_name: &'a str,
+    pub stage_name: &'a str,
+    pub alert_type: &'a AlertType,
+    pub severity: Severity,
+    pub metadata: &'a JobMetadata,
+    pub at: DateTime<Utc>,
+}
+
+/// Where an alert goes, and which rules sent it there.
+#[derive(Debug, Clone, Default)]
+pub struct AlertRouting {
+    pub matched_rules: Vec<String>,
+    pub channel_ids: Vec<String>,
+    /// Whether no rule matched and the alert went to the default channels.
+    pub is_default: bool,
+}
+
+/// Routes the alert by the rules, given in the order they are evaluated in, falling back to `default_channel_ids`
+/// when none of them match. Errors of watchdog itself always go to `default_channel_ids`, rules only add to them.
+pub fn route_alert(rules: &[RoutingRule], alert: &RoutedAlert, default_channel_ids: &str) -> AlertRouting {
+    let mut routing = AlertRouting::default();
+
+    for rule in rules.iter().filter(|rule| rule.enabled) {
+        if !matches_alert(&rule.matcher, alert) {
+            continue;
+        }
+        routing.matched_rules.push(rule.name.clone());
+        add_channel_ids(&mut routing.channel_ids, &rule.channel_ids);
+        if !rule.continue_matching {
+            break;
+        }
+    }
+
+    routing.is_default = routing.matched_rules.is_empty();
+    if routing.is_default || *alert.alert_type == AlertType::Error {
+        add_channel_ids(&mut routing.channel_ids, default_channel_ids);
+    }
+    routing
+}
+
+/// The channels that may hold alerts of the job, so resolving its runs reaches them all: the default ones, and
+/// those of every rule matching the job whatever the alert.
+pub fn get_resolve_channel_ids(rules: &[RoutingRule], app_name: &str, job_name: &str, metadata: &JobMetadata, default_channel_ids: &str) -> Vec<String> {
+    let mut channel_ids = vec![];
+    add_channel_ids(&mut channel_ids, default_channel_ids);
+
+    for rule in rules.iter().filter(|rule| rule.enabled && matches_job(&rule.matcher, app_name, job_name, metadata)) {
+        add_channel_ids(&mut channel_ids, &rule.channel_ids);
+    }
+    channel_ids
+}
+
+fn matches_alert(matcher: &RoutingMatcher, alert: &RoutedAlert) -> bool {
+    matches_job(matcher, alert.app_name, alert.job_name, alert.metadata)
+        && (matcher.alert_types.is_empty() || matcher.alert_types.contains(alert.alert_type))
+        && (matcher.severities.is_empty() || matcher.severities.contains(&alert.severity))
+        && (matcher.stage_names.is_empty() || matcher.stage_names.iter().any(|stage_name| stage_name == alert.stage_name))
+        && matcher.time_window.as_ref().is_none_or(|time_window| is_within(time_window, &alert.at))
+}
+
+fn matches_job(matcher: &RoutingMatcher, app_name: &str, job_name: &str, metadata: &JobMetadata) -> bool {
+    matcher.app_name.as_ref().is_none_or(|pattern| matches_glob(pattern, app_name))
+        && matcher.job_name.as_ref().is_none_or(|pattern| matches_glob(pattern, job_name))
+        && (matcher.tags.is_empty() || matcher.tags.iter().any(|tag| metadata.tags.contains(tag)))
+}
+
+/// Whether `value` matches the glob `pattern`, `*` matching any number of characters and `?` exactly one.
+pub fn matches_glob(pattern: &str, value: &str) -> bool {
+    let (pattern, value): (Vec<char>, Vec<char>) = (pattern.chars().collect(), value.chars().collect());
+    let (mut p, mut v) = (0, 0);
+    // Where the last `*` was, and how much of the value it covers so far, to backtrack to on a mismatch
+    let mut star: Option<(usize, usize)> = None;
+
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == value[v]) {
+            p += 1;
+            v += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, v));
+            p += 1;
+        } else if let Some((star_p, star_v)) = star {
+            p = star_p + 1;
+            v = star_v + 1;
+            star = Some((star_p, star_v + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn is_within(time_window: &TimeWindow, at: &DateTime<Utc>) -> bool {
+    let tz = time_window.zone_id.as_ref().and_then(|zone_id| get_tz(zone_id).ok()).unwrap_or(UTC);
+    let zoned_at = at.with_timezone(&tz);
+    let (time, day) = (zoned_at.time(), zoned_at.weekday());
+
+    // Windows running past midnight belong to the day they start on
+    let window_day = if time_window.start <= time_window.end {
+        if time < time_window.start || time >= time_window.end {
+            return false;
+        }
+        day
+    } else if time >= time_window.start {
+        day
+    } else if time < time_window.end {
+        day.pred()
+    } else {
+        return false;
+    };
+    time_window.days.is_empty() || time_window.days.contains(&window_day)
+}
+
+fn add_channel_ids(channel_ids: &mut Vec<String>, channel_ids_str: &str) {
+    for channel_id in channel_ids_str.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        if !channel_ids.iter().any(|existing| existing == channel_id) {
+            channel_ids.push(channel_id.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveTime, TimeZone, Weekday};
+
+    fn rule(name: &str, matcher: RoutingMatcher, channel_ids: &str, continue_matching: bool) -> RoutingRule {
+        RoutingRule {
+            name: name.to_string(),
+            priority: 0,
+            enabled: true,
+            matcher: diesel_json::Json(matcher),
+            channel_ids: channel_ids.to_string(),
+            continue_matching,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn app_matcher(app_name: &str) -> RoutingMatcher {
+        RoutingMatcher { app_name: Some(app_name.to_string()), ..RoutingMatcher::default() }
+    }
+
+    fn alert<'a>(alert_type: &'a AlertType, metadata: &'a JobMetadata) -> RoutedAlert<'a> {
+        RoutedAlert {
+            app_name: "billing-api",
+            job_name: "nightly-export",
+            stage_name: "load",
+            alert_type,
+            severity: Severity::Critical,
+            metadata,
+            at: Utc.with_ymd_and_hms(2026, 10, 19, 12, 0, 0).unwrap(),
+        }
+    }
+
+    fn window(start: (u32, u32), end: (u32, u32), days: Vec<Weekday>) -> TimeWindow {
+        TimeWindow {
+            start: NaiveTime::from_hms_opt(start.0, start.1, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(end.0, end.1, 0).unwrap(),
+            zone_id: None,
+            days,
+        }
+    }
+
+    #[test]
+    fn glob_matches_literals_and_wildcards() {
+        assert!(matches_glob("billing-api", "billing-api"));
+        assert!(!matches_glob("billing-api", "billing-apis"));
+        assert!(matches_glob("*", ""));
+        assert!(matches_glob("billing-*", "billing-"));
+        assert!(matches_glob("job-?", "job-1"));
+        assert!(!matches_glob("job-?", "job-"));
+        assert!(!matches_glob("job-?", "job-12"));
+    }
+
+    #[test]
+    fn glob_backtracks_to_the_last_star() {
+        // The first `-export` seen is not the end of the value, the star has to take it
+        assert!(matches_glob("*-export", "nightly-export-export"));
+        assert!(matches_glob("a*b*c", "aXbYbZc"));
+        assert!(matches_glob("*?-?", "ab-c"));
+        assert!(matches_glob("**x", "aax"));
+        assert!(!matches_glob("a*b*c", "aXbYbZ"));
+        assert!(!matches_glob("*-export", "nightly-exports"));
+    }
+
+    #[test]
+    fn window_within_a_day() {
+        let business_hours = window((9, 0), (17, 0), vec![Weekday::Mon]);
+        assert!(is_within(&business_hours, &Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap()));
+        assert!(!is_within(&business_hours, &Utc.with_ymd_and_hms(2026, 10, 19, 17, 0, 0).unwrap()));
+        assert!(!is_within(&business_hours, &Utc.with_ymd_and_hms(2026, 10, 20, 12, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn window_crossing_midnight_belongs_to_the_day_it_starts_on() {
+        let monday_night = window((22, 0), (6, 0), vec![Weekday::Mon]);
+        // Monday 23:00 and the early hours of Tuesday are within it
+        assert!(is_within(&monday_night, &Utc.with_ymd_and_hms(2026, 10, 19, 23, 0, 0).unwrap()));
+        assert!(is_within(&monday_night, &Utc.with_ymd_and_hms(2026, 10, 20, 5, 59, 0).unwrap()));
+        // The early hours of Monday belong to Sunday night's window
+        assert!(!is_within(&monday_night, &Utc.with_ymd_and_hms(2026, 10, 19, 3, 0, 0).unwrap()));
+        // Tuesday night is not within it, nor is the day in between
+        assert!(!is_within(&monday_night, &Utc.with_ymd_and_hms(2026, 10, 20, 23, 0, 0).unwrap()));
+        assert!(!is_within(&monday_night, &Utc.with_ymd_and_hms(2026, 10, 20, 6, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn window_in_its_zone() {
+        let mut monday_morning = window((8, 0), (10, 0), vec![Weekday::Mon]);
+        monday_morning.zone_id = Some("US/Eastern".to_string());
+        assert!(is_within(&monday_morning, &Utc.with_ymd_and_hms(2026, 10, 19, 13, 0, 0).unwrap()));
+        assert!(!is_within(&monday_morning, &Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn first_matching_rule_stops_the_evaluation() {
+        let metadata = JobMetadata::default();
+        let rules = vec![
+            rule("other-app", app_matcher("payments-*"), "payments", false),
+            rule("billing", app_matcher("billing-*"), "billing", false),
+            rule("everything", RoutingMatcher::default(), "catch-all", false),
+        ];
+
+        let routing = route_alert(&rules, &alert(&AlertType::Timeout, &metadata), "job-channel");
+        assert_eq!(routing.matched_rules, vec!["billing"]);
+        assert_eq!(routing.channel_ids, vec!["billing"]);
+        assert!(!routing.is_default);
+    }
+
+    #[test]
+    fn continue_matching_adds_the_channels_of_later_rules() {
+        let metadata = JobMetadata::default();
+        let mut rules = vec![
+            rule("billing", app_matcher("billing-*"), "billing, ops", true),
+            rule("disabled", RoutingMatcher::default(), "nowhere", false),
+            rule("critical", RoutingMatcher { severities: vec![Severity::Critical], ..RoutingMatcher::default() }, "ops,pager", false),
+            rule("everything", RoutingMatcher::default(), "catch-all", false),
+        ];
+        rules[1].enabled = false;
+
+        let routing = route_alert(&rules, &alert(&AlertType::Failed, &metadata), "job-channel");
+        assert_eq!(routing.matched_rules, vec!["billing", "critical"]);
+        assert_eq!(routing.channel_ids, vec!["billing", "ops", "pager"]);
+    }
+
+    #[test]
+    fn unmatched_alerts_go_to_the_default_channels() {
+        let metadata = JobMetadata::default();
+        let rules = vec![
+            rule("payments", app_matcher("payments-*"), "payments", false),
+            rule("failures", RoutingMatcher { alert_types: vec![AlertType::Failed], ..RoutingMatcher::default() }, "failures", false),
+        ];
+
+        let routing = route_alert(&rules, &alert(&AlertType::Timeout, &metadata), "job-channel,backup");
+        assert!(routing.matched_rules.is_empty());
+        assert_eq!(routing.channel_ids, vec!["job-channel", "backup"]);
+        assert!(routing.is_default);
+    }
+
+    #[test]
+    fn error_alerts_keep_the_error_channels() {
+        let metadata = JobMetadata::default();
+        let rules = vec![rule("everything", RoutingMatcher::default(), "catch-all", false)];
+
+        let routing = route_alert(&rules, &alert(&AlertType::Error, &metadata), "errors");
+        assert_eq!(routing.matched_rules, vec!["everything"]);
+        assert_eq!(routing.channel_ids, vec!["catch-all", "errors"]);
+    }
+}
@@ -0,0 +1,43 @@
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz::UTC;
+use crate::cron_utils::get_previous_execution_time;
+use crate::db::connection::DbConnection;
+use crate::db::snooze_repository::get_active_snoozes_of_job;
+use crate::errors::AppError;
+use crate::models::{BlackoutWindow, JobConfig, Snooze};
+use crate::time_utils::get_tz;
+
+/// Why alerts of the job are silenced at `now`, if they are: a snooze of the job or of its application, or one of
+/// its blackout windows. Timeouts are still recorded while silenced, only the alerts are held back.
+pub async fn get_silence_reason(
+    conn: &mut DbConnection<'_>,
+    job_config: &JobConfig,
+    now: &DateTime<Utc>,
+) -> Result<Option<String>, AppError> {
+    let snoozes = get_active_snoozes_of_job(conn, &job_config.app_name, &job_config.job_name, *now).await?;
+    if let Some(snooze) = get_effective_snooze(snoozes) {
+        let target = if snooze.job_name.is_some() { "job" } else { "application" };
+        let reason = snooze.reason.map(|reason| format!(": {}", reason)).unwrap_or_default();
+        return Ok(Some(format!("{} snoozed until {}{}", target, snooze.snoozed_until, reason)));
+    }
+
+    Ok(get_blackout_window(job_config, now)
+        .map(|window| format!("blackout window '{}' of {} seconds", window.schedule, window.duration_seconds)))
+}
+
+/// Of the snoozes silencing a job, the one lasting longest.
+pub fn get_effective_snooze(snoozes: Vec<Snooze>) -> Option<Snooze> {
+    snoozes.into_iter().max_by_key(|snooze| snooze.snoozed_until)
+}
+
+/// The blackout window of the job `at` falls in. Windows recur in the job's zone, or in UTC for unscheduled jobs.
+pub fn get_blackout_window<'a>(job_config: &'a JobConfig, at: &DateTime<Utc>) -> Option<&'a BlackoutWindow> {
+    let tz = job_config.zone_id.as_ref().and_then(|zone_id| get_tz(zone_id).ok()).unwrap_or(UTC);
+    let zoned_at = at.with_timezone(&tz);
+
+    // Only the latest start matters, windows of a schedule all last as long so earlier ones end sooner
+    job_config.blackout_windows.iter().find(|window| {
+        let Ok(start) = get_previous_execution_time(&window.schedule, &zoned_at) else { return false };
+        Duration::try_seconds(window.duration_seconds as i64).is_some_and(|duration| zoned_at < start + duration)
+    })
+}
@@ -0,0 +1,56 @@
+use serde_json::Value;
+use tracing::info;
+use crate::db::channel_repository::{get_all_channels, save_channel};
+use crate::db::connection::PgPool;
+use crate::db::revision_repository::{get_all_channel_revisions, update_channel_revision_snapshot};
+use crate::dtos::channel::ChannelSnapshot;
+use crate::errors::AppError;
+use crate::notification::dispatcher::NotificationDispatcher;
+
+/// Encrypts channel secrets stored before a secret key was configured, in channels and in their revision snapshots.
+pub async fn seal_stored_channel_secrets(pool: &PgPool, dispatcher: &NotificationDispatcher) -> Result<(), AppError> {
+    if !dispatcher.encrypts_secrets() {
+        return Ok(());
+    }
+
+    let mut conn = pool.get().await?;
+    let mut sealed = 0;
+
+    for mut channel in get_all_channels(&mut conn).await? {
+        let configuration = dispatcher.seal_config(&channel.provider_type, &channel.configuration)?;
+        if configuration != channel.configuration {
+            channel.configuration = configuration;
+            save_channel(&mut conn, channel).await?;
+            sealed += 1;
+        }
+    }
+
+    for revision in get_all_channel_revisions(&mut conn).await? {
+        let Ok(mut snapshot) = serde_json::from_value::<ChannelSnapshot>(revision.snapshot.clone()) else {
+            continue;
+        };
+        snapshot.configuration = dispatcher.seal_config(&snapshot.provider_type, &snapshot.configuration)?;
+        let snapshot = serde_json::to_value(snapshot)
+            .map_err(|e| AppError::InternalError(format!("failed to serialize snapshot: {}", e)))?;
+        if snapshot != revision.snapshot {
+            update_channel_revision_snapshot(&mut conn, revision.id, &snapshot).await?;
+            sealed += 1;
+        }
+    }
+
+    if sealed > 0 {
+        info!("Encrypted plaintext secrets in {} stored channel configurations", sealed);
+    }
+    Ok(())
+}
+
+/// A channel revision snapshot with its secrets redacted, as returned by the API.
+pub fn redact_snapshot(dispatcher: &NotificationDispatcher, snapshot: &Value) -> Value {
+    match serde_json::from_value::<ChannelSnapshot>(snapshot.clone()) {
+        Ok(mut channel_snapshot) => {
+            channel_snapshot.configuration = dispatcher.redact_config(&channel_snapshot.provider_type, &channel_snapshot.configuration);
+            serde_json::to_value(channel_snapshot).unwrap_or(Value::Null)
+        }
+        Err(_) => Value::Null,
+    }
+}
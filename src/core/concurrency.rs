@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+use tracing::{error, warn};
+use crate::db::connection::DbConnection;
+use crate::db::run_repository::{create_job_run_triggered_at, save_run_with_events};
+use crate::dtos::run_event::RunEvent;
+use crate::errors::AppError;
+use crate::models::{ConcurrencyPolicy, JobConfig, JobRun, JobRunStatus, RunEventSource, RunEventType};
+use crate::notification::core::send_failed;
+use crate::notification::dispatcher::NotificationDispatcher;
+use crate::pubsub::publish_run_event;
+use crate::time_utils::get_utc_now;
+
+const CONCURRENCY_STAGE_NAME: &str = "concurrency";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunOrigin {
+    /// Triggered or reported through the api; under `Forbid` the run is rejected.
+    Api,
+    /// Expected by the scheduler for an occurrence; under `Forbid` it is still tracked so its deadlines are checked.
+    Scheduler,
+}
+
+impl From<RunOrigin> for RunEventSource {
+    fn from(origin: RunOrigin) -> Self {
+        match origin {
+            RunOrigin::Api => Self::Api,
+            RunOrigin::Scheduler => Self::Scheduler,
+        }
+    }
+}
+
+/// Starts a new run of the job next to its `in_flight_runs`, applying the job's concurrency policy first.
+pub async fn start_job_run(
+    conn: &mut DbConnection<'_>,
+    dispatcher: &NotificationDispatcher,
+    job_config: &JobConfig,
+    in_flight_runs: &[JobRun],
+    scheduled_for: Option<DateTime<Utc>>,
+    run_key: Option<String>,
+    origin: RunOrigin,
+) -> Result<JobRun, AppError> {
+    if let Some(running) = in_flight_runs.first() {
+        match job_config.concurrency_policy {
+            ConcurrencyPolicy::Allow => {}
+            ConcurrencyPolicy::Forbid => {
+                let message = format!(
+                    "A new run{} started while {} earlier run(s) are still in progress, the oldest triggered at {}",
+                    scheduled_for.map(|time| format!(" scheduled at {}", time)).unwrap_or_default(),
+                    in_flight_runs.len(),
+                    running.triggered_at,
+                );
+                warn!("{}-{}: {}", job_config.app_name, job_config.job_name, message);
+                if let Err(e) = send_failed(dispatcher, job_config, running, CONCURRENCY_STAGE_NAME, &message, &job_config.channel_ids).await {
+                    error!("Failed to send concurrency alert for job {}: {:?}", job_config.job_name, e);
+                }
+                if origin == RunOrigin::Api {
+                    return Err(AppError::Conflict(format!(
+                        "Job '{}' of application '{}' forbids concurrent runs, run {} is still in progress",
+                        job_config.job_name, job_config.app_name, running.id
+                    )));
+                }
+            }
+            ConcurrencyPolicy::Replace => {
+                for running in in_flight_runs.iter() {
+                    let mut replaced = running.clone();
+                    replaced.status = JobRunStatus::Replaced;
+                    let event = RunEvent::new(RunEventType::StatusChanged, &replaced, None, origin.into());
+                    save_run_with_events(conn, replaced, std::slice::from_ref(&event)).await?;
+                    publish_run_event(conn, event).await;
+                }
+            }
+        }
+    }
+
+    let (new_run, created) = create_job_run_triggered_at(conn, &job_config.app_name, &job_config.job_name, get_utc_now(), scheduled_for, run_key, origin.into()).await?;
+    publish_run_event(conn, created).await;
+    Ok(new_run)
+}
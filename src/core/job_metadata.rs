@@ -0,0 +1,42 @@
+use crate::db::application_repository::get_application_config;
+use crate::db::config_repository::get_job_config_by_app_name_and_job_name;
+use crate::db::connection::DbConnection;
+use crate::errors::AppError;
+use crate::models::JobMetadata;
+
+/// The metadata alerts of the job carry, its own merged with its application's. Jobs that aren't configured,
+/// e.g. ones named in an invalid stage update, get the application's alone.
+pub async fn get_job_metadata(
+    conn: &mut DbConnection<'_>,
+    app_name: &str,
+    job_name: &str,
+) -> Result<JobMetadata, AppError> {
+    let job_metadata = get_job_config_by_app_name_and_job_name(conn, app_name, job_name).await?
+        .map(|job_config| job_config.metadata.0)
+        .unwrap_or_default();
+    let application_metadata = get_application_config(conn, app_name).await?
+        .map(|application_config| application_config.metadata.0);
+
+    Ok(merge_metadata(job_metadata, application_metadata.as_ref()))
+}
+
+/// Fields the job sets take precedence over the application's, the tags of both are kept.
+pub fn merge_metadata(job_metadata: JobMetadata, application_metadata: Option<&JobMetadata>) -> JobMetadata {
+    let Some(application_metadata) = application_metadata else {
+        return job_metadata;
+    };
+
+    let mut tags = job_metadata.tags;
+    for tag in application_metadata.tags.iter() {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+
+    JobMetadata {
+        owner_team: job_metadata.owner_team.or_else(|| application_metadata.owner_team.clone()),
+        contact: job_metadata.contact.or_else(|| application_metadata.contact.clone()),
+        tags,
+        runbook_url: job_metadata.runbook_url.or_else(|| application_metadata.runbook_url.clone()),
+    }
+}
@@ -3,6 +3,7 @@ use std::ops::Add;
 use chrono::{DateTime, Duration, Utc};
 use chrono_tz::Tz;
 use tracing::{debug, warn};
+use crate::api::run_handler::JobRunStageType;
 use crate::cron_utils::get_min;
 use crate::models::{JobConfig, JobRun, JobRunStage, JobRunStageStatus, JobRunStatus};
 use crate::models::JobRunStageStatus::Missed;
@@ -26,23 +27,22 @@ pub fn detect_time_outs(
     sorted_job_stages
         .into_iter()
         .filter_map(|job_stage| {
-            // Get an owned copy of the existing stage, or create a new brand new one
-            let mut occurring_stage = occurring_stages_map
-                .get(&job_stage.name)
-                .cloned()
-                .unwrap_or_else(|| JobRunStage{
-                    name: job_stage.name.clone(),
-                    start_status: None,
-                    start_date_time: None,
-                    complete_status: None,
-                    complete_date_time: None,
-                });
+            // The latest attempt of the stage, timeouts are recorded on it as a new event
+            let latest_attempt = occurring_stages_map.get(&job_stage.name);
+            let mut occurring_stage = JobRunStage {
+                name: job_stage.name.clone(),
+                start_status: None,
+                start_date_time: None,
+                complete_status: None,
+                complete_date_time: None,
+                attempt: Some(latest_attempt.map(get_attempt).unwrap_or(1)),
+            };
 
             let mut updated = false;
 
             // Check Start Timeout
             if let Some(start_offset_secs) = job_stage.start {
-                if occurring_stage.start_status.is_none() {
+                if latest_attempt.and_then(|stage| stage.start_status.as_ref()).is_none() {
                     let deadline = job_start_time.add(Duration::seconds(start_offset_secs as i64));
                     if deadline < *current_time {
                         debug!("Detected start timeout for stage: {}", job_stage.name);
@@ -55,7 +55,7 @@ pub fn detect_time_outs(
 
             // Check Complete Timeout
             if let Some(complete_offset_secs) = job_stage.complete {
-                if occurring_stage.complete_status.is_none() {
+                if latest_attempt.and_then(|stage| stage.complete_status.as_ref()).is_none() {
                     let deadline = job_start_time.add(Duration::seconds(complete_offset_secs as i64));
                     if deadline < *current_time {
                         debug!("Detected complete timeout for stage: {}", job_stage.name);
@@ -71,57 +71,340 @@ pub fn detect_time_outs(
         .collect()
 }
 
+/// The latest attempt of every stage of the run, see [`get_latest_attempt`].
 pub fn get_event_stage_map(job_run: &JobRun) -> HashMap<String, JobRunStage> {
     let mut map = HashMap::new();
     for stage in job_run.stages.iter() {
-        map.entry(stage.name.clone())
-            .or_insert_with(|| stage.clone());
+        if !map.contains_key(&stage.name) {
+            map.extend(get_latest_attempt(&job_run.stages, &stage.name).map(|latest| (stage.name.clone(), latest)));
+        }
     }
     map
 }
 
+/// Every event of a stage is recorded separately; this merges the events of the stage's latest attempt into one
+/// stage holding its start and its completion.
+pub fn get_latest_attempt(stages: &[JobRunStage], stage_name: &str) -> Option<JobRunStage> {
+    let mut latest: Option<JobRunStage> = None;
+    for stage in stages.iter().filter(|stage| stage.name == stage_name) {
+        match latest.as_mut() {
+            Some(current) if get_attempt(stage) < get_attempt(current) => {}
+            Some(current) if get_attempt(stage) == get_attempt(current) => {
+                if stage.start_status.is_some() {
+                    current.start_status = stage.start_status.clone();
+                    current.start_date_time = stage.start_date_time;
+                }
+                if stage.complete_status.is_some() {
+                    current.complete_status = stage.complete_status.clone();
+                    current.complete_date_time = stage.complete_date_time;
+                }
+            }
+            _ => latest = Some(stage.clone()),
+        }
+    }
+    latest
+}
+
+/// The attempt a stage event reported without one belongs to. A start after the latest attempt ended begins the next
+/// one, other events belong to the latest attempt.
+pub fn get_event_attempt(stages: &[JobRunStage], stage_name: &str, stage_type: &JobRunStageType) -> u32 {
+    match get_latest_attempt(stages, stage_name) {
+        Some(latest) if *stage_type == JobRunStageType::Start && latest.complete_status.is_some() => get_attempt(&latest) + 1,
+        Some(latest) => get_attempt(&latest),
+        None => 1,
+    }
+}
+
+pub fn get_attempt(stage: &JobRunStage) -> u32 {
+    stage.attempt.unwrap_or(1)
+}
+
+/// Replaced, skipped and cancelled runs are no longer expected to report, their deadlines aren't checked.
+pub fn is_untracked(job_run: &JobRun) -> bool {
+    matches!(job_run.status, JobRunStatus::Replaced | JobRunStatus::Skipped | JobRunStatus::Cancelled)
+}
+
 pub fn get_status(job_config: &JobConfig, job_run: &JobRun) -> JobRunStatus {
-    if job_run.status != JobRunStatus::InProgress {
+    // A failed run is looked at again, the failed stage may have been retried since
+    if job_run.status == JobRunStatus::Complete || is_untracked(job_run) {
         return job_run.status.clone();
     }
 
-    let occurring_stages_map = get_event_stage_map(job_run);
+    // A missed deadline fails the run for good
+    for stage in job_run.stages.iter() {
+        if stage.start_status == Some(Missed) || stage.complete_status == Some(Missed) {
+            warn!("Job failed due to missed deadline in stage: {}", stage.name);
+            return JobRunStatus::Failed;
+        }
+    }
 
-    let mut is_missed = false;
+    // A reported failure (recorded as the completion) only counts while it ends the latest attempt of its stage
+    let latest_attempts = get_event_stage_map(job_run);
     for job_stage in job_config.stages.iter() {
-        // If the map doesn't contain the key
-        if !occurring_stages_map.contains_key(&job_stage.name) {
-            is_missed = true;
-            continue;
+        if let Some(latest_attempt) = latest_attempts.get(&job_stage.name)
+            && (latest_attempt.start_status == Some(JobRunStageStatus::Failed) || latest_attempt.complete_status == Some(JobRunStageStatus::Failed)) {
+            warn!("Job failed due to failed attempt {} of stage: {}", get_attempt(latest_attempt), job_stage.name);
+            return JobRunStatus::Failed;
         }
+    }
+
+    // A run only completes once its last checkpoint occurred, until then later events may still belong to it
+    if has_finished_all_stages(job_config, &job_run.stages) {
+        JobRunStatus::Complete
+    } else {
+        JobRunStatus::InProgress
+    }
+}
 
-        // We know it exists now, unwrap is safe
-        let occurring_stage = occurring_stages_map.get(&job_stage.name).unwrap();
+/// True when the latest stage event is the one that reported the last outstanding checkpoint of the run,
+/// i.e. every configured stage has now occurred (complete, or start for start-only stages).
+/// Late events recorded after a timeout count, so a run that recovers can resolve its earlier alerts.
+pub fn is_finished_by_latest_stage(job_config: &JobConfig, job_run: &JobRun) -> bool {
+    match job_run.stages.split_last() {
+        Some((_, previous_stages)) => has_finished_all_stages(job_config, &job_run.stages)
+            && !has_finished_all_stages(job_config, previous_stages),
+        None => false,
+    }
+}
 
-        // Check Start Status
-        // If job config requires start AND event has a start status recorded
-        if job_stage.start.is_some() && occurring_stage.start_status.is_some() {
-            // If that recorded status is not Occurred (e.g., it's Missed or Failed)
-            if *occurring_stage.start_status.as_ref().unwrap() != JobRunStageStatus::Occurred {
-                warn!("Job failed due to non-occurred start status in stage: {}", job_stage.name);
-                return JobRunStatus::Failed;
-            }
+fn has_finished_all_stages(job_config: &JobConfig, stages: &[JobRunStage]) -> bool {
+    job_config.stages.iter().all(|job_stage| {
+        stages.iter()
+            .filter(|stage| stage.name == job_stage.name)
+            .any(|stage| if job_stage.complete.is_some() {
+                stage.complete_status == Some(JobRunStageStatus::Occurred)
+            } else {
+                stage.start_status == Some(JobRunStageStatus::Occurred)
+            })
+    })
+}
+
+/// Whether the run was started for the occurrence at `start_time`. Runs created before runs were tied to their
+/// occurrence match on their trigger time, from the grace period before the occurrence until the one before the next.
+pub fn belongs_to_occurrence(job_run: &JobRun, start_time: &DateTime<Utc>, window_start: &DateTime<Utc>, window_end: &DateTime<Utc>) -> bool {
+    match job_run.scheduled_for {
+        Some(scheduled_for) => scheduled_for == *start_time,
+        None => job_run.triggered_at >= *window_start && job_run.triggered_at < *window_end,
+    }
+}
+
+/// Picks the run a stage event without a run id belongs to among the job's runs in progress, oldest first: the oldest
+/// run still waiting for the event. Starts also go to a run retrying the stage, and completions prefer a run that saw
+/// the stage's latest attempt start over one that never did.
+pub fn find_run_for_stage_event<'a>(in_flight_runs: &'a [JobRun], stage_name: &str, stage_type: &JobRunStageType) -> Option<&'a JobRun> {
+    let has_reported = |job_run: &JobRun, start: bool| {
+        get_latest_attempt(&job_run.stages, stage_name).is_some_and(|stage| {
+            let status = if start { &stage.start_status } else { &stage.complete_status };
+            matches!(status, Some(JobRunStageStatus::Occurred) | Some(JobRunStageStatus::Failed) | Some(JobRunStageStatus::Retrying))
+        })
+    };
+    let is_retrying = |job_run: &JobRun| {
+        get_latest_attempt(&job_run.stages, stage_name).is_some_and(|stage| stage.complete_status == Some(JobRunStageStatus::Retrying))
+    };
+
+    match stage_type {
+        JobRunStageType::Start => in_flight_runs.iter().find(|run| !has_reported(run, true) || is_retrying(run)),
+        JobRunStageType::Complete | JobRunStageType::Failed | JobRunStageType::Retrying => in_flight_runs.iter()
+            .find(|run| has_reported(run, true) && !has_reported(run, false))
+            .or_else(|| in_flight_runs.iter().find(|run| !has_reported(run, false))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+    use crate::models::{ConcurrencyPolicy, JobMetadata, JobStageConfig, Severity};
+    use JobRunStageStatus::{Failed, Occurred, Retrying};
+
+    fn job_config(stages: Vec<(&str, Option<u32>)>) -> JobConfig {
+        let stages = stages.into_iter()
+            .map(|(name, max_attempts)| JobStageConfig {
+                name: name.to_string(),
+                start: Some(60),
+                complete: Some(600),
+                max_attempts,
+                severity: Severity::default(),
+            })
+            .collect();
+        JobConfig {
+            app_name: "app1".to_string(),
+            job_name: "job1".to_string(),
+            schedule: None,
+            zone_id: None,
+            enabled: true,
+            stages: diesel_json::Json(stages),
+            channel_ids: "channel1".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            concurrency_policy: ConcurrencyPolicy::Allow,
+            blackout_windows: diesel_json::Json(vec![]),
+            metadata: diesel_json::Json(JobMetadata::default()),
+        }
+    }
+
+    fn job_run(stages: Vec<JobRunStage>) -> JobRun {
+        let triggered_at = Utc.with_ymd_and_hms(2026, 10, 19, 12, 0, 0).unwrap();
+        JobRun {
+            id: Uuid::new_v4(),
+            app_name: "app1".to_string(),
+            job_name: "job1".to_string(),
+            triggered_at,
+            status: JobRunStatus::InProgress,
+            stages: diesel_json::Json(stages),
+            created_at: triggered_at,
+            updated_at: triggered_at,
+            scheduled_for: None,
+            run_key: None,
+            status_reason: None,
         }
+    }
 
-        // Check Complete Status
-        // If job config requires complete AND event has a complete status recorded
-        if job_stage.complete.is_some() && occurring_stage.complete_status.is_some() {
-            // If that recorded status is not Occurred
-            if *occurring_stage.complete_status.as_ref().unwrap() != JobRunStageStatus::Occurred {
-                warn!("Job failed due to non-occurred complete status in stage: {}", job_stage.name);
-                return JobRunStatus::Failed;
-            }
+    fn started(name: &str, attempt: u32) -> JobRunStage {
+        stage(name, attempt, Some(Occurred), None)
+    }
+
+    fn ended(name: &str, attempt: u32, status: JobRunStageStatus) -> JobRunStage {
+        stage(name, attempt, None, Some(status))
+    }
+
+    fn stage(name: &str, attempt: u32, start_status: Option<JobRunStageStatus>, complete_status: Option<JobRunStageStatus>) -> JobRunStage {
+        let at = Utc.with_ymd_and_hms(2026, 10, 19, 12, 0, 0).unwrap();
+        JobRunStage {
+            name: name.to_string(),
+            start_date_time: start_status.as_ref().map(|_| at),
+            start_status,
+            complete_date_time: complete_status.as_ref().map(|_| at),
+            complete_status,
+            attempt: Some(attempt),
         }
     }
 
-    if is_missed {
-        JobRunStatus::InProgress
-    } else {
-        JobRunStatus::Complete
+    #[test]
+    fn latest_attempt_merges_its_start_and_completion() {
+        let stages = vec![started("load", 1), ended("load", 1, Retrying), started("load", 2), started("other", 1), ended("load", 2, Occurred)];
+
+        let latest = get_latest_attempt(&stages, "load").unwrap();
+        assert_eq!(latest.attempt, Some(2));
+        assert_eq!(latest.start_status, Some(Occurred));
+        assert_eq!(latest.complete_status, Some(Occurred));
+        assert!(get_latest_attempt(&stages, "missing").is_none());
+    }
+
+    #[test]
+    fn latest_attempt_ignores_late_events_of_earlier_attempts() {
+        let stages = vec![started("load", 1), started("load", 2), ended("load", 1, Retrying)];
+
+        let latest = get_latest_attempt(&stages, "load").unwrap();
+        assert_eq!(latest.attempt, Some(2));
+        assert_eq!(latest.complete_status, None);
+    }
+
+    #[test]
+    fn start_after_a_retry_opens_the_next_attempt() {
+        let mut stages = vec![started("load", 1)];
+        assert_eq!(get_event_attempt(&stages, "load", &JobRunStageType::Start), 1);
+        assert_eq!(get_event_attempt(&stages, "load", &JobRunStageType::Retrying), 1);
+
+        stages.push(ended("load", 1, Retrying));
+        assert_eq!(get_event_attempt(&stages, "load", &JobRunStageType::Start), 2);
+        // A completion without a new start still belongs to the attempt that was retried
+        assert_eq!(get_event_attempt(&stages, "load", &JobRunStageType::Complete), 1);
+
+        stages.push(started("load", 2));
+        assert_eq!(get_event_attempt(&stages, "load", &JobRunStageType::Start), 2);
+        assert_eq!(get_event_attempt(&stages, "load", &JobRunStageType::Failed), 2);
+        assert_eq!(get_event_attempt(&stages, "other", &JobRunStageType::Start), 1);
+    }
+
+    #[test]
+    fn run_fails_only_on_the_last_attempt() {
+        let job_config = job_config(vec![("load", Some(3))]);
+        let mut stages = vec![started("load", 1), ended("load", 1, Retrying), started("load", 2), ended("load", 2, Retrying)];
+        assert_eq!(get_status(&job_config, &job_run(stages.clone())), JobRunStatus::InProgress);
+
+        stages.extend([started("load", 3), ended("load", 3, Failed)]);
+        assert_eq!(get_status(&job_config, &job_run(stages)), JobRunStatus::Failed);
+    }
+
+    #[test]
+    fn failed_run_recovers_when_the_stage_is_retried() {
+        let job_config = job_config(vec![("load", None)]);
+        let mut stages = vec![started("load", 1), ended("load", 1, Failed)];
+        let mut failed_run = job_run(stages.clone());
+        failed_run.status = JobRunStatus::Failed;
+        assert_eq!(get_status(&job_config, &failed_run), JobRunStatus::Failed);
+
+        stages.push(started("load", 2));
+        failed_run.stages = diesel_json::Json(stages.clone());
+        assert_eq!(get_status(&job_config, &failed_run), JobRunStatus::InProgress);
+
+        stages.push(ended("load", 2, Occurred));
+        failed_run.stages = diesel_json::Json(stages);
+        assert_eq!(get_status(&job_config, &failed_run), JobRunStatus::Complete);
+    }
+
+    #[test]
+    fn missed_deadline_fails_the_run_for_good() {
+        let job_config = job_config(vec![("load", None)]);
+        let stages = vec![stage("load", 1, Some(JobRunStageStatus::Missed), None), started("load", 1), ended("load", 1, Occurred)];
+        assert_eq!(get_status(&job_config, &job_run(stages)), JobRunStatus::Failed);
+    }
+
+    #[test]
+    fn closed_runs_keep_their_status() {
+        let job_config = job_config(vec![("load", None)]);
+        let mut skipped_run = job_run(vec![started("load", 1), ended("load", 1, Failed)]);
+        skipped_run.status = JobRunStatus::Skipped;
+        assert_eq!(get_status(&job_config, &skipped_run), JobRunStatus::Skipped);
+    }
+
+    #[test]
+    fn start_goes_to_the_oldest_run_waiting_for_it() {
+        let runs = vec![
+            job_run(vec![started("load", 1)]),
+            job_run(vec![]),
+            job_run(vec![]),
+        ];
+        let found = find_run_for_stage_event(&runs, "load", &JobRunStageType::Start).unwrap();
+        assert_eq!(found.id, runs[1].id);
+    }
+
+    #[test]
+    fn start_goes_to_a_run_retrying_the_stage() {
+        let runs = vec![
+            job_run(vec![started("load", 1), ended("load", 1, Retrying)]),
+            job_run(vec![]),
+        ];
+        let found = find_run_for_stage_event(&runs, "load", &JobRunStageType::Start).unwrap();
+        assert_eq!(found.id, runs[0].id);
+    }
+
+    #[test]
+    fn completion_prefers_a_run_that_started_the_stage() {
+        let runs = vec![
+            job_run(vec![]),
+            job_run(vec![started("load", 1)]),
+        ];
+        for stage_type in [JobRunStageType::Complete, JobRunStageType::Failed, JobRunStageType::Retrying] {
+            let found = find_run_for_stage_event(&runs, "load", &stage_type).unwrap();
+            assert_eq!(found.id, runs[1].id);
+        }
+    }
+
+    #[test]
+    fn completion_without_a_started_run_goes_to_the_oldest_one_not_completed() {
+        let runs = vec![
+            job_run(vec![started("load", 1), ended("load", 1, Occurred)]),
+            job_run(vec![]),
+            job_run(vec![]),
+        ];
+        let found = find_run_for_stage_event(&runs, "load", &JobRunStageType::Complete).unwrap();
+        assert_eq!(found.id, runs[1].id);
+
+        let completed = vec![job_run(vec![started("load", 1), ended("load", 1, Occurred)])];
+        assert!(find_run_for_stage_event(&completed, "load", &JobRunStageType::Complete).is_none());
+        assert!(find_run_for_stage_event(&completed, "load", &JobRunStageType::Start).is_none());
     }
 }
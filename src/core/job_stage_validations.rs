@@ -44,3 +44,18 @@ pub fn check_valid_complete(job_config: &JobConfig, stage_name: &str) -> Result<
 
     Ok(())
 }
+
+// Runs are only tracked for `max_stage_duration_hours`, so later deadlines would never be detected
+pub fn check_stage_offsets(stages: &[JobStageConfig], max_stage_duration_hours: i32) -> Result<(), AppError> {
+    let max_offset_secs = max_stage_duration_hours.max(0) as u64 * 3600;
+
+    for stage in stages.iter() {
+        if [stage.start, stage.complete].iter().flatten().any(|offset| *offset > max_offset_secs) {
+            return Err(AppError::BadRequest(format!(
+                "offsets of stage {} exceed the max stage duration of {} hours", stage.name, max_stage_duration_hours
+            )));
+        }
+    }
+
+    Ok(())
+}
@@ -1,4 +1,13 @@
 pub mod entities;
 pub mod job_stage_validations;
 pub mod process_timeouts;
-pub mod job_run_matching;
\ No newline at end of file
+pub mod job_run_matching;
+pub mod schedule_forecast;
+pub mod retention;
+pub mod channel_secrets;
+pub mod concurrency;
+pub mod run_closing;
+pub mod alert_silencing;
+pub mod job_metadata;
+pub mod alert_routing;
+pub mod run_timeline;
\ No newline at end of file
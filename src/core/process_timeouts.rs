@@ -1,21 +1,29 @@
+use diesel_async::AsyncConnection;
+use diesel_async::scoped_futures::ScopedFutureExt;
 use tracing::{error, info, warn};
 use std::collections::{HashMap};
 use chrono::{Duration, DateTime, Utc};
 use chrono_tz::Tz;
 use chrono_tz::Tz::UTC;
-use linked_hash_map::LinkedHashMap;
 use crate::config::Config;
-use crate::core::job_run_matching::detect_time_outs;
+use crate::core::concurrency::{start_job_run, RunOrigin};
+use crate::core::job_run_matching::{belongs_to_occurrence, detect_time_outs, is_untracked};
 use crate::db::config_repository::get_all_enabled_configs;
 use crate::db::connection::{DbConnection, PgPool};
-use crate::db::run_repository::{create_new_job_run, get_all_pending_job_runs, save_run};
+use crate::db::run_repository::{create_job_run_triggered_at, get_all_pending_job_runs, get_job_run_by_id_for_update, get_job_runs_of_occurrence, save_run_with_events};
+use crate::db::schedule_checkpoint_repository::{get_schedule_checkpoint, save_schedule_checkpoint};
+use crate::dtos::run_event::RunEvent;
 use crate::errors::AppError;
-use crate::models::{JobConfig, JobRun, JobRunStage, JobRunStatus, Settings};
-use crate::cron_utils::{get_job_start_time, in_between};
+use crate::models::{ConcurrencyPolicy, JobConfig, JobRun, JobRunStageStatus, JobRunStatus, RunEventSource, RunEventType, Settings};
+use crate::cron_utils::{get_evaluation_window, get_execution_times_between, get_next_execution_times};
 use crate::notification::core::{send_timeout};
 use crate::notification::dispatcher::NotificationDispatcher;
+use crate::pubsub::publish_run_event;
 use crate::time_utils::{change_to_utc, get_tz, get_utc_now};
 
+/// Most missed occurrences evaluated per job in one pass; after a long outage only the latest ones are alerted on.
+const MAX_CATCH_UP_OCCURRENCES: usize = 50;
+
 pub async fn check_all_timeouts(
     pool: &PgPool,
     notification_dispatcher: &NotificationDispatcher,
@@ -36,80 +44,198 @@ pub async fn check_all_timeouts(
 
     let time_boundary = utc_now.checked_sub_signed(Duration::hours(settings.max_stage_duration_hours as i64));
     let pending_events: Vec<JobRun> = get_all_pending_job_runs(&mut conn, time_boundary.unwrap()).await?;
-    let latest_job_runs_by_name: HashMap<String, JobRun> = get_valid_events(&pending_events);
+    let mut job_runs_by_name: HashMap<String, Vec<JobRun>> = HashMap::new();
+    for job_run in pending_events.iter() {
+        job_runs_by_name.entry(format!("{}-{}", job_run.app_name, job_run.job_name))
+            .or_default()
+            .push(job_run.clone());
+    }
 
     let mut zoned_current_times: HashMap<Tz, DateTime<Tz>> = HashMap::default();
     zoned_current_times.insert(UTC, utc_now.with_timezone(&UTC));
 
     info!("utc time now: {}", utc_now);
-    process_scheduled_job_timeouts(&mut conn, &all_enabled_configs, &latest_job_runs_by_name, &mut zoned_current_times, &utc_now, notification_dispatcher, config).await;
+    process_missed_job_occurrences(&mut conn, &all_enabled_configs, &utc_now, notification_dispatcher, config).await;
+    process_scheduled_job_timeouts(&mut conn, &all_enabled_configs, job_runs_by_name, &mut zoned_current_times, &utc_now, notification_dispatcher, config).await;
     process_manual_job_timeouts(&mut conn, pending_events, &jobs_by_name, &utc_now, notification_dispatcher).await;
 
     Ok(())
 }
 
-fn get_valid_events(pending_events: &Vec<JobRun>) -> HashMap<String, JobRun> {
-    pending_events.into_iter().fold(HashMap::new(), |mut acc, job_run| {
-        acc.entry(format!("{}-{}", job_run.app_name, job_run.job_name))
-            .and_modify(|existing| {
-                // Compare based on the logic: if existing is older, replace it
-                if job_run.created_at > existing.created_at {
-                    *existing = job_run.clone();
-                }
-            })
-            .or_insert(job_run.clone());
-        acc
-    })
-}
-
+/// Evaluates every occurrence whose window is still open. Runs of a job may overlap, so each occurrence is checked
+/// against its own run; an occurrence without one gets a run, subject to the job's concurrency policy.
 async fn process_scheduled_job_timeouts(
     conn: &mut DbConnection<'_>,
-    all_enabled_jobs: &Vec<JobConfig>,
-    latest_job_runs_by_name: &HashMap<String, JobRun>,
+    all_enabled_jobs: &[JobConfig],
+    mut job_runs_by_name: HashMap<String, Vec<JobRun>>,
     zoned_current_times: &mut HashMap<Tz, DateTime<Tz>>,
     utc_now: &DateTime<Utc>,
     notification_dispatcher: &NotificationDispatcher,
     config: &Config
 ) {
     for job_config in all_enabled_jobs.iter().filter(|job| job.schedule.is_some()) {
-        if let Some(zone) = &job_config.zone_id && let Ok(tz) = get_tz(&zone) {
-            let zoned_time_now = zoned_current_times
-                .entry(tz)
-                .or_insert_with(|| {utc_now.with_timezone(&tz)});
-            match in_between(job_config, *zoned_time_now, config.scheduler_fixed_delay_seconds) {
-                Err(e) => error!("Time delta calculation failed for {}: {:?}", job_config.job_name, e),
-                Ok(true) => {
-                    if let Ok(job_start_time) = get_job_start_time(job_config, zoned_time_now) {
-                        let job_config_key = format!("{}-{}", job_config.app_name, job_config.job_name);
-
-                        let grace_threshold = job_start_time - Duration::seconds(config.grace_time_seconds);
-
-                        // let job_run_option = latest_job_runs_by_name.get(&job_config_key).cloned();
-                        let mut job_run = match latest_job_runs_by_name.get(&job_config_key) {
-                            // Case 1: Job exists AND is within the grace period
-                            Some(existing) if existing.created_at >= grace_threshold => existing.clone(),
-
-                            // Case 2: Job doesn't exist OR it's older than the grace period
-                            _ => {
-                                match create_new_job_run(conn, &job_config.app_name, &job_config.job_name).await {
-                                    Ok(new_run) => new_run,
-                                    Err(_) => {
-                                        error!("Failed to insert job_run for job: {}", job_config.job_name);
-                                        continue; // Note: Ensure this is inside a loop
-                                    }
-                                }
+        let (Some(cron), Some(zone)) = (&job_config.schedule, &job_config.zone_id) else { continue };
+        let Ok(tz) = get_tz(zone) else { continue };
+        let Some(window) = get_evaluation_window(job_config, config.scheduler_fixed_delay_seconds) else {
+            error!("Skipping job {} with a stage without deadlines", job_config.job_name);
+            continue;
+        };
+        let Some(window_opened_after) = utc_now.checked_sub_signed(window) else { continue };
+
+        let zoned_time_now = *zoned_current_times
+            .entry(tz)
+            .or_insert_with(|| {utc_now.with_timezone(&tz)});
+        let occurrences = match get_execution_times_between(cron, &window_opened_after.with_timezone(&tz), &zoned_time_now) {
+            Ok(occurrences) => occurrences,
+            Err(e) => {
+                error!("Time delta calculation failed for {}: {:?}", job_config.job_name, e);
+                continue;
+            }
+        };
+
+        let job_config_key = format!("{}-{}", job_config.app_name, job_config.job_name);
+        let job_runs = job_runs_by_name.entry(job_config_key).or_default();
+        let grace = Duration::seconds(config.grace_time_seconds);
+
+        for occurrence in occurrences {
+            let start_time = occurrence.with_timezone(&Utc);
+            let next_start_time = get_next_execution_times(cron, &occurrence, 1).ok()
+                .and_then(|times| times.first().map(|next| next.with_timezone(&Utc)))
+                .unwrap_or(*utc_now);
+
+            let occurrence_run = job_runs.iter()
+                .filter(|run| belongs_to_occurrence(run, &start_time, &(start_time - grace), &(next_start_time - grace)))
+                .max_by_key(|run| run.created_at)
+                .cloned();
+
+            let mut job_run = match occurrence_run {
+                Some(existing) if is_untracked(&existing) => continue,
+                Some(existing) => existing,
+                None => {
+                    let in_flight_runs: Vec<JobRun> = job_runs.iter()
+                        .filter(|run| run.status == JobRunStatus::InProgress)
+                        .cloned()
+                        .collect();
+                    match start_job_run(conn, notification_dispatcher, job_config, &in_flight_runs, Some(start_time), None, RunOrigin::Scheduler).await {
+                        Ok(new_run) => {
+                            if job_config.concurrency_policy == ConcurrencyPolicy::Replace {
+                                job_runs.iter_mut()
+                                    .filter(|run| run.status == JobRunStatus::InProgress)
+                                    .for_each(|run| run.status = JobRunStatus::Replaced);
                             }
-                        };
+                            job_runs.push(new_run.clone());
+                            new_run
+                        },
+                        Err(e) => {
+                            error!("Failed to insert job_run for job {}: {:?}", job_config.job_name, e);
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            update_event_stages(conn, job_config, &zoned_time_now, &occurrence, &mut job_run, notification_dispatcher, None).await;
+        }
+    }
+}
+
+/// Evaluates the occurrences whose window closed without a pass seeing it, e.g. while watchdog was down or the
+/// scheduler was stalled. Every job keeps a checkpoint of the occurrences evaluated so far; occurrences between the
+/// checkpoint and the start of the windows still open get a run if they have none, and their timeouts are alerted
+/// on as late. Evaluation is idempotent, stages already marked missed are not alerted on again.
+async fn process_missed_job_occurrences(
+    conn: &mut DbConnection<'_>,
+    all_enabled_jobs: &[JobConfig],
+    utc_now: &DateTime<Utc>,
+    notification_dispatcher: &NotificationDispatcher,
+    config: &Config
+) {
+    'jobs: for job_config in all_enabled_jobs.iter().filter(|job| job.schedule.is_some()) {
+        let (Some(cron), Some(zone)) = (&job_config.schedule, &job_config.zone_id) else { continue };
+        let Ok(tz) = get_tz(zone) else { continue };
+        let Some(window) = get_evaluation_window(job_config, config.scheduler_fixed_delay_seconds) else { continue };
+        // Occurrences up to here are out of the regular pass's reach
+        let Some(boundary) = utc_now.checked_sub_signed(window) else { continue };
+
+        let checkpoint = match get_schedule_checkpoint(conn, &job_config.app_name, &job_config.job_name).await {
+            Ok(checkpoint) => checkpoint,
+            Err(e) => {
+                error!("Failed to load schedule checkpoint for job {}: {:?}", job_config.job_name, e);
+                continue;
+            }
+        };
+
+        // A job seen for the first time starts from now, its history is not backfilled
+        let evaluated_until = match checkpoint {
+            Some(checkpoint) if checkpoint.evaluated_until < boundary => checkpoint.evaluated_until,
+            Some(_) => continue,
+            None => {
+                if let Err(e) = save_schedule_checkpoint(conn, &job_config.app_name, &job_config.job_name, boundary).await {
+                    error!("Failed to save schedule checkpoint for job {}: {:?}", job_config.job_name, e);
+                }
+                continue;
+            }
+        };
 
-                        update_event_stages(conn, job_config, zoned_time_now, &job_start_time, &mut job_run, notification_dispatcher).await;
+        let mut occurrences = match get_execution_times_between(cron, &evaluated_until.with_timezone(&tz), &boundary.with_timezone(&tz)) {
+            Ok(occurrences) => occurrences,
+            Err(e) => {
+                error!("Failed to list missed occurrences for job {}: {:?}", job_config.job_name, e);
+                continue;
+            }
+        };
+        if occurrences.len() > MAX_CATCH_UP_OCCURRENCES {
+            let skipped = occurrences.len() - MAX_CATCH_UP_OCCURRENCES;
+            warn!("Skipping the {} oldest missed occurrences of job {}, evaluating the latest {}", skipped, job_config.job_name, MAX_CATCH_UP_OCCURRENCES);
+            occurrences.drain(..skipped);
+        }
 
-                    } else {
-                        // handle_failure(notification_dispatcher, job_config, &f).await;
-                        error!("Failed to get job start time for job: {}", job_config.job_name);
+        let grace = Duration::seconds(config.grace_time_seconds);
+        let zoned_time_now = utc_now.with_timezone(&tz);
+        for occurrence in occurrences {
+            // A run belongs to the occurrence it was triggered for, up to the next one
+            let next_occurrence = get_next_execution_times(cron, &occurrence, 1).ok()
+                .and_then(|times| times.first().cloned())
+                .map(|next| next.with_timezone(&Utc))
+                .unwrap_or(*utc_now);
+            let runs = get_job_runs_of_occurrence(
+                conn,
+                &job_config.app_name,
+                &job_config.job_name,
+                occurrence.with_timezone(&Utc),
+                occurrence.with_timezone(&Utc) - grace,
+                next_occurrence - grace,
+            ).await;
+
+            let mut job_run = match runs.map(|runs| runs.into_iter().next()) {
+                Ok(Some(existing)) if is_untracked(&existing) => continue,
+                Ok(Some(existing)) => existing,
+                Ok(None) => match create_job_run_triggered_at(conn, &job_config.app_name, &job_config.job_name, occurrence.with_timezone(&Utc), Some(occurrence.with_timezone(&Utc)), None, RunEventSource::Scheduler).await {
+                    Ok((new_run, created)) => {
+                        publish_run_event(conn, created).await;
+                        new_run
+                    },
+                    Err(e) => {
+                        error!("Failed to insert missed job_run for job {}: {:?}", job_config.job_name, e);
+                        continue 'jobs;
                     }
                 },
-                _ => {}
-            }
+                Err(e) => {
+                    error!("Failed to load job_runs for job {}: {:?}", job_config.job_name, e);
+                    continue 'jobs;
+                }
+            };
+
+            warn!("Evaluating missed occurrence {} of job {}", occurrence, job_config.job_name);
+            let alert_message = format!(
+                "Late alert: the run scheduled at {} was not evaluated in time, watchdog was unavailable. Detected at {}",
+                occurrence, zoned_time_now,
+            );
+            update_event_stages(conn, job_config, &zoned_time_now, &occurrence, &mut job_run, notification_dispatcher, Some(alert_message)).await;
+        }
+
+        if let Err(e) = save_schedule_checkpoint(conn, &job_config.app_name, &job_config.job_name, boundary).await {
+            error!("Failed to save schedule checkpoint for job {}: {:?}", job_config.job_name, e);
         }
     }
 }
@@ -121,7 +247,7 @@ async fn process_manual_job_timeouts(
     utc_now: &DateTime<Utc>,
     notification_dispatcher: &NotificationDispatcher,
 ) {
-    for mut job_run in pending_events.into_iter().filter(|e| e.status != JobRunStatus::Complete) {
+    for mut job_run in pending_events.into_iter().filter(|e| e.status != JobRunStatus::Complete && !is_untracked(e)) {
 
         let job_config_key = format!("{}-{}", job_run.app_name, job_run.job_name);
         let job_config_option = jobs_by_name.get(&job_config_key);
@@ -130,7 +256,7 @@ async fn process_manual_job_timeouts(
             let job_start_time = job_run.triggered_at.with_timezone(&UTC);
             let tz_utc_now = utc_now.with_timezone(&UTC);
 
-            update_event_stages(conn, job_config, &tz_utc_now, &job_start_time, &mut job_run, notification_dispatcher).await;
+            update_event_stages(conn, job_config, &tz_utc_now, &job_start_time, &mut job_run, notification_dispatcher, None).await;
             //handle_failure(notification_dispatcher, job_config, &f).await;
         }
     }
@@ -143,39 +269,69 @@ async fn update_event_stages(
     job_start_time: &DateTime<Tz>,
     job_run: &mut JobRun,
     notification_dispatcher: &NotificationDispatcher,
+    alert_message: Option<String>,
 ) {
-    let event_stages = detect_time_outs(job_config, job_run, zoned_time_now, job_start_time);
-
-    warn!("timeout detected for stages: {:?}", event_stages);
-
-    if !event_stages.is_empty() {
-        job_run.stages = diesel_json::Json(combine(&job_run.stages, event_stages.clone()));
-        job_run.status = JobRunStatus::Failed;
-        job_run.updated_at = change_to_utc(&zoned_time_now).unwrap();
+    let (job_config_clone, run_id, time_now, start_time) = (job_config.clone(), job_run.id, *zoned_time_now, *job_start_time);
+    let result = conn.transaction(|conn| async move {
+        // Timeouts are detected on the locked run, the run read at the start of the pass may have been updated,
+        // skipped or cancelled since
+        let Some(mut fresh_run) = get_job_run_by_id_for_update(conn, &run_id).await? else {
+            return Ok::<_, AppError>(None);
+        };
+        if fresh_run.status == JobRunStatus::Complete || is_untracked(&fresh_run) {
+            return Ok(Some((fresh_run, vec![], vec![])));
+        }
 
-        if let Err(e) = save_run(conn, job_run.clone()).await {
-            error!("Failed to save job_run {}: {:?}", job_run.job_name, e);
-            return;
+        let event_stages = detect_time_outs(&job_config_clone, &fresh_run, &time_now, &start_time);
+        if event_stages.is_empty() {
+            return Ok(Some((fresh_run, event_stages, vec![])));
         }
 
+        warn!("timeout detected for stages: {:?}", event_stages);
+        let previous_status = fresh_run.status.clone();
+        fresh_run.status = JobRunStatus::Failed;
+        fresh_run.updated_at = change_to_utc(&time_now).unwrap();
+
+        // Recorded as events of the stage's latest attempt, next to its earlier ones
+        let mut events = vec![];
         for event_stage in event_stages.iter() {
-            info!("in event timeout: {:?}", event_stage);
-            let _ = send_timeout(&notification_dispatcher, &job_config, &job_run, &event_stage.name).await;
+            let missed_checkpoints = [
+                (&event_stage.start_status, RunEventType::StartMissed, "start"),
+                (&event_stage.complete_status, RunEventType::CompletionMissed, "completion"),
+            ];
+            for (status, event_type, missed) in missed_checkpoints {
+                if *status == Some(JobRunStageStatus::Missed) {
+                    events.push(RunEvent::new(event_type, &fresh_run, Some(&event_stage.name), RunEventSource::Scheduler)
+                        .with_attempt(event_stage.attempt)
+                        .with_message(Some(format!("{} of stage {} was not reported in time", missed, event_stage.name))));
+                }
+            }
+        }
+        if previous_status != JobRunStatus::Failed {
+            events.push(RunEvent::new(RunEventType::StatusChanged, &fresh_run, None, RunEventSource::Scheduler));
         }
-    }
-}
 
-fn combine(stages1: &Vec<JobRunStage>, stages2: Vec<JobRunStage>) -> Vec<JobRunStage> {
-    let mut combined_map = LinkedHashMap::new();
+        let saved = save_run_with_events(conn, fresh_run, &events).await?;
+        Ok(Some((saved, event_stages, events)))
+    }.scope_boxed()).await;
 
-    for stage in stages1.iter() {
-        combined_map.insert(stage.name.clone(), stage.clone());
+    let (saved, event_stages, events) = match result {
+        Ok(Some(updated)) => updated,
+        Ok(None) => return,
+        Err(e) => {
+            error!("Failed to save job_run {}: {:?}", job_run.job_name, e);
+            return;
+        }
+    };
+    *job_run = saved;
+    for event in events {
+        publish_run_event(conn, event).await;
     }
 
-    // Iterate through the second list and overwrite entries with matching names
-    for stage in stages2.into_iter() {
-        combined_map.insert(stage.name.clone(), stage);
+    // Alerts go out once the run is committed
+    for event_stage in event_stages.iter() {
+        if let Err(e) = send_timeout(notification_dispatcher, job_config, job_run, &event_stage.name, alert_message.clone()).await {
+            error!("Failed to send timeout notification for {} stage {}: {:?}", job_run.job_name, event_stage.name, e);
+        }
     }
-
-    combined_map.values().cloned().collect()
 }
@@ -0,0 +1,19 @@
+use chrono::Duration;
+use tracing::info;
+use crate::db::audit_repository::delete_audit_logs_before;
+use crate::db::connection::PgPool;
+use crate::errors::AppError;
+use crate::models::Settings;
+use crate::time_utils::get_utc_now;
+
+pub async fn purge_expired_audit_logs(pool: &PgPool, settings: &Settings) -> Result<(), AppError> {
+    let mut conn = pool.get().await?;
+
+    let time_boundary = get_utc_now() - Duration::days(settings.audit_retention_days as i64);
+    let deleted = delete_audit_logs_before(&mut conn, time_boundary).await?;
+
+    if deleted > 0 {
+        info!("Purged {} audit logs older than {}", deleted, time_boundary);
+    }
+    Ok(())
+}
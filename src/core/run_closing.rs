@@ -0,0 +1,81 @@
+use chrono::{DateTime, Duration, Utc};
+use diesel_async::AsyncConnection;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use tracing::info;
+use crate::cron_utils::get_next_execution_times;
+use crate::db::connection::DbConnection;
+use crate::db::run_repository::{create_job_run_triggered_at, get_job_run_by_id_for_update, get_job_runs_of_occurrence, save_run_with_events};
+use crate::dtos::run_event::RunEvent;
+use crate::errors::AppError;
+use crate::models::{JobConfig, JobRun, JobRunStatus, RunEventSource, RunEventType};
+use crate::pubsub::publish_run_event;
+use crate::time_utils::get_tz;
+
+/// Marks the run `Skipped` or `Cancelled` with the reason. Only runs in progress, or failed ones whose alerts turned
+/// out to be expected, can be closed.
+pub async fn close_job_run(
+    conn: &mut DbConnection<'_>,
+    job_run: JobRun,
+    status: JobRunStatus,
+    reason: &str,
+) -> Result<JobRun, AppError> {
+    let reason = reason.to_string();
+    let (closed, event) = conn.transaction(|conn| async move {
+        // The status is checked on the locked run, a stage update or timeout pass may have changed it since it was read
+        let mut job_run = get_job_run_by_id_for_update(conn, &job_run.id).await?
+            .ok_or_else(|| AppError::NotFound(format!("job run not found for id: {}", job_run.id)))?;
+        if !matches!(job_run.status, JobRunStatus::InProgress | JobRunStatus::Failed) {
+            return Err(AppError::Conflict(format!("Run {} is already {:?}", job_run.id, job_run.status)));
+        }
+
+        info!("Marking run {} of job {}-{} {:?}: {}", job_run.id, job_run.app_name, job_run.job_name, status, reason);
+        job_run.status = status;
+        job_run.status_reason = Some(reason.clone());
+        let event = RunEvent::new(RunEventType::StatusChanged, &job_run, None, RunEventSource::Api).with_message(Some(reason));
+        let closed = save_run_with_events(conn, job_run, std::slice::from_ref(&event)).await?;
+        Ok::<_, AppError>((closed, event))
+    }.scope_boxed()).await?;
+
+    publish_run_event(conn, event).await;
+    Ok(closed)
+}
+
+/// Closes the runs of the occurrence at `scheduled_for`. An occurrence without a run yet, e.g. one still ahead, gets
+/// one that is closed right away, so the scheduler finds it instead of expecting the run.
+pub async fn close_occurrence(
+    conn: &mut DbConnection<'_>,
+    job_config: &JobConfig,
+    scheduled_for: DateTime<Utc>,
+    grace_time_seconds: i64,
+    status: JobRunStatus,
+    reason: &str,
+) -> Result<JobRun, AppError> {
+    let (app_name, job_name) = (&job_config.app_name, &job_config.job_name);
+    let (Some(schedule), Some(zone_id)) = (&job_config.schedule, &job_config.zone_id) else {
+        return Err(AppError::BadRequest(format!("Job {}-{} has no schedule, its runs are closed by id", app_name, job_name)));
+    };
+
+    let occurrence = scheduled_for.with_timezone(&get_tz(zone_id)?);
+    let mut next_occurrences = get_next_execution_times(schedule, &(occurrence - Duration::seconds(1)), 2)?.into_iter();
+    if next_occurrences.next() != Some(occurrence) {
+        return Err(AppError::BadRequest(format!("{} is not an occurrence of job {}-{} ({})", scheduled_for, app_name, job_name, schedule)));
+    }
+    let next_occurrence = next_occurrences.next().map(|next| next.with_timezone(&Utc)).unwrap_or(scheduled_for);
+    let grace = Duration::seconds(grace_time_seconds);
+
+    let runs = get_job_runs_of_occurrence(conn, app_name, job_name, scheduled_for, scheduled_for - grace, next_occurrence - grace).await?;
+    if runs.is_empty() {
+        let (new_run, created) = create_job_run_triggered_at(conn, app_name, job_name, scheduled_for, Some(scheduled_for), None, RunEventSource::Api).await?;
+        publish_run_event(conn, created).await;
+        return close_job_run(conn, new_run, status, reason).await;
+    }
+
+    // Runs come latest first, the latest one is returned
+    let mut closed_runs = vec![];
+    for job_run in runs.iter().filter(|run| matches!(run.status, JobRunStatus::InProgress | JobRunStatus::Failed)) {
+        closed_runs.push(close_job_run(conn, job_run.clone(), status.clone(), reason).await?);
+    }
+    closed_runs.into_iter().next().ok_or_else(|| AppError::Conflict(format!(
+        "The run of job {}-{} scheduled at {} is already {:?}", app_name, job_name, scheduled_for, runs[0].status
+    )))
+}
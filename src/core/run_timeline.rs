@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+use crate::dtos::run_event::{JobRunEventDto, StageAttemptDto, StageAttemptStatus};
+use crate::models::{JobRunStage, JobRunStageStatus, RunEventType};
+
+/// Folds the run's events, in the order they were recorded, into the attempts of its stages. Later events of an
+/// attempt override what earlier ones said about it.
+pub fn get_stage_attempts(events: &[JobRunEventDto]) -> Vec<StageAttemptDto> {
+    let mut stage_attempts: Vec<StageAttemptDto> = vec![];
+
+    for event in events {
+        let Some(stage_name) = &event.stage_name else {
+            continue;
+        };
+        let status = match event.event_type {
+            RunEventType::StageStarted => StageAttemptStatus::Running,
+            RunEventType::StageCompleted => StageAttemptStatus::Completed,
+            RunEventType::StageRetrying => StageAttemptStatus::Retrying,
+            RunEventType::StageFailed => StageAttemptStatus::Failed,
+            RunEventType::StartMissed | RunEventType::CompletionMissed => StageAttemptStatus::TimedOut,
+            RunEventType::RunCreated | RunEventType::StatusChanged => continue,
+        };
+
+        let index = match stage_attempts.iter().position(|stage| stage.name == *stage_name && stage.attempt == event.attempt) {
+            Some(index) => index,
+            None => {
+                stage_attempts.push(StageAttemptDto {
+                    name: stage_name.clone(),
+                    attempt: event.attempt,
+                    status: status.clone(),
+                    started_at: None,
+                    ended_at: None,
+                    message: None,
+                });
+                stage_attempts.len() - 1
+            }
+        };
+        let stage_attempt = &mut stage_attempts[index];
+
+        match status {
+            StageAttemptStatus::Running => stage_attempt.started_at = Some(event.occurred_at),
+            StageAttemptStatus::Completed | StageAttemptStatus::Retrying | StageAttemptStatus::Failed => stage_attempt.ended_at = Some(event.occurred_at),
+            StageAttemptStatus::TimedOut => {}
+        }
+        stage_attempt.status = status;
+        if event.message.is_some() {
+            stage_attempt.message = event.message.clone();
+        }
+    }
+    stage_attempts
+}
+
+/// The stages of the run, one for every stage event in the order they were recorded, see [`get_run_stage`].
+pub fn get_run_stages(events: &[JobRunEventDto]) -> Vec<JobRunStage> {
+    events.iter()
+        .filter_map(|event| get_run_stage(&event.event_type, event.stage_name.as_ref(), event.attempt, event.occurred_at))
+        .collect()
+}
+
+/// A stage event as the run stage recording it: starts and missed starts fill the start, the other events the
+/// completion. `None` for events not about a stage.
+pub fn get_run_stage(event_type: &RunEventType, stage_name: Option<&String>, attempt: Option<u32>, occurred_at: DateTime<Utc>) -> Option<JobRunStage> {
+    let (is_start, status) = match event_type {
+        RunEventType::StageStarted => (true, JobRunStageStatus::Occurred),
+        RunEventType::StartMissed => (true, JobRunStageStatus::Missed),
+        RunEventType::StageCompleted => (false, JobRunStageStatus::Occurred),
+        RunEventType::StageFailed => (false, JobRunStageStatus::Failed),
+        RunEventType::StageRetrying => (false, JobRunStageStatus::Retrying),
+        RunEventType::CompletionMissed => (false, JobRunStageStatus::Missed),
+        RunEventType::RunCreated | RunEventType::StatusChanged => return None,
+    };
+
+    let mut stage = JobRunStage {
+        name: stage_name?.clone(),
+        start_status: None,
+        start_date_time: None,
+        complete_status: None,
+        complete_date_time: None,
+        attempt,
+    };
+    if is_start {
+        stage.start_status = Some(status);
+        stage.start_date_time = Some(occurred_at);
+    } else {
+        stage.complete_status = Some(status);
+        stage.complete_date_time = Some(occurred_at);
+    }
+    Some(stage)
+}
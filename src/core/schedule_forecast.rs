@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use chrono_tz::Tz::UTC;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use crate::core::job_run_matching::{belongs_to_occurrence, get_event_stage_map};
+use crate::cron_utils::{get_execution_times_between, get_next_execution_times, get_stage_deadline};
+use crate::errors::AppError;
+use crate::models::{JobConfig, JobRun, JobRunStageStatus, JobRunStatus};
+use crate::time_utils::get_tz;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ForecastStatus {
+    Pending,
+    Running,
+    Done,
+    Late,
+    Failed,
+    Replaced,
+    Skipped,
+    Cancelled,
+}
+
+#[derive(Debug, Clone)]
+pub struct ForecastEntry {
+    pub job_config: JobConfig,
+    pub start_time: DateTime<Tz>,
+    pub scheduled: bool,
+    pub job_run: Option<JobRun>,
+    pub status: ForecastStatus,
+}
+
+/// Expands the schedules of all enabled jobs within the window and attaches the run that belongs to each
+/// occurrence. Runs of unscheduled jobs are listed on their own.
+pub fn build_forecast(
+    job_configs: &[JobConfig],
+    job_runs: Vec<JobRun>,
+    from: &DateTime<Utc>,
+    to: &DateTime<Utc>,
+    utc_now: &DateTime<Utc>,
+    grace_time_seconds: i64,
+) -> Vec<ForecastEntry> {
+    let mut runs_by_name: HashMap<String, Vec<JobRun>> = HashMap::new();
+    for job_run in job_runs.into_iter() {
+        runs_by_name.entry(format!("{}-{}", job_run.app_name, job_run.job_name))
+            .or_default()
+            .push(job_run);
+    }
+    for runs in runs_by_name.values_mut() {
+        runs.sort_by_key(|run| run.triggered_at);
+    }
+
+    let mut entries = vec![];
+    for job_config in job_configs.iter().filter(|job_config| job_config.enabled) {
+        let job_config_key = format!("{}-{}", job_config.app_name, job_config.job_name);
+        let mut job_runs = runs_by_name.remove(&job_config_key).unwrap_or_default();
+
+        let tz = match job_config.zone_id.as_ref().map(get_tz) {
+            Some(Ok(tz)) => tz,
+            Some(Err(e)) => {
+                error!("Skipping forecast for {}: {:?}", job_config_key, e);
+                continue;
+            }
+            None => UTC,
+        };
+
+        if let Some(schedule) = job_config.schedule.as_ref() {
+            let occurrences = match expected_occurrences(schedule, &from.with_timezone(&tz), &to.with_timezone(&tz)) {
+                Ok(occurrences) => occurrences,
+                Err(e) => {
+                    error!("Skipping forecast for {}: {:?}", job_config_key, e);
+                    continue;
+                }
+            };
+
+            for window in occurrences.windows(2) {
+                let (start_time, next_start_time) = (window[0], window[1]);
+                let window_start = start_time - Duration::seconds(grace_time_seconds);
+                let window_end = next_start_time - Duration::seconds(grace_time_seconds);
+
+                let job_run = job_runs.iter()
+                    .position(|run| belongs_to_occurrence(run, &start_time.with_timezone(&Utc), &window_start.with_timezone(&Utc), &window_end.with_timezone(&Utc)))
+                    .map(|index| job_runs.remove(index));
+
+                entries.push(new_entry(job_config, start_time, true, job_run, utc_now));
+            }
+        } else {
+            // Unscheduled runs are tracked relative to their trigger time, like in process_manual_job_timeouts
+            for job_run in job_runs.into_iter() {
+                let start_time = job_run.triggered_at.with_timezone(&tz);
+                entries.push(new_entry(job_config, start_time, false, Some(job_run), utc_now));
+            }
+        }
+    }
+
+    entries.sort_by_key(|entry| entry.start_time.with_timezone(&Utc));
+    entries
+}
+
+// Occurrences within the window plus the one following it, which bounds the last occurrence's runs
+fn expected_occurrences(schedule: &str, from: &DateTime<Tz>, to: &DateTime<Tz>) -> Result<Vec<DateTime<Tz>>, AppError> {
+    // The window includes an occurrence right at its start, e.g. at midnight for the current day
+    let mut occurrences = get_execution_times_between(schedule, &(*from - Duration::seconds(1)), to)?;
+    let bound = occurrences.last().unwrap_or(to);
+    occurrences.extend(get_next_execution_times(schedule, bound, 1)?);
+    Ok(occurrences)
+}
+
+fn new_entry(job_config: &JobConfig, start_time: DateTime<Tz>, scheduled: bool, job_run: Option<JobRun>, utc_now: &DateTime<Utc>) -> ForecastEntry {
+    let status = get_forecast_status(job_config, &start_time, job_run.as_ref(), utc_now);
+    ForecastEntry {
+        job_config: job_config.clone(),
+        start_time,
+        scheduled,
+        job_run,
+        status,
+    }
+}
+
+pub fn get_forecast_status(job_config: &JobConfig, start_time: &DateTime<Tz>, job_run: Option<&JobRun>, utc_now: &DateTime<Utc>) -> ForecastStatus {
+    let occurring_stages_map = job_run.map(get_event_stage_map).unwrap_or_default();
+
+    if let Some(run) = job_run {
+        match run.status {
+            JobRunStatus::Complete => return ForecastStatus::Done,
+            JobRunStatus::Failed => {
+                let has_failed_stage = run.stages.iter().any(|stage| {
+                    stage.start_status == Some(JobRunStageStatus::Failed) || stage.complete_status == Some(JobRunStageStatus::Failed)
+                });
+                return if has_failed_stage { ForecastStatus::Failed } else { ForecastStatus::Late };
+            }
+            JobRunStatus::Replaced => return ForecastStatus::Replaced,
+            JobRunStatus::Skipped => return ForecastStatus::Skipped,
+            JobRunStatus::Cancelled => return ForecastStatus::Cancelled,
+            JobRunStatus::InProgress => {}
+        }
+    }
+
+    let is_late = job_config.stages.iter().any(|job_stage| {
+        let occurring_stage = occurring_stages_map.get(&job_stage.name);
+        let start_pending = occurring_stage.and_then(|stage| stage.start_status.as_ref()).is_none();
+        let complete_pending = occurring_stage.and_then(|stage| stage.complete_status.as_ref()).is_none();
+
+        let is_overdue = |offset: Option<u64>| {
+            get_stage_deadline(start_time, offset).is_some_and(|deadline| deadline < *utc_now)
+        };
+
+        (start_pending && is_overdue(job_stage.start)) || (complete_pending && is_overdue(job_stage.complete))
+    });
+
+    if is_late {
+        ForecastStatus::Late
+    } else if job_run.is_some() {
+        ForecastStatus::Running
+    } else {
+        ForecastStatus::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+    use crate::models::{ConcurrencyPolicy, JobMetadata, JobRunStage, JobStageConfig, Severity};
+
+    const GRACE_TIME_SECONDS: i64 = 60;
+
+    fn at(hour: u32, minute: u32, second: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 19, hour, minute, second).unwrap()
+    }
+
+    /// A job with one stage that has to start within 10 minutes.
+    fn job_config(job_name: &str, schedule: Option<&str>) -> JobConfig {
+        JobConfig {
+            app_name: "app1".to_string(),
+            job_name: job_name.to_string(),
+            schedule: schedule.map(str::to_string),
+            zone_id: schedule.map(|_| "UTC".to_string()),
+            enabled: true,
+            stages: diesel_json::Json(vec![JobStageConfig {
+                name: "load".to_string(),
+                start: Some(600),
+                complete: None,
+                max_attempts: None,
+                severity: Severity::default(),
+            }]),
+            channel_ids: "channel1".to_string(),
+            created_at: at(0, 0, 0),
+            updated_at: at(0, 0, 0),
+            concurrency_policy: ConcurrencyPolicy::Allow,
+            blackout_windows: diesel_json::Json(vec![]),
+            metadata: diesel_json::Json(JobMetadata::default()),
+        }
+    }
+
+    fn job_run(job_name: &str, triggered_at: DateTime<Utc>, scheduled_for: Option<DateTime<Utc>>, status: JobRunStatus) -> JobRun {
+        JobRun {
+            id: Uuid::new_v4(),
+            app_name: "app1".to_string(),
+            job_name: job_name.to_string(),
+            triggered_at,
+            status,
+            stages: diesel_json::Json(vec![]),
+            created_at: triggered_at,
+            updated_at: triggered_at,
+            scheduled_for,
+            run_key: None,
+            status_reason: None,
+        }
+    }
+
+    fn stage(start_status: Option<JobRunStageStatus>, complete_status: Option<JobRunStageStatus>) -> JobRunStage {
+        JobRunStage {
+            name: "load".to_string(),
+            start_date_time: start_status.as_ref().map(|_| at(12, 0, 0)),
+            start_status,
+            complete_date_time: complete_status.as_ref().map(|_| at(12, 0, 0)),
+            complete_status,
+            attempt: Some(1),
+        }
+    }
+
+    /// Job name, start time, whether it's scheduled, status and run of a forecast entry.
+    type EntrySummary = (String, DateTime<Utc>, bool, ForecastStatus, Option<Uuid>);
+
+    fn summary(entries: &[ForecastEntry]) -> Vec<EntrySummary> {
+        entries.iter()
+            .map(|entry| (
+                entry.job_config.job_name.clone(),
+                entry.start_time.with_timezone(&Utc),
+                entry.scheduled,
+                entry.status.clone(),
+                entry.job_run.as_ref().map(|run| run.id),
+            ))
+            .collect()
+    }
+
+    #[test]
+    fn occurrences_get_the_runs_scheduled_for_them() {
+        let hourly = job_config("hourly", Some("0 0 * * * *"));
+        let done = job_run("hourly", at(12, 0, 5), Some(at(12, 0, 0)), JobRunStatus::Complete);
+        // Backfilled long after its occurrence, and a late start of the occurrence before the window
+        let mut backfill = job_run("hourly", at(18, 0, 0), Some(at(13, 0, 0)), JobRunStatus::InProgress);
+        backfill.stages = diesel_json::Json(vec![stage(Some(JobRunStageStatus::Occurred), None)]);
+        let previous = job_run("hourly", at(12, 0, 10), Some(at(11, 0, 0)), JobRunStatus::InProgress);
+        // Recorded before runs were tied to their occurrence, within the grace time of 14:00
+        let legacy = job_run("hourly", at(13, 59, 30), None, JobRunStatus::InProgress);
+
+        let entries = build_forecast(
+            &[hourly],
+            vec![previous, legacy.clone(), backfill.clone(), done.clone()],
+            &at(12, 0, 0),
+            &at(14, 30, 0),
+            &at(14, 5, 0),
+            GRACE_TIME_SECONDS,
+        );
+        assert_eq!(summary(&entries), vec![
+            ("hourly".to_string(), at(12, 0, 0), true, ForecastStatus::Done, Some(done.id)),
+            ("hourly".to_string(), at(13, 0, 0), true, ForecastStatus::Running, Some(backfill.id)),
+            ("hourly".to_string(), at(14, 0, 0), true, ForecastStatus::Running, Some(legacy.id)),
+        ]);
+    }
+
+    #[test]
+    fn occurrences_without_a_run_are_pending_until_a_deadline_passes() {
+        let two_hourly = job_config("two-hourly", Some("0 0 */2 * * *"));
+
+        let entries = build_forecast(&[two_hourly], vec![], &at(12, 0, 0), &at(14, 30, 0), &at(14, 5, 0), GRACE_TIME_SECONDS);
+        assert_eq!(summary(&entries), vec![
+            ("two-hourly".to_string(), at(12, 0, 0), true, ForecastStatus::Late, None),
+            ("two-hourly".to_string(), at(14, 0, 0), true, ForecastStatus::Pending, None),
+        ]);
+    }
+
+    #[test]
+    fn unscheduled_runs_are_listed_by_trigger_time_and_disabled_jobs_left_out() {
+        let manual = job_config("manual", None);
+        let mut disabled = job_config("disabled", Some("0 0 * * * *"));
+        disabled.enabled = false;
+        let hourly = job_config("hourly", Some("0 0 * * * *"));
+        let manual_run = job_run("manual", at(13, 30, 0), None, JobRunStatus::Skipped);
+        let hourly_run = job_run("hourly", at(13, 0, 0), Some(at(13, 0, 0)), JobRunStatus::Cancelled);
+
+        let entries = build_forecast(
+            &[manual, disabled, hourly],
+            vec![hourly_run.clone(), manual_run.clone()],
+            &at(13, 0, 0),
+            &at(13, 45, 0),
+            &at(13, 40, 0),
+            GRACE_TIME_SECONDS,
+        );
+        assert_eq!(summary(&entries), vec![
+            ("hourly".to_string(), at(13, 0, 0), true, ForecastStatus::Cancelled, Some(hourly_run.id)),
+            ("manual".to_string(), at(13, 30, 0), false, ForecastStatus::Skipped, Some(manual_run.id)),
+        ]);
+    }
+
+    #[test]
+    fn failed_runs_are_late_unless_a_stage_failed() {
+        let hourly = job_config("hourly", Some("0 0 * * * *"));
+        let start_time = at(12, 0, 0).with_timezone(&UTC);
+        let mut failed = job_run("hourly", at(12, 0, 0), Some(at(12, 0, 0)), JobRunStatus::Failed);
+        failed.stages = diesel_json::Json(vec![stage(Some(JobRunStageStatus::Missed), None)]);
+        assert_eq!(get_forecast_status(&hourly, &start_time, Some(&failed), &at(12, 30, 0)), ForecastStatus::Late);
+
+        failed.stages = diesel_json::Json(vec![stage(Some(JobRunStageStatus::Occurred), None), stage(None, Some(JobRunStageStatus::Failed))]);
+        assert_eq!(get_forecast_status(&hourly, &start_time, Some(&failed), &at(12, 30, 0)), ForecastStatus::Failed);
+    }
+}
@@ -40,36 +40,24 @@ pub fn get_job_start_time(job: &JobConfig, current_time: &DateTime<Tz>) -> Resul
     get_cron_start_time(job, current_time)
 }
 
-pub fn get_job_complete_time(job: &JobConfig, current_time: DateTime<Tz>) -> Result<DateTime<Tz>, AppError> {
-    let max_duration = job
-        .stages
-        .iter()
-        .map(|a| {
-            get_max(a.start, a.complete).unwrap_or_else(|| i64::MAX as u64)
-            // if a.complete.is_some() {
-            //     a.complete.unwrap()
-            // } else {
-            //     a.start.unwrap()
-            // }
-        })
-        .max()
-        .unwrap_or_else(|| i64::MAX as u64);
-
-    let start_time = get_cron_start_time(job, &current_time)?;
-    Ok(start_time.add(Duration::seconds(max_duration as i64)))
+/// The occurrence a run starting at `current_time` belongs to; runs may start up to `grace_time_seconds` early.
+pub fn get_run_occurrence(job: &JobConfig, current_time: &DateTime<Tz>, grace_time_seconds: i64) -> Result<DateTime<Tz>, AppError> {
+    get_job_start_time(job, &current_time.add(Duration::seconds(grace_time_seconds)))
 }
 
-pub fn in_between(job: &JobConfig, current_time: DateTime<Tz>, scheduler_fixed_delay_seconds: u64) -> Result<bool, AppError> {
-    let job_start_time = get_job_start_time(job, &current_time)?;
-    let job_complete_time = get_job_complete_time(job, current_time)?;
-
-    let buffer_complete_time = job_complete_time.add(Duration::seconds((scheduler_fixed_delay_seconds * 4) as i64));
-    info!("job start time: {}, job complete time: {}, buffered_time: {}, current_time: {}", job_start_time, job_complete_time, buffer_complete_time, current_time);
-
-    let is_after_start = current_time >= job_start_time;
-    let is_before_complete = current_time < buffer_complete_time;
+/// How long after an occurrence the regular pass keeps evaluating it: until the latest stage deadline plus a buffer
+/// of a few passes. `None` when a stage has no deadline, so the window never closes.
+pub fn get_evaluation_window(job: &JobConfig, scheduler_fixed_delay_seconds: u64) -> Option<Duration> {
+    let max_offset = job
+        .stages
+        .iter()
+        .map(|a| get_max(a.start, a.complete))
+        .collect::<Option<Vec<u64>>>()?
+        .into_iter()
+        .max()?;
 
-    Ok(is_after_start && is_before_complete)
+    Duration::try_seconds(i64::try_from(max_offset).ok()?)?
+        .checked_add(&Duration::try_seconds((scheduler_fixed_delay_seconds * 4) as i64)?)
 }
 
 pub fn get_previous_execution_time<TZ>(
@@ -126,3 +114,29 @@ pub fn get_previous_execution_time<TZ>(
         None => Err(AppError::InternalError(cron_string.to_string())),
     }
 }
+
+pub fn get_next_execution_times(
+    cron_string: &str,
+    from_date_time: &DateTime<Tz>,
+    count: usize,
+) -> Result<Vec<DateTime<Tz>>, AppError> {
+    let schedule = Schedule::from_str(cron_string)
+        .map_err(|_| AppError::BadRequest(format!("invalid schedule found: {}", cron_string)))?;
+
+    Ok(schedule.after(from_date_time).take(count).collect())
+}
+
+pub fn get_stage_deadline(start_time: &DateTime<Tz>, offset_secs: Option<u64>) -> Option<DateTime<Tz>> {
+    offset_secs.map(|secs| start_time.add(Duration::seconds(secs as i64)))
+}
+
+pub fn get_execution_times_between(
+    cron_string: &str,
+    from_date_time: &DateTime<Tz>,
+    to_date_time: &DateTime<Tz>,
+) -> Result<Vec<DateTime<Tz>>, AppError> {
+    let schedule = Schedule::from_str(cron_string)
+        .map_err(|_| AppError::BadRequest(format!("invalid schedule found: {}", cron_string)))?;
+
+    Ok(schedule.after(from_date_time).take_while(|dt| dt <= to_date_time).collect())
+}
@@ -0,0 +1,51 @@
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
+use diesel::upsert::excluded;
+use diesel_async::RunQueryDsl;
+use crate::db::connection::DbConnection;
+use crate::errors::AppError;
+use crate::models::{ApplicationConfig, NewApplicationConfig};
+
+pub async fn get_application_config(
+    conn: &mut DbConnection<'_>,
+    _app_name: &str,
+) -> Result<Option<ApplicationConfig>, AppError> {
+    use crate::schema::application_configs::dsl::*;
+
+    let application_config = application_configs
+        .find(_app_name)
+        .first::<ApplicationConfig>(conn)
+        .await
+        .optional()?;
+
+    Ok(application_config)
+}
+
+pub async fn get_all_application_configs(
+    conn: &mut DbConnection<'_>,
+) -> Result<Vec<ApplicationConfig>, AppError> {
+    use crate::schema::application_configs::dsl::*;
+
+    let result = application_configs
+        .load::<ApplicationConfig>(conn)
+        .await?;
+
+    Ok(result)
+}
+
+/// Creates the config of the application, or replaces the metadata of the existing one.
+pub async fn save_application_config(
+    conn: &mut DbConnection<'_>,
+    new_application_config: NewApplicationConfig,
+) -> Result<ApplicationConfig, AppError> {
+    use crate::schema::application_configs::dsl::*;
+
+    let saved = diesel::insert_into(application_configs)
+        .values(&new_application_config)
+        .on_conflict(app_name)
+        .do_update()
+        .set(metadata.eq(excluded(metadata)))
+        .get_result::<ApplicationConfig>(conn)
+        .await?;
+
+    Ok(saved)
+}
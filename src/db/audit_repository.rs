@@ -0,0 +1,68 @@
+use chrono::{DateTime, Utc};
+use diesel::{QueryDsl, ExpressionMethods};
+use diesel_async::RunQueryDsl;
+use crate::db::connection::DbConnection;
+use crate::dtos::audit::AuditLogQuery;
+use crate::errors::AppError;
+use crate::models::{AuditLog, NewAuditLog};
+
+pub async fn insert_audit_log(
+    conn: &mut DbConnection<'_>,
+    new_audit_log: NewAuditLog,
+) -> Result<AuditLog, AppError> {
+    use crate::schema::audit_logs::dsl::*;
+    let audit_log = diesel::insert_into(audit_logs)
+        .values(&new_audit_log)
+        .get_result::<AuditLog>(conn)
+        .await?;
+
+    Ok(audit_log)
+}
+
+pub async fn find_audit_logs(
+    conn: &mut DbConnection<'_>,
+    query: &AuditLogQuery,
+    limit: i64,
+) -> Result<Vec<AuditLog>, AppError> {
+    use crate::schema::audit_logs::dsl::*;
+    let mut statement = audit_logs.into_boxed();
+
+    if let Some(from) = query.from {
+        statement = statement.filter(occurred_at.ge(from));
+    }
+    if let Some(to) = query.to {
+        statement = statement.filter(occurred_at.le(to));
+    }
+    if let Some(_actor) = &query.actor {
+        statement = statement.filter(actor.eq(_actor));
+    }
+    if let Some(_target) = &query.target {
+        statement = statement.filter(target.eq(_target));
+    }
+    if let Some(_method) = &query.method {
+        statement = statement.filter(method.eq(_method.to_uppercase()));
+    }
+    if let Some(_route) = &query.route {
+        statement = statement.filter(route.eq(_route));
+    }
+
+    let result = statement
+        .order(occurred_at.desc())
+        .limit(limit)
+        .load::<AuditLog>(conn)
+        .await?;
+
+    Ok(result)
+}
+
+pub async fn delete_audit_logs_before(
+    conn: &mut DbConnection<'_>,
+    time_boundary: DateTime<Utc>,
+) -> Result<usize, AppError> {
+    use crate::schema::audit_logs::dsl::*;
+    let deleted = diesel::delete(audit_logs.filter(occurred_at.lt(time_boundary)))
+        .execute(conn)
+        .await?;
+
+    Ok(deleted)
+}
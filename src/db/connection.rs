@@ -1,14 +1,22 @@
+use std::time::Duration;
 use bb8::{Pool, PooledConnection};
 use diesel_async::AsyncPgConnection;
 use diesel_async::pooled_connection::AsyncDieselConnectionManager;
+use crate::config::PoolConfig;
 use crate::errors::AppError;
 
 pub type PgPool = Pool<AsyncDieselConnectionManager<AsyncPgConnection>>;
 
 pub type DbConnection<'a> = PooledConnection<'a, AsyncDieselConnectionManager<AsyncPgConnection>>;
 
-pub async fn get_connection_pool(db_url: &str) -> Result<PgPool, AppError> {
+pub async fn get_connection_pool(db_url: &str, pool_config: &PoolConfig) -> Result<PgPool, AppError> {
     let connection_manager = AsyncDieselConnectionManager::<AsyncPgConnection>::new(db_url);
-    let pool = Pool::builder().build(connection_manager).await?;
+    let pool = Pool::builder()
+        .max_size(pool_config.max_size)
+        .min_idle(pool_config.min_idle)
+        .connection_timeout(Duration::from_secs(pool_config.connection_timeout_seconds))
+        .idle_timeout(pool_config.idle_timeout_seconds.map(Duration::from_secs))
+        .build(connection_manager)
+        .await?;
     Ok(pool)
 }
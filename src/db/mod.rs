@@ -2,4 +2,11 @@ pub mod config_repository;
 pub mod connection;
 pub mod run_repository;
 pub mod channel_repository;
-pub mod settings_repository;
\ No newline at end of file
+pub mod settings_repository;
+pub mod revision_repository;
+pub mod audit_repository;
+pub mod schedule_checkpoint_repository;
+pub mod snooze_repository;
+pub mod application_repository;
+pub mod routing_rule_repository;
+pub mod run_event_repository;
\ No newline at end of file
@@ -0,0 +1,245 @@
+use diesel::{QueryDsl, ExpressionMethods, OptionalExtension};
+use serde_json::Value;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use crate::db::channel_repository::{insert_channel, save_channel};
+use crate::db::config_repository::{insert_config, save_config};
+use crate::db::connection::DbConnection;
+use crate::dtos::channel::ChannelSnapshot;
+use crate::dtos::job_config::JobConfigDto;
+use crate::errors::AppError;
+use crate::models::{Channel, ChannelRevision, JobConfig, JobConfigRevision, NewChannel, NewChannelRevision, NewJobConfig, NewJobConfigRevision, RevisionChangeType};
+
+async fn insert_job_config_revision(
+    conn: &mut DbConnection<'_>,
+    job_config: &JobConfig,
+    _change_type: RevisionChangeType,
+    _changed_by: Option<String>,
+) -> Result<JobConfigRevision, AppError> {
+    use crate::schema::job_config_revisions::dsl::*;
+    use crate::schema::job_configs;
+
+    // Concurrent changes of the job wait for each other here, so each gets the next revision
+    job_configs::table
+        .find((&job_config.app_name, &job_config.job_name))
+        .select(job_configs::app_name)
+        .for_update()
+        .first::<String>(conn)
+        .await?;
+
+    let latest_revision = job_config_revisions
+        .filter(app_name.eq(&job_config.app_name))
+        .filter(job_name.eq(&job_config.job_name))
+        .select(revision)
+        .order(revision.desc())
+        .first::<i32>(conn)
+        .await
+        .optional()?;
+
+    let _snapshot = serde_json::to_value(JobConfigDto::from(job_config.clone()))
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    let new_revision = NewJobConfigRevision {
+        app_name: job_config.app_name.clone(),
+        job_name: job_config.job_name.clone(),
+        revision: latest_revision.unwrap_or(0) + 1,
+        change_type: _change_type,
+        changed_by: _changed_by,
+        snapshot: _snapshot,
+    };
+
+    let inserted = diesel::insert_into(job_config_revisions)
+        .values(&new_revision)
+        .get_result::<JobConfigRevision>(conn)
+        .await?;
+
+    Ok(inserted)
+}
+
+pub async fn get_job_config_revisions(
+    conn: &mut DbConnection<'_>,
+    _app_name: &str,
+    _job_name: &str,
+) -> Result<Vec<JobConfigRevision>, AppError> {
+    use crate::schema::job_config_revisions::dsl::*;
+    let revisions = job_config_revisions
+        .filter(app_name.eq(_app_name))
+        .filter(job_name.eq(_job_name))
+        .order(revision.desc())
+        .load::<JobConfigRevision>(conn)
+        .await?;
+
+    Ok(revisions)
+}
+
+pub async fn get_job_config_revision(
+    conn: &mut DbConnection<'_>,
+    _app_name: &str,
+    _job_name: &str,
+    _revision: i32,
+) -> Result<Option<JobConfigRevision>, AppError> {
+    use crate::schema::job_config_revisions::dsl::*;
+    let res = job_config_revisions
+        .filter(app_name.eq(_app_name))
+        .filter(job_name.eq(_job_name))
+        .filter(revision.eq(_revision))
+        .first::<JobConfigRevision>(conn)
+        .await
+        .optional()?;
+
+    Ok(res)
+}
+
+async fn insert_channel_revision(
+    conn: &mut DbConnection<'_>,
+    channel: &Channel,
+    _change_type: RevisionChangeType,
+    _changed_by: Option<String>,
+) -> Result<ChannelRevision, AppError> {
+    use crate::schema::channel_revisions::dsl::*;
+    use crate::schema::channels;
+
+    // Concurrent changes of the channel wait for each other here, so each gets the next revision
+    channels::table
+        .find(&channel.name)
+        .select(channels::name)
+        .for_update()
+        .first::<String>(conn)
+        .await?;
+
+    let latest_revision = channel_revisions
+        .filter(channel_name.eq(&channel.name))
+        .select(revision)
+        .order(revision.desc())
+        .first::<i32>(conn)
+        .await
+        .optional()?;
+
+    let _snapshot = serde_json::to_value(ChannelSnapshot::from(channel.clone()))
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    let new_revision = NewChannelRevision {
+        channel_name: channel.name.clone(),
+        revision: latest_revision.unwrap_or(0) + 1,
+        change_type: _change_type,
+        changed_by: _changed_by,
+        snapshot: _snapshot,
+    };
+
+    let inserted = diesel::insert_into(channel_revisions)
+        .values(&new_revision)
+        .get_result::<ChannelRevision>(conn)
+        .await?;
+
+    Ok(inserted)
+}
+
+pub async fn get_channel_revisions(
+    conn: &mut DbConnection<'_>,
+    _channel_name: &str,
+) -> Result<Vec<ChannelRevision>, AppError> {
+    use crate::schema::channel_revisions::dsl::*;
+    let revisions = channel_revisions
+        .filter(channel_name.eq(_channel_name))
+        .order(revision.desc())
+        .load::<ChannelRevision>(conn)
+        .await?;
+
+    Ok(revisions)
+}
+
+pub async fn get_channel_revision(
+    conn: &mut DbConnection<'_>,
+    _channel_name: &str,
+    _revision: i32,
+) -> Result<Option<ChannelRevision>, AppError> {
+    use crate::schema::channel_revisions::dsl::*;
+    let res = channel_revisions
+        .filter(channel_name.eq(_channel_name))
+        .filter(revision.eq(_revision))
+        .first::<ChannelRevision>(conn)
+        .await
+        .optional()?;
+
+    Ok(res)
+}
+
+pub async fn get_all_channel_revisions(
+    conn: &mut DbConnection<'_>,
+) -> Result<Vec<ChannelRevision>, AppError> {
+    use crate::schema::channel_revisions::dsl::*;
+    let revisions = channel_revisions
+        .order(id.asc())
+        .load::<ChannelRevision>(conn)
+        .await?;
+
+    Ok(revisions)
+}
+
+pub async fn update_channel_revision_snapshot(
+    conn: &mut DbConnection<'_>,
+    _id: i64,
+    _snapshot: &Value,
+) -> Result<(), AppError> {
+    use crate::schema::channel_revisions::dsl::*;
+    diesel::update(channel_revisions.find(_id))
+        .set(snapshot.eq(_snapshot))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Creates the job config along with its first revision, in one transaction.
+pub async fn insert_config_with_revision(
+    conn: &mut DbConnection<'_>,
+    new_config: NewJobConfig,
+    _changed_by: Option<String>,
+) -> Result<JobConfig, AppError> {
+    conn.transaction(|conn| async move {
+        let inserted = insert_config(conn, new_config).await?;
+        insert_job_config_revision(conn, &inserted, RevisionChangeType::Created, _changed_by).await?;
+        Ok(inserted)
+    }.scope_boxed()).await
+}
+
+/// Saves the job config and records the change as its next revision, in one transaction.
+pub async fn save_config_with_revision(
+    conn: &mut DbConnection<'_>,
+    config: JobConfig,
+    _change_type: RevisionChangeType,
+    _changed_by: Option<String>,
+) -> Result<JobConfig, AppError> {
+    conn.transaction(|conn| async move {
+        let saved = save_config(conn, config).await?;
+        insert_job_config_revision(conn, &saved, _change_type, _changed_by).await?;
+        Ok(saved)
+    }.scope_boxed()).await
+}
+
+/// Creates the channel along with its first revision, in one transaction.
+pub async fn insert_channel_with_revision(
+    conn: &mut DbConnection<'_>,
+    new_channel: NewChannel,
+    _changed_by: Option<String>,
+) -> Result<Channel, AppError> {
+    conn.transaction(|conn| async move {
+        let inserted = insert_channel(conn, new_channel).await?;
+        insert_channel_revision(conn, &inserted, RevisionChangeType::Created, _changed_by).await?;
+        Ok(inserted)
+    }.scope_boxed()).await
+}
+
+/// Saves the channel and records the change as its next revision, in one transaction.
+pub async fn save_channel_with_revision(
+    conn: &mut DbConnection<'_>,
+    _channel: Channel,
+    _change_type: RevisionChangeType,
+    _changed_by: Option<String>,
+) -> Result<Channel, AppError> {
+    conn.transaction(|conn| async move {
+        let saved = save_channel(conn, _channel).await?;
+        insert_channel_revision(conn, &saved, _change_type, _changed_by).await?;
+        Ok(saved)
+    }.scope_boxed()).await
+}
@@ -0,0 +1,84 @@
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
+use diesel_async::RunQueryDsl;
+use crate::db::connection::DbConnection;
+use crate::errors::AppError;
+use crate::models::{NewRoutingRule, RoutingRule};
+
+pub async fn get_routing_rule_by_name(
+    conn: &mut DbConnection<'_>,
+    _name: &str,
+) -> Result<Option<RoutingRule>, AppError> {
+    use crate::schema::routing_rules::dsl::*;
+    let res = routing_rules
+        .find(_name)
+        .first::<RoutingRule>(conn)
+        .await
+        .optional()?;
+    Ok(res)
+}
+
+/// Every rule, in the order they are evaluated in.
+pub async fn get_all_routing_rules(
+    conn: &mut DbConnection<'_>,
+) -> Result<Vec<RoutingRule>, AppError> {
+    use crate::schema::routing_rules::dsl::*;
+    let res = routing_rules
+        .order((priority.asc(), name.asc()))
+        .load::<RoutingRule>(conn)
+        .await?;
+
+    Ok(res)
+}
+
+/// The rules alerts are routed by, in the order they are evaluated in.
+pub async fn get_enabled_routing_rules(
+    conn: &mut DbConnection<'_>,
+) -> Result<Vec<RoutingRule>, AppError> {
+    use crate::schema::routing_rules::dsl::*;
+    let res = routing_rules
+        .filter(enabled.eq(true))
+        .order((priority.asc(), name.asc()))
+        .load::<RoutingRule>(conn)
+        .await?;
+
+    Ok(res)
+}
+
+pub async fn insert_routing_rule(
+    conn: &mut DbConnection<'_>,
+    new_routing_rule: NewRoutingRule,
+) -> Result<RoutingRule, AppError> {
+    use crate::schema::routing_rules::dsl::*;
+    let routing_rule = diesel::insert_into(routing_rules)
+        .values(&new_routing_rule)
+        .get_result::<RoutingRule>(conn)
+        .await?;
+
+    Ok(routing_rule)
+}
+
+pub async fn save_routing_rule(
+    conn: &mut DbConnection<'_>,
+    _routing_rule: RoutingRule,
+) -> Result<RoutingRule, AppError> {
+    use crate::schema::routing_rules::dsl::*;
+    let updated = diesel::update(routing_rules.find(_routing_rule.name.clone()))
+        .set(&_routing_rule)
+        .get_result::<RoutingRule>(conn)
+        .await?;
+
+    Ok(updated)
+}
+
+/// Returns the number of rules removed, 0 when there was no rule with the name.
+pub async fn delete_routing_rule(
+    conn: &mut DbConnection<'_>,
+    _name: &str,
+) -> Result<usize, AppError> {
+    use crate::schema::routing_rules::dsl::*;
+    let deleted = diesel::delete(routing_rules.find(_name))
+        .execute(conn)
+        .await?;
+
+    Ok(deleted)
+}
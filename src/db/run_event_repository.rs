@@ -0,0 +1,63 @@
+use diesel::{QueryDsl, ExpressionMethods};
+use diesel_async::RunQueryDsl;
+use uuid::Uuid;
+use crate::db::connection::DbConnection;
+use crate::dtos::run_event::RunEvent;
+use crate::errors::AppError;
+use crate::models::{JobRunEvent, NewJobRunEvent};
+
+pub async fn insert_job_run_event(
+    conn: &mut DbConnection<'_>,
+    event: &RunEvent,
+) -> Result<JobRunEvent, AppError> {
+    use crate::schema::job_run_events::dsl::*;
+
+    let new_event = NewJobRunEvent {
+        job_run_id: event.job_run_id.parse::<Uuid>()
+            .map_err(|e| AppError::InternalError(format!("invalid run id {}: {}", event.job_run_id, e)))?,
+        event_type: event.event_type.clone(),
+        stage_name: event.stage_name.clone(),
+        attempt: event.attempt.map(|_attempt| _attempt as i32),
+        status: event.status.clone(),
+        message: event.message.clone(),
+        source: event.source,
+        occurred_at: event.occurred_at,
+    };
+
+    let inserted = diesel::insert_into(job_run_events)
+        .values(&new_event)
+        .get_result::<JobRunEvent>(conn)
+        .await?;
+
+    Ok(inserted)
+}
+
+/// The events of the run, in the order they were recorded.
+pub async fn get_job_run_events(
+    conn: &mut DbConnection<'_>,
+    _job_run_id: &Uuid,
+) -> Result<Vec<JobRunEvent>, AppError> {
+    use crate::schema::job_run_events::dsl::*;
+    let events = job_run_events
+        .filter(job_run_id.eq(_job_run_id))
+        .order(id.asc())
+        .load::<JobRunEvent>(conn)
+        .await?;
+
+    Ok(events)
+}
+
+/// The events of the runs, in the order they were recorded.
+pub async fn get_job_run_events_of_runs(
+    conn: &mut DbConnection<'_>,
+    _job_run_ids: &[Uuid],
+) -> Result<Vec<JobRunEvent>, AppError> {
+    use crate::schema::job_run_events::dsl::*;
+    let events = job_run_events
+        .filter(job_run_id.eq_any(_job_run_ids))
+        .order(id.asc())
+        .load::<JobRunEvent>(conn)
+        .await?;
+
+    Ok(events)
+}
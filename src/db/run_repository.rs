@@ -1,11 +1,14 @@
 use chrono::{DateTime, Utc};
-use diesel::{QueryDsl, OptionalExtension, ExpressionMethods};
-use diesel_async::RunQueryDsl;
+use diesel::{QueryDsl, OptionalExtension, ExpressionMethods, BoolExpressionMethods};
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use diesel_async::scoped_futures::ScopedFutureExt;
 use uuid::Uuid;
+use crate::core::run_timeline::get_run_stages;
 use crate::db::connection::DbConnection;
+use crate::db::run_event_repository::{get_job_run_events_of_runs, insert_job_run_event};
+use crate::dtos::run_event::{JobRunEventDto, RunEvent};
 use crate::errors::AppError;
-use crate::models::{JobRun, JobRunStatus, NewJobRun};
-use crate::time_utils::get_utc_now;
+use crate::models::{JobRun, JobRunStatus, NewJobRun, RunEventSource, RunEventType};
 
 pub async fn get_job_run_by_id(
     conn: &mut DbConnection<'_>,
@@ -18,25 +21,41 @@ pub async fn get_job_run_by_id(
         .await
         .optional()?;
 
-    Ok(job_run)
+    with_event_stage(conn, job_run).await
 }
 
-pub async fn get_latest_job_run_by_app_name_and_job_name(
+/// The run, locked until the end of the transaction so events of the run are applied one at a time.
+pub async fn get_job_run_by_id_for_update(
+    conn: &mut DbConnection<'_>,
+    _run_id: &Uuid,
+) -> Result<Option<JobRun>, AppError> {
+    use crate::schema::job_runs::dsl::*;
+    let job_run = job_runs
+        .find(_run_id)
+        .for_update()
+        .first::<JobRun>(conn)
+        .await
+        .optional()?;
+
+    with_event_stage(conn, job_run).await
+}
+
+pub async fn get_job_run_by_key(
     conn: &mut DbConnection<'_>,
     _app_name: &str,
     _job_name: &str,
-    _start_time: &DateTime<Utc>,
+    _run_key: &str,
 ) -> Result<Option<JobRun>, AppError> {
     use crate::schema::job_runs::dsl::*;
     let job_run = job_runs
         .filter(app_name.eq(_app_name))
         .filter(job_name.eq(_job_name))
-        .filter(triggered_at.ge(_start_time))
+        .filter(run_key.eq(_run_key))
         .first::<JobRun>(conn)
         .await
         .optional()?;
 
-    Ok(job_run)
+    with_event_stage(conn, job_run).await
 }
 
 pub async fn get_all_runs_top_100(conn: &mut DbConnection<'_>) -> Result<Vec<JobRun>, AppError> {
@@ -46,37 +65,114 @@ pub async fn get_all_runs_top_100(conn: &mut DbConnection<'_>) -> Result<Vec<Job
         .load::<JobRun>(conn)
         .await?;
 
-    Ok(result)
+    with_event_stages(conn, result).await
 }
 
 pub async fn get_all_pending_job_runs(
     conn: &mut DbConnection<'_>,
     time_boundary: DateTime<Utc>,
+) -> Result<Vec<JobRun>, AppError> {
+    use crate::schema::job_runs::dsl::*;
+    // Runs of occurrences still ahead, e.g. skipped ones, are kept however long ago they were updated
+    let result = job_runs
+        .filter(updated_at.ge(time_boundary).or(scheduled_for.ge(time_boundary)))
+        .load::<JobRun>(conn)
+        .await?;
+
+    with_event_stages(conn, result).await
+}
+
+/// Runs scheduled for an occurrence in `[from, to]`, and runs without an occurrence triggered in `[triggered_from, to]`:
+/// those of unscheduled jobs, and those created before runs were tied to their occurrence.
+pub async fn get_job_runs_between(
+    conn: &mut DbConnection<'_>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    triggered_from: DateTime<Utc>,
+) -> Result<Vec<JobRun>, AppError> {
+    use crate::schema::job_runs::dsl::*;
+    let result = job_runs
+        .filter(scheduled_for.ge(from).and(scheduled_for.le(to)).or(
+            scheduled_for.is_null().and(triggered_at.ge(triggered_from)).and(triggered_at.le(to))
+        ))
+        .order(triggered_at.asc())
+        .load::<JobRun>(conn)
+        .await?;
+
+    with_event_stages(conn, result).await
+}
+
+/// Runs of one job belonging to the occurrence at `_scheduled_for`, latest first. Runs created before runs were tied
+/// to their occurrence match when they were triggered in `[from, to)`.
+pub async fn get_job_runs_of_occurrence(
+    conn: &mut DbConnection<'_>,
+    _app_name: &str,
+    _job_name: &str,
+    _scheduled_for: DateTime<Utc>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
 ) -> Result<Vec<JobRun>, AppError> {
     use crate::schema::job_runs::dsl::*;
     let result = job_runs
-        .filter(updated_at.ge(time_boundary))
+        .filter(app_name.eq(_app_name))
+        .filter(job_name.eq(_job_name))
+        .filter(scheduled_for.eq(_scheduled_for).or(
+            scheduled_for.is_null().and(triggered_at.ge(from)).and(triggered_at.lt(to))
+        ))
+        .order(created_at.desc())
+        .load::<JobRun>(conn)
+        .await?;
+
+    with_event_stages(conn, result).await
+}
+
+/// Runs of one job still in progress that were triggered since `since`, oldest occurrence first.
+pub async fn get_in_flight_job_runs(
+    conn: &mut DbConnection<'_>,
+    _app_name: &str,
+    _job_name: &str,
+    since: DateTime<Utc>,
+) -> Result<Vec<JobRun>, AppError> {
+    use crate::schema::job_runs::dsl::*;
+    let mut result = job_runs
+        .filter(app_name.eq(_app_name))
+        .filter(job_name.eq(_job_name))
+        .filter(status.eq(JobRunStatus::InProgress))
+        .filter(triggered_at.ge(since))
         .load::<JobRun>(conn)
         .await?;
 
-    Ok(result)
+    result.sort_by_key(|run| (run.scheduled_for.unwrap_or(run.triggered_at), run.created_at));
+    with_event_stages(conn, result).await
 }
 
-pub async fn create_new_job_run(
+/// Inserts the run and records its `run_created` event in one transaction, returning both.
+pub async fn create_job_run_triggered_at(
     conn: &mut DbConnection<'_>,
     _app_name: &String,
     _job_name: &String,
-) -> Result<JobRun, AppError> {
+    _triggered_at: DateTime<Utc>,
+    _scheduled_for: Option<DateTime<Utc>>,
+    _run_key: Option<String>,
+    source: RunEventSource,
+) -> Result<(JobRun, RunEvent), AppError> {
 
     let new_job_run = NewJobRun {
         app_name: _app_name.clone(),
         job_name: _job_name.clone(),
         status: JobRunStatus::InProgress,
         stages: diesel_json::Json(Vec::new()),
-        triggered_at: get_utc_now(),
+        triggered_at: _triggered_at,
+        scheduled_for: _scheduled_for,
+        run_key: _run_key,
     };
 
-    insert_run(conn, new_job_run).await
+    conn.transaction(|conn| async move {
+        let job_run = insert_run(conn, new_job_run).await?;
+        let event = RunEvent::new(RunEventType::RunCreated, &job_run, None, source);
+        insert_job_run_event(conn, &event).await?;
+        Ok((job_run, event))
+    }.scope_boxed()).await
 }
 
 pub async fn insert_run(
@@ -102,5 +198,47 @@ pub async fn save_run(
         .get_result::<JobRun>(conn)
         .await?;
 
-    Ok(job_run)
+    with_event_stage(conn, Some(job_run)).await?
+        .ok_or_else(|| AppError::InternalError(format!("job run {} vanished while saving", run.id)))
+}
+
+/// Saves the run and records the events about the change in one transaction.
+pub async fn save_run_with_events(
+    conn: &mut DbConnection<'_>,
+    run: JobRun,
+    events: &[RunEvent],
+) -> Result<JobRun, AppError> {
+    let events = events.to_vec();
+    conn.transaction(|conn| async move {
+        for event in events.iter() {
+            insert_job_run_event(conn, event).await?;
+        }
+        save_run(conn, run).await
+    }.scope_boxed()).await
+}
+
+async fn with_event_stage(conn: &mut DbConnection<'_>, job_run: Option<JobRun>) -> Result<Option<JobRun>, AppError> {
+    match job_run {
+        Some(job_run) => Ok(with_event_stages(conn, vec![job_run]).await?.pop()),
+        None => Ok(None),
+    }
+}
+
+/// Fills in the stages of the runs from their events.
+async fn with_event_stages(conn: &mut DbConnection<'_>, mut runs: Vec<JobRun>) -> Result<Vec<JobRun>, AppError> {
+    if runs.is_empty() {
+        return Ok(runs);
+    }
+    let run_ids: Vec<Uuid> = runs.iter().map(|run| run.id).collect();
+    let events = get_job_run_events_of_runs(conn, &run_ids).await?;
+
+    for run in runs.iter_mut() {
+        let run_events: Vec<JobRunEventDto> = events.iter()
+            .filter(|event| event.job_run_id == run.id)
+            .cloned()
+            .map(JobRunEventDto::from)
+            .collect();
+        run.stages = diesel_json::Json(get_run_stages(&run_events));
+    }
+    Ok(runs)
 }
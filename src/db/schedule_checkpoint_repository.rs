@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use diesel::{QueryDsl, ExpressionMethods, OptionalExtension};
+use diesel::upsert::excluded;
+use diesel_async::RunQueryDsl;
+use crate::db::connection::DbConnection;
+use crate::errors::AppError;
+use crate::models::{JobScheduleCheckpoint, NewJobScheduleCheckpoint};
+
+pub async fn get_schedule_checkpoint(
+    conn: &mut DbConnection<'_>,
+    _app_name: &str,
+    _job_name: &str,
+) -> Result<Option<JobScheduleCheckpoint>, AppError> {
+    use crate::schema::job_schedule_checkpoints::dsl::*;
+
+    let checkpoint = job_schedule_checkpoints
+        .find((_app_name, _job_name))
+        .first::<JobScheduleCheckpoint>(conn)
+        .await
+        .optional()?;
+
+    Ok(checkpoint)
+}
+
+pub async fn save_schedule_checkpoint(
+    conn: &mut DbConnection<'_>,
+    _app_name: &str,
+    _job_name: &str,
+    _evaluated_until: DateTime<Utc>,
+) -> Result<JobScheduleCheckpoint, AppError> {
+    use crate::schema::job_schedule_checkpoints::dsl::*;
+
+    let new_checkpoint = NewJobScheduleCheckpoint {
+        app_name: _app_name.to_string(),
+        job_name: _job_name.to_string(),
+        evaluated_until: _evaluated_until,
+    };
+
+    let saved = diesel::insert_into(job_schedule_checkpoints)
+        .values(&new_checkpoint)
+        .on_conflict((app_name, job_name))
+        .do_update()
+        .set(evaluated_until.eq(excluded(evaluated_until)))
+        .get_result::<JobScheduleCheckpoint>(conn)
+        .await?;
+
+    Ok(saved)
+}
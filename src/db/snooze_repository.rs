@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use crate::db::connection::DbConnection;
+use crate::errors::AppError;
+use crate::models::{NewSnooze, Snooze};
+
+/// Snoozes still in effect at `now`, of every application.
+pub async fn get_active_snoozes(
+    conn: &mut DbConnection<'_>,
+    now: DateTime<Utc>,
+) -> Result<Vec<Snooze>, AppError> {
+    use crate::schema::snoozes::dsl::*;
+
+    let result = snoozes
+        .filter(snoozed_until.gt(now))
+        .load::<Snooze>(conn)
+        .await?;
+
+    Ok(result)
+}
+
+/// Snoozes in effect at `now` that silence the job, its own and the ones of its application.
+pub async fn get_active_snoozes_of_job(
+    conn: &mut DbConnection<'_>,
+    _app_name: &str,
+    _job_name: &str,
+    now: DateTime<Utc>,
+) -> Result<Vec<Snooze>, AppError> {
+    use crate::schema::snoozes::dsl::*;
+
+    let result = snoozes
+        .filter(app_name.eq(_app_name))
+        .filter(job_name.eq(_job_name).or(job_name.is_null()))
+        .filter(snoozed_until.gt(now))
+        .load::<Snooze>(conn)
+        .await?;
+
+    Ok(result)
+}
+
+/// Snoozes the job, or the application when `job_name` is absent, replacing its previous snooze.
+pub async fn save_snooze(
+    conn: &mut DbConnection<'_>,
+    new_snooze: NewSnooze,
+) -> Result<Snooze, AppError> {
+    use crate::schema::snoozes::dsl::*;
+
+    delete_snooze(conn, &new_snooze.app_name, new_snooze.job_name.as_deref()).await?;
+
+    let saved = diesel::insert_into(snoozes)
+        .values(&new_snooze)
+        .get_result::<Snooze>(conn)
+        .await?;
+
+    Ok(saved)
+}
+
+/// Removes the snooze of the job, or of the application when `job_name` is absent, returning it if there was one.
+pub async fn delete_snooze(
+    conn: &mut DbConnection<'_>,
+    _app_name: &str,
+    _job_name: Option<&str>,
+) -> Result<Option<Snooze>, AppError> {
+    use crate::schema::snoozes::dsl::*;
+
+    let target = snoozes.filter(app_name.eq(_app_name));
+    let deleted = match _job_name {
+        Some(_job_name) => diesel::delete(target.filter(job_name.eq(_job_name))).get_results::<Snooze>(conn).await?,
+        None => diesel::delete(target.filter(job_name.is_null())).get_results::<Snooze>(conn).await?,
+    };
+
+    Ok(deleted.into_iter().next())
+}
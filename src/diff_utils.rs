@@ -0,0 +1,65 @@
+use std::collections::BTreeSet;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FieldChange {
+    pub path: String,
+    pub from: Option<Value>,
+    pub to: Option<Value>,
+}
+
+/// Compares two JSON documents field by field. Arrays of named objects (like stages) are matched by
+/// their `name`, so reordering or inserting a stage does not show up as a change to every later stage.
+pub fn diff_json(from: &Value, to: &Value) -> Vec<FieldChange> {
+    let mut changes = vec![];
+    diff_value("", Some(from), Some(to), &mut changes);
+    changes
+}
+
+fn diff_value(path: &str, from: Option<&Value>, to: Option<&Value>, changes: &mut Vec<FieldChange>) {
+    match (from, to) {
+        (Some(Value::Object(from_map)), Some(Value::Object(to_map))) => {
+            let keys: BTreeSet<&String> = from_map.keys().chain(to_map.keys()).collect();
+            for key in keys {
+                diff_value(&join_path(path, key), from_map.get(key), to_map.get(key), changes);
+            }
+        }
+        (Some(Value::Array(from_items)), Some(Value::Array(to_items))) => {
+            if let (Some(from_named), Some(to_named)) = (named_items(from_items), named_items(to_items)) {
+                let names: BTreeSet<&str> = from_named.iter().chain(to_named.iter()).map(|(name, _)| *name).collect();
+                for name in names {
+                    diff_value(&format!("{}[{}]", path, name), find_named(&from_named, name), find_named(&to_named, name), changes);
+                }
+            } else {
+                for index in 0..from_items.len().max(to_items.len()) {
+                    diff_value(&format!("{}[{}]", path, index), from_items.get(index), to_items.get(index), changes);
+                }
+            }
+        }
+        (from, to) if from != to => changes.push(FieldChange {
+            path: path.to_string(),
+            from: from.cloned(),
+            to: to.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+fn named_items(items: &[Value]) -> Option<Vec<(&str, &Value)>> {
+    items.iter()
+        .map(|item| item.get("name").and_then(Value::as_str).map(|name| (name, item)))
+        .collect()
+}
+
+fn find_named<'a>(items: &[(&str, &'a Value)], name: &str) -> Option<&'a Value> {
+    items.iter().find(|(n, _)| *n == name).map(|(_, value)| *value)
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::models::{ApplicationConfig, JobMetadata};
+use crate::validations::validate_job_metadata;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplicationDto {
+    pub app_name: String,
+    /// Applied to the jobs of the application where they don't set their own.
+    pub metadata: diesel_json::Json<JobMetadata>,
+}
+
+impl From<ApplicationConfig> for ApplicationDto {
+    fn from(application_config: ApplicationConfig) -> Self {
+        Self {
+            app_name: application_config.app_name,
+            metadata: application_config.metadata,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Validate)]
+pub struct ApplicationUpdateRequest {
+    #[serde(default)]
+    #[validate(custom(function = "validate_job_metadata"))]
+    pub metadata: diesel_json::Json<JobMetadata>,
+}
@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use validator::Validate;
+use crate::models::AuditLog;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct AuditLogQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub actor: Option<String>,
+    pub target: Option<String>,
+    pub method: Option<String>,
+    pub route: Option<String>,
+    #[validate(range(min = 1, max = 1000))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogDto {
+    pub id: i64,
+    pub occurred_at: DateTime<Utc>,
+    pub actor: Option<String>,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub method: String,
+    pub route: String,
+    pub path: String,
+    pub target: Option<String>,
+    pub request_summary: Option<Value>,
+    pub status_code: i32,
+}
+
+impl From<AuditLog> for AuditLogDto {
+    fn from(audit_log: AuditLog) -> Self {
+        Self {
+            id: audit_log.id,
+            occurred_at: audit_log.occurred_at,
+            actor: audit_log.actor,
+            client_ip: audit_log.client_ip,
+            user_agent: audit_log.user_agent,
+            method: audit_log.method,
+            route: audit_log.route,
+            path: audit_log.path,
+            target: audit_log.target,
+            request_summary: audit_log.request_summary,
+            status_code: audit_log.status_code,
+        }
+    }
+}
@@ -1,7 +1,11 @@
 use crate::validations::{validate_name, validate_config_json};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use validator::Validate;
+use crate::errors::AppError;
 use crate::models::{Channel, ProviderType};
+use crate::notification::plugin_registry::DeliveryReport;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct ChannelResponseDto {
@@ -34,4 +38,52 @@ pub struct ChannelUpdateRequest {
     pub provider_type: ProviderType,
     #[validate(custom(function = "validate_config_json"))]
     pub configuration: String,
-}
\ No newline at end of file
+}
+
+/// The revisioned part of a channel, as stored in `channel_revisions.snapshot`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ChannelSnapshot {
+    pub name: String,
+    pub provider_type: ProviderType,
+    pub configuration: Value,
+}
+
+impl From<Channel> for ChannelSnapshot {
+    fn from(channel: Channel) -> Self {
+        Self {
+            name: channel.name,
+            provider_type: channel.provider_type,
+            configuration: channel.configuration,
+        }
+    }
+}
+
+/// The outcome of a test alert sent through a channel.
+#[derive(Clone, Debug, Serialize)]
+pub struct ChannelTestResultDto {
+    pub channel: String,
+    pub provider_type: ProviderType,
+    pub success: bool,
+    pub status_code: Option<u16>,
+    pub response: Option<String>,
+    pub error: Option<String>,
+    pub tested_at: DateTime<Utc>,
+}
+
+impl ChannelTestResultDto {
+    pub fn new(channel: &Channel, result: Result<DeliveryReport, AppError>, tested_at: DateTime<Utc>) -> Self {
+        let (report, error) = match result {
+            Ok(report) => (report, None),
+            Err(e) => (DeliveryReport::default(), Some(e.message().to_string())),
+        };
+        Self {
+            channel: channel.name.clone(),
+            provider_type: channel.provider_type.clone(),
+            success: report.success,
+            status_code: report.status_code,
+            response: report.response,
+            error,
+            tested_at,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use crate::health::{ListenerHealth, SchedulerHealth, ServiceHealth};
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Healthy,
+    /// Still detecting timeouts, but settings changes and live run events from other instances are not received.
+    Degraded,
+    Unhealthy,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseHealthDto {
+    pub connected: bool,
+    pub latency_ms: Option<i64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SchedulerHealthDto {
+    pub stalled: bool,
+    #[serde(flatten)]
+    pub details: SchedulerHealth,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthDto {
+    pub status: HealthStatus,
+    pub started_at: DateTime<Utc>,
+    pub checked_at: DateTime<Utc>,
+    pub database: DatabaseHealthDto,
+    pub scheduler: SchedulerHealthDto,
+    pub listener: ListenerHealth,
+}
+
+impl HealthDto {
+    pub fn new(health: ServiceHealth, scheduler_stalled: bool, database: DatabaseHealthDto, checked_at: DateTime<Utc>) -> Self {
+        let status = if !database.connected || scheduler_stalled {
+            HealthStatus::Unhealthy
+        } else if !health.listener.connected {
+            HealthStatus::Degraded
+        } else {
+            HealthStatus::Healthy
+        };
+
+        Self {
+            status,
+            started_at: health.started_at,
+            checked_at,
+            database,
+            scheduler: SchedulerHealthDto { stalled: scheduler_stalled, details: health.scheduler },
+            listener: health.listener,
+        }
+    }
+}
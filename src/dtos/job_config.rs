@@ -1,7 +1,8 @@
-use crate::validations::validate_name;
+use crate::validations::{validate_blackout_windows, validate_job_metadata, validate_name, validate_schedule, validate_stages, validate_zone_id};
 use serde::{Deserialize, Serialize};
-use validator::Validate;
-use crate::models::{JobConfig, JobStageConfig};
+use validator::{Validate, ValidationError};
+use crate::dtos::snooze::SnoozeDto;
+use crate::models::{BlackoutWindow, ConcurrencyPolicy, JobConfig, JobMetadata, JobStageConfig};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobConfigDto {
@@ -12,6 +13,18 @@ pub struct JobConfigDto {
     pub enabled: bool,
     pub stages: diesel_json::Json<Vec<JobStageConfig>>,
     pub channel_ids: String,
+    // Absent from snapshots taken before the policy existed
+    #[serde(default)]
+    pub concurrency_policy: ConcurrencyPolicy,
+    #[serde(default)]
+    pub blackout_windows: diesel_json::Json<Vec<BlackoutWindow>>,
+    /// The job's own metadata; alerts also carry what its application sets.
+    #[serde(default)]
+    pub metadata: diesel_json::Json<JobMetadata>,
+    /// The snooze silencing the job right now, its own or its application's. Not part of the configuration,
+    /// so never in revision snapshots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snooze: Option<SnoozeDto>,
 }
 
 impl From<JobConfig> for JobConfigDto {
@@ -24,26 +37,86 @@ impl From<JobConfig> for JobConfigDto {
             enabled: job_config.enabled,
             stages: job_config.stages,
             channel_ids: job_config.channel_ids,
+            concurrency_policy: job_config.concurrency_policy,
+            blackout_windows: job_config.blackout_windows,
+            metadata: job_config.metadata,
+            snooze: None,
         }
     }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_create_schedule_zone"))]
 pub struct JobConfigCreateRequest {
     #[validate(custom(function = "validate_name"))]
     pub app_name: String,
     #[validate(custom(function = "validate_name"))]
     pub job_name: String,
+    #[validate(custom(function = "validate_schedule"))]
     pub schedule: Option<String>,
+    #[validate(custom(function = "validate_zone_id"))]
     pub zone_id: Option<String>,
+    #[validate(custom(function = "validate_stages"))]
     pub stages: diesel_json::Json<Vec<JobStageConfig>>,
     pub channel_ids: String,
+    #[serde(default)]
+    pub concurrency_policy: ConcurrencyPolicy,
+    #[serde(default)]
+    #[validate(custom(function = "validate_blackout_windows"))]
+    pub blackout_windows: diesel_json::Json<Vec<BlackoutWindow>>,
+    #[serde(default)]
+    #[validate(custom(function = "validate_job_metadata"))]
+    pub metadata: diesel_json::Json<JobMetadata>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_update_schedule_zone"))]
 pub struct JobConfigUpdateRequest {
+    #[validate(custom(function = "validate_schedule"))]
     pub schedule: Option<String>,
+    #[validate(custom(function = "validate_zone_id"))]
     pub zone_id: Option<String>,
+    #[validate(custom(function = "validate_stages"))]
     pub stages: diesel_json::Json<Vec<JobStageConfig>>,
     pub channel_ids: String,
+    #[serde(default)]
+    pub concurrency_policy: ConcurrencyPolicy,
+    #[serde(default)]
+    #[validate(custom(function = "validate_blackout_windows"))]
+    pub blackout_windows: diesel_json::Json<Vec<BlackoutWindow>>,
+    #[serde(default)]
+    #[validate(custom(function = "validate_job_metadata"))]
+    pub metadata: diesel_json::Json<JobMetadata>,
+}
+
+/// Filters job configs on their metadata, merged with their application's.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JobConfigQuery {
+    pub owner_team: Option<String>,
+    pub tag: Option<String>,
+}
+
+impl JobConfigQuery {
+    pub fn matches(&self, metadata: &JobMetadata) -> bool {
+        self.owner_team.as_ref().is_none_or(|owner_team| metadata.owner_team.as_ref() == Some(owner_team))
+            && self.tag.as_ref().is_none_or(|tag| metadata.tags.contains(tag))
+    }
+}
+
+fn validate_create_schedule_zone(request: &JobConfigCreateRequest) -> Result<(), ValidationError> {
+    validate_schedule_zone(&request.schedule, &request.zone_id)
+}
+
+fn validate_update_schedule_zone(request: &JobConfigUpdateRequest) -> Result<(), ValidationError> {
+    validate_schedule_zone(&request.schedule, &request.zone_id)
+}
+
+// Scheduled jobs are evaluated in their own zone, so a schedule without a zone is never checked
+fn validate_schedule_zone(schedule: &Option<String>, zone_id: &Option<String>) -> Result<(), ValidationError> {
+    if schedule.is_some() && zone_id.is_none() {
+        let mut error = ValidationError::new("missing_zone_id");
+        error.message = Some("zone_id is required when a schedule is configured".into());
+        return Err(error);
+    }
+    Ok(())
 }
\ No newline at end of file
@@ -1,18 +1,6 @@
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use crate::models::{JobRun, JobRunStage, JobRunStatus};
+use crate::models::{JobRun, JobRunStatus};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct JobRunDto {
-    pub id: String,
-    pub app_name: String,
-    pub job_name: String,
-    pub triggered_at: DateTime<Utc>,
-    pub status: JobRunStatus,
-    pub stages: diesel_json::Json<Vec<JobRunStage>>,
-    pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
-}
+pub use watchdog_api::job_run::JobRunDto;
 
 impl From<JobRun> for JobRunDto {
     fn from(job_run: JobRun) -> Self {
@@ -21,10 +9,40 @@ impl From<JobRun> for JobRunDto {
             app_name: job_run.app_name,
             job_name: job_run.job_name,
             triggered_at: job_run.triggered_at,
-            status: job_run.status,
-            stages: job_run.stages,
+            status: job_run.status.into(),
+            stages: job_run.stages.0,
             created_at: job_run.created_at,
             updated_at: job_run.updated_at,
+            scheduled_for: job_run.scheduled_for,
+            run_key: job_run.run_key,
+            status_reason: job_run.status_reason,
+        }
+    }
+}
+
+// Exhaustive both ways, so the database enum can't drift from the one clients see
+impl From<JobRunStatus> for watchdog_api::job_run::JobRunStatus {
+    fn from(status: JobRunStatus) -> Self {
+        match status {
+            JobRunStatus::InProgress => Self::InProgress,
+            JobRunStatus::Complete => Self::Complete,
+            JobRunStatus::Failed => Self::Failed,
+            JobRunStatus::Replaced => Self::Replaced,
+            JobRunStatus::Skipped => Self::Skipped,
+            JobRunStatus::Cancelled => Self::Cancelled,
+        }
+    }
+}
+
+impl From<watchdog_api::job_run::JobRunStatus> for JobRunStatus {
+    fn from(status: watchdog_api::job_run::JobRunStatus) -> Self {
+        match status {
+            watchdog_api::job_run::JobRunStatus::InProgress => Self::InProgress,
+            watchdog_api::job_run::JobRunStatus::Complete => Self::Complete,
+            watchdog_api::job_run::JobRunStatus::Failed => Self::Failed,
+            watchdog_api::job_run::JobRunStatus::Replaced => Self::Replaced,
+            watchdog_api::job_run::JobRunStatus::Skipped => Self::Skipped,
+            watchdog_api::job_run::JobRunStatus::Cancelled => Self::Cancelled,
         }
     }
 }
@@ -1,4 +1,12 @@
 pub mod channel;
 pub mod job_config;
 pub mod job_run;
-pub mod settings;
\ No newline at end of file
+pub mod settings;
+pub mod schedule;
+pub mod revision;
+pub mod audit;
+pub mod run_event;
+pub mod health;
+pub mod snooze;
+pub mod application;
+pub mod routing_rule;
\ No newline at end of file
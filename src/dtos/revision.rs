@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::diff_utils::FieldChange;
+use crate::models::{ChannelRevision, JobConfigRevision, RevisionChangeType};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionDto {
+    pub revision: i32,
+    pub change_type: RevisionChangeType,
+    pub changed_by: Option<String>,
+    pub snapshot: Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<JobConfigRevision> for RevisionDto {
+    fn from(revision: JobConfigRevision) -> Self {
+        Self {
+            revision: revision.revision,
+            change_type: revision.change_type,
+            changed_by: revision.changed_by,
+            snapshot: revision.snapshot,
+            created_at: revision.created_at,
+        }
+    }
+}
+
+impl From<ChannelRevision> for RevisionDto {
+    fn from(revision: ChannelRevision) -> Self {
+        Self {
+            revision: revision.revision,
+            change_type: revision.change_type,
+            changed_by: revision.changed_by,
+            snapshot: revision.snapshot,
+            created_at: revision.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionDiffQuery {
+    pub from: i32,
+    pub to: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionDiffDto {
+    pub from_revision: i32,
+    pub to_revision: i32,
+    pub changes: Vec<FieldChange>,
+}
@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::core::alert_routing::AlertRouting;
+use crate::models::{RoutingMatcher, RoutingRule, Severity};
+use crate::notification::core::AlertType;
+use crate::validations::{validate_channel_ids, validate_name, validate_routing_matcher};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingRuleDto {
+    pub name: String,
+    pub priority: i32,
+    pub enabled: bool,
+    pub matcher: diesel_json::Json<RoutingMatcher>,
+    pub channel_ids: String,
+    #[serde(rename = "continue")]
+    pub continue_matching: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<RoutingRule> for RoutingRuleDto {
+    fn from(routing_rule: RoutingRule) -> Self {
+        Self {
+            name: routing_rule.name,
+            priority: routing_rule.priority,
+            enabled: routing_rule.enabled,
+            matcher: routing_rule.matcher,
+            channel_ids: routing_rule.channel_ids,
+            continue_matching: routing_rule.continue_matching,
+            created_at: routing_rule.created_at,
+            updated_at: routing_rule.updated_at,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Validate)]
+pub struct RoutingRuleCreateRequest {
+    #[validate(custom(function = "validate_name"))]
+    pub name: String,
+    #[serde(default)]
+    pub priority: i32,
+    /// Enabled when absent.
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    #[validate(custom(function = "validate_routing_matcher"))]
+    pub matcher: diesel_json::Json<RoutingMatcher>,
+    #[validate(custom(function = "validate_channel_ids"))]
+    pub channel_ids: String,
+    #[serde(default, rename = "continue")]
+    pub continue_matching: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Validate)]
+pub struct RoutingRuleUpdateRequest {
+    #[serde(default)]
+    pub priority: i32,
+    /// Left as it is when absent.
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    #[validate(custom(function = "validate_routing_matcher"))]
+    pub matcher: diesel_json::Json<RoutingMatcher>,
+    #[validate(custom(function = "validate_channel_ids"))]
+    pub channel_ids: String,
+    #[serde(default, rename = "continue")]
+    pub continue_matching: bool,
+}
+
+/// A hypothetical alert to route. The job's metadata is used for its tags unless `tags` are given.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RoutingTestRequest {
+    pub app_name: String,
+    pub job_name: String,
+    #[serde(default)]
+    pub stage_name: String,
+    pub alert_type: AlertType,
+    /// The severity of the stage when absent, as alerts get it.
+    pub severity: Option<Severity>,
+    /// When the alert is sent, now when absent.
+    pub at: Option<DateTime<Utc>>,
+    pub tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingTestResultDto {
+    pub matched_rules: Vec<String>,
+    pub channel_ids: Vec<String>,
+    /// Whether no rule matched, so the alert goes to the job's channels, or the error channels for errors.
+    pub is_default: bool,
+    pub at: DateTime<Utc>,
+}
+
+impl RoutingTestResultDto {
+    pub fn new(routing: AlertRouting, at: DateTime<Utc>) -> Self {
+        Self {
+            matched_rules: routing.matched_rules,
+            channel_ids: routing.channel_ids,
+            is_default: routing.is_default,
+            at,
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::models::{JobRun, JobRunEvent, JobRunStatus, RunEventSource, RunEventType};
+use crate::time_utils::get_utc_now;
+
+/// A run lifecycle event, recorded in the run's timeline, published on the `run_events` channel and streamed to SSE subscribers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunEvent {
+    pub event_type: RunEventType,
+    pub job_run_id: String,
+    pub app_name: String,
+    pub job_name: String,
+    pub stage_name: Option<String>,
+    /// The attempt of the stage the event is about.
+    #[serde(default)]
+    pub attempt: Option<u32>,
+    pub status: JobRunStatus,
+    /// What was reported with the event, e.g. the error of a failed stage or why a run was skipped.
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub source: RunEventSource,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl RunEvent {
+    pub fn new(event_type: RunEventType, job_run: &JobRun, stage_name: Option<&str>, source: RunEventSource) -> Self {
+        Self {
+            event_type,
+            job_run_id: job_run.id.to_string(),
+            app_name: job_run.app_name.clone(),
+            job_name: job_run.job_name.clone(),
+            stage_name: stage_name.map(str::to_string),
+            attempt: None,
+            status: job_run.status.clone(),
+            message: None,
+            source,
+            occurred_at: get_utc_now(),
+        }
+    }
+
+    pub fn with_attempt(mut self, attempt: Option<u32>) -> Self {
+        self.attempt = attempt;
+        self
+    }
+
+    pub fn with_message(mut self, message: Option<String>) -> Self {
+        self.message = message;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunEventFilter {
+    pub app_name: Option<String>,
+    pub job_name: Option<String>,
+}
+
+impl RunEventFilter {
+    pub fn matches(&self, event: &RunEvent) -> bool {
+        self.app_name.as_ref().is_none_or(|app_name| *app_name == event.app_name)
+            && self.job_name.as_ref().is_none_or(|job_name| *job_name == event.job_name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRunEventDto {
+    pub id: i64,
+    pub event_type: RunEventType,
+    pub stage_name: Option<String>,
+    pub attempt: Option<u32>,
+    /// The status of the run once the event was applied.
+    pub status: JobRunStatus,
+    pub message: Option<String>,
+    pub source: RunEventSource,
+    pub occurred_at: DateTime<Utc>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl From<JobRunEvent> for JobRunEventDto {
+    fn from(event: JobRunEvent) -> Self {
+        Self {
+            id: event.id,
+            event_type: event.event_type,
+            stage_name: event.stage_name,
+            attempt: event.attempt.map(|attempt| attempt as u32),
+            status: event.status,
+            message: event.message,
+            source: event.source,
+            occurred_at: event.occurred_at,
+            recorded_at: event.recorded_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StageAttemptStatus {
+    Running,
+    Completed,
+    Retrying,
+    Failed,
+    /// The start or the completion of the attempt was not reported in time.
+    TimedOut,
+}
+
+/// One attempt of a stage, as told by the run's events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageAttemptDto {
+    pub name: String,
+    pub attempt: Option<u32>,
+    pub status: StageAttemptStatus,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    /// The latest message reported for the attempt.
+    pub message: Option<String>,
+}
+
+/// Body of `GET /job-runs/{job_run_id}/events`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRunTimelineDto {
+    pub job_run_id: String,
+    pub events: Vec<JobRunEventDto>,
+    /// The stage attempts derived from `events`, in the order they were first seen.
+    pub stages: Vec<StageAttemptDto>,
+}
@@ -0,0 +1,109 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::core::job_run_matching::get_event_stage_map;
+use crate::core::schedule_forecast::{ForecastEntry, ForecastStatus};
+use crate::cron_utils::get_stage_deadline;
+use crate::models::{JobRunStageStatus, JobStageConfig};
+use crate::validations::{validate_schedule, validate_stages, validate_zone_id};
+
+#[derive(Clone, Debug, Serialize, Deserialize, Validate)]
+pub struct SchedulePreviewRequest {
+    #[validate(custom(function = "validate_schedule"))]
+    pub schedule: String,
+    #[validate(custom(function = "validate_zone_id"))]
+    pub zone_id: String,
+    #[validate(custom(function = "validate_stages"))]
+    pub stages: diesel_json::Json<Vec<JobStageConfig>>,
+    #[validate(range(min = 1, max = 100))]
+    pub count: Option<usize>,
+    pub from: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageDeadlineDto {
+    pub name: String,
+    pub start_deadline: Option<DateTime<FixedOffset>>,
+    pub complete_deadline: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledOccurrenceDto {
+    pub start_time: DateTime<FixedOffset>,
+    pub stages: Vec<StageDeadlineDto>,
+}
+
+impl ScheduledOccurrenceDto {
+    pub fn new(start_time: &DateTime<Tz>, stages: &[JobStageConfig]) -> Self {
+        Self {
+            start_time: start_time.fixed_offset(),
+            stages: stages
+                .iter()
+                .map(|stage| StageDeadlineDto {
+                    name: stage.name.clone(),
+                    start_deadline: get_stage_deadline(start_time, stage.start).map(|dt| dt.fixed_offset()),
+                    complete_deadline: get_stage_deadline(start_time, stage.complete).map(|dt| dt.fixed_offset()),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleForecastQuery {
+    /// Defaults to the start of the current day in `zone_id`.
+    pub from: Option<DateTime<Utc>>,
+    /// Defaults to UTC.
+    pub zone_id: Option<String>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForecastStageDto {
+    pub name: String,
+    pub start_deadline: Option<DateTime<FixedOffset>>,
+    pub start_status: Option<JobRunStageStatus>,
+    pub complete_deadline: Option<DateTime<FixedOffset>>,
+    pub complete_status: Option<JobRunStageStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForecastEntryDto {
+    pub app_name: String,
+    pub job_name: String,
+    pub start_time: DateTime<FixedOffset>,
+    pub scheduled: bool,
+    pub status: ForecastStatus,
+    pub job_run_id: Option<String>,
+    pub stages: Vec<ForecastStageDto>,
+}
+
+impl From<ForecastEntry> for ForecastEntryDto {
+    fn from(entry: ForecastEntry) -> Self {
+        let occurring_stages_map = entry.job_run.as_ref().map(get_event_stage_map).unwrap_or_default();
+        let stages = entry.job_config.stages
+            .iter()
+            .map(|stage| {
+                let occurring_stage = occurring_stages_map.get(&stage.name);
+                ForecastStageDto {
+                    name: stage.name.clone(),
+                    start_deadline: get_stage_deadline(&entry.start_time, stage.start).map(|dt| dt.fixed_offset()),
+                    start_status: occurring_stage.and_then(|s| s.start_status.clone()),
+                    complete_deadline: get_stage_deadline(&entry.start_time, stage.complete).map(|dt| dt.fixed_offset()),
+                    complete_status: occurring_stage.and_then(|s| s.complete_status.clone()),
+                }
+            })
+            .collect();
+
+        Self {
+            app_name: entry.job_config.app_name,
+            job_name: entry.job_config.job_name,
+            start_time: entry.start_time.fixed_offset(),
+            scheduled: entry.scheduled,
+            status: entry.status,
+            job_run_id: entry.job_run.map(|run| run.id.to_string()),
+            stages,
+        }
+    }
+}
@@ -9,7 +9,8 @@ pub struct SettingsResponseDto {
     pub maintenance_mode: bool,
     pub default_channels: String,
     pub error_channels: String,
-    pub max_stage_duration_hours: i32
+    pub max_stage_duration_hours: i32,
+    pub audit_retention_days: i32,
 }
 
 impl From<Settings> for SettingsResponseDto {
@@ -20,7 +21,8 @@ impl From<Settings> for SettingsResponseDto {
             maintenance_mode: settings.maintenance_mode,
             default_channels: settings.default_channels,
             error_channels: settings.error_channels,
-            max_stage_duration_hours: settings.max_stage_duration_hours
+            max_stage_duration_hours: settings.max_stage_duration_hours,
+            audit_retention_days: settings.audit_retention_days,
         }
     }
 }
@@ -32,5 +34,7 @@ pub struct SettingsUpdateRequest {
     pub maintenance_mode: Option<bool>,
     pub default_channels: Option<String>,
     pub error_channels: Option<String>,
-    pub max_stage_duration_hours: Option<i32>
+    pub max_stage_duration_hours: Option<i32>,
+    #[validate(range(min = 1))]
+    pub audit_retention_days: Option<i32>,
 }
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::models::Snooze;
+
+/// Longest snooze, 30 days; longer silences should disable the job instead.
+const MAX_SNOOZE_SECONDS: u64 = 30 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnoozeDto {
+    pub app_name: String,
+    /// Absent when the whole application is snoozed.
+    pub job_name: Option<String>,
+    pub snoozed_until: DateTime<Utc>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Snooze> for SnoozeDto {
+    fn from(snooze: Snooze) -> Self {
+        Self {
+            app_name: snooze.app_name,
+            job_name: snooze.job_name,
+            snoozed_until: snooze.snoozed_until,
+            reason: snooze.reason,
+            created_at: snooze.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct SnoozeRequest {
+    #[validate(range(min = 1, max = MAX_SNOOZE_SECONDS))]
+    pub duration_seconds: u64,
+    pub reason: Option<String>,
+}
@@ -6,7 +6,7 @@ use chrono_tz::ParseError;
 use diesel_async::pooled_connection::PoolError;
 use strum_macros::Display;
 use validator::{ValidationErrors};
-use crate::jsend::AppResponse;
+use watchdog_api::jsend::AppResponse;
 
 #[derive(Debug, Display)]
 pub enum AppError {
@@ -19,6 +19,19 @@ pub enum AppError {
     InternalError(String),
 }
 
+impl AppError {
+    /// The detail carried by the error, without the variant name.
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::NotFound(msg)
+            | AppError::DatabaseError(msg)
+            | AppError::BadRequest(msg)
+            | AppError::Conflict(msg)
+            | AppError::InternalError(msg) => msg,
+        }
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> axum::response::Response {
         let (status, message) = match self {
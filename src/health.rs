@@ -0,0 +1,77 @@
+use std::sync::{Arc, RwLock};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use crate::time_utils::get_utc_now;
+
+/// The scheduler counts as stalled once no pass succeeded for this many fixed delays.
+const SCHEDULER_STALL_FACTOR: i32 = 3;
+
+/// Liveness of the background tasks, updated by the tasks themselves and read by the health endpoint.
+#[derive(Debug, Clone)]
+pub struct ServiceHealth {
+    pub started_at: DateTime<Utc>,
+    pub scheduler: SchedulerHealth,
+    pub listener: ListenerHealth,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SchedulerHealth {
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub last_pass_duration_ms: Option<i64>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+    pub consecutive_failures: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ListenerHealth {
+    pub connected: bool,
+    pub connected_since: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+pub type SharedHealth = Arc<RwLock<ServiceHealth>>;
+
+impl ServiceHealth {
+    pub fn new() -> Self {
+        Self {
+            started_at: get_utc_now(),
+            scheduler: SchedulerHealth::default(),
+            listener: ListenerHealth::default(),
+        }
+    }
+
+    pub fn record_scheduler_success(&mut self, finished_at: DateTime<Utc>, duration: Duration) {
+        self.scheduler.last_success_at = Some(finished_at);
+        self.scheduler.last_pass_duration_ms = Some(duration.num_milliseconds());
+        self.scheduler.consecutive_failures = 0;
+    }
+
+    /// Returns the number of passes that failed in a row, including this one.
+    pub fn record_scheduler_failure(&mut self, error: String) -> u32 {
+        self.scheduler.last_error = Some(error);
+        self.scheduler.last_error_at = Some(get_utc_now());
+        self.scheduler.consecutive_failures += 1;
+        self.scheduler.consecutive_failures
+    }
+
+    pub fn record_listener_connected(&mut self) {
+        self.listener.connected = true;
+        self.listener.connected_since = Some(get_utc_now());
+    }
+
+    pub fn record_listener_disconnected(&mut self, error: String) {
+        self.listener.connected = false;
+        self.listener.connected_since = None;
+        self.listener.last_error = Some(error);
+    }
+
+    /// Whether the scheduler has gone too long without a successful pass, counted from startup until the first one.
+    pub fn is_scheduler_stalled(&self, initial_delay_seconds: u64, fixed_delay_seconds: u64, now: DateTime<Utc>) -> bool {
+        let allowed = Duration::seconds(fixed_delay_seconds as i64) * SCHEDULER_STALL_FACTOR;
+        match self.scheduler.last_success_at {
+            Some(last_success_at) => now - last_success_at > allowed,
+            None => now - self.started_at > allowed + Duration::seconds(initial_delay_seconds as i64),
+        }
+    }
+}
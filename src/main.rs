@@ -1,23 +1,28 @@
 mod api;
+mod audit;
 mod config;
 mod core;
 mod cron_utils;
 mod db;
+mod diff_utils;
 mod dtos;
 mod errors;
-mod jsend;
+mod health;
 mod models;
 mod notification;
 mod pubsub;
 mod router;
 mod scheduler;
 mod schema;
+mod secrets;
 mod settings;
 mod time_utils;
 mod validations;
 mod migrations;
 
-use crate::config::{Config, from_env};
+use crate::config::{Config, LogFormat, load};
+use crate::dtos::run_event::RunEvent;
+use crate::health::{ServiceHealth, SharedHealth};
 use crate::models::Settings;
 use crate::notification::dispatcher::NotificationDispatcher;
 use crate::notification::init::init_notification;
@@ -28,43 +33,65 @@ use axum::Router;
 use db::connection::{PgPool, get_connection_pool};
 use std::sync::RwLock;
 use std::{net::SocketAddr, sync::Arc};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
 use tower_http::services::{ServeDir, ServeFile};
-use tracing::{Level, info};
+use tracing::{error, info, warn};
 use tracing_subscriber::FmtSubscriber;
 use crate::migrations::run_migrations;
 use crate::pubsub::start_listener;
+use crate::core::channel_secrets::seal_stored_channel_secrets;
 
 type SharedSettings = Arc<RwLock<Settings>>;
 
+const RUN_EVENTS_CAPACITY: usize = 1024;
+
 #[derive(Clone)]
 pub struct AppState {
     pub config: Config, // Note: Use the type name you have
     pub settings: SharedSettings,
     pub pool: PgPool, // Assuming your pool type is PgPool
     pub dispatcher: NotificationDispatcher,
+    pub run_events: broadcast::Sender<RunEvent>,
+    pub health: SharedHealth,
+    /// Cancelled when the server starts shutting down.
+    pub shutdown: CancellationToken,
 }
 
 type SharedState = Arc<AppState>;
 
 #[tokio::main]
 async fn main() {
-    // Set up logging
-    let subscriber = FmtSubscriber::builder()
-        .with_max_level(Level::INFO)
-        .finish();
-    tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+    let config = match load() {
+        Ok(config) => config,
+        Err(errors) => {
+            eprintln!("Invalid configuration:\n{}", errors);
+            std::process::exit(1);
+        }
+    };
 
-    let config = from_env();
+    // Set up logging
+    let subscriber = FmtSubscriber::builder().with_max_level(config.log_level);
+    match config.log_format {
+        LogFormat::Full => tracing::subscriber::set_global_default(subscriber.finish()),
+        LogFormat::Compact => tracing::subscriber::set_global_default(subscriber.compact().finish()),
+        LogFormat::Json => tracing::subscriber::set_global_default(subscriber.json().finish()),
+    }.expect("setting default subscriber failed");
 
     // 1. Run Migrations First (Synchronous/Blocking)
     // We do this before starting the async runtime's heavy lifting
     run_migrations(&config.postgres_url).expect("Failed to run migrations!");
 
-    let pool: PgPool = get_connection_pool(&config.postgres_url)
+    let pool: PgPool = get_connection_pool(&config.postgres_url, &config.db_pool)
         .await
         .expect("Failed to create Postgres connection pool! Is the DB running?");
 
-    let dispatcher = init_notification(pool.clone()).await;
+    let dispatcher = init_notification(pool.clone(), &config).await;
+
+    if let Err(e) = seal_stored_channel_secrets(&pool, &dispatcher).await {
+        error!("Failed to encrypt stored channel secrets: {}", e.message());
+    }
 
     let initial_settings = from_db(&pool)
         .await
@@ -75,19 +102,25 @@ async fn main() {
         settings: Arc::new(RwLock::new(initial_settings)),
         pool: pool.clone(),
         dispatcher: dispatcher.clone(),
+        run_events: broadcast::channel(RUN_EVENTS_CAPACITY).0,
+        health: Arc::new(RwLock::new(ServiceHealth::new())),
+        shutdown: CancellationToken::new(),
     });
+    let shutdown = state.shutdown.clone();
 
     // tokio::spawn(scheduler(&get_connection_pool(&config.postgres_url).await.unwrap()));
     let scheduler_pool = pool.clone();
     let state_clone = state.clone();
-    tokio::spawn(async move { scheduler(&scheduler_pool, &dispatcher, state_clone).await });
+    let scheduler_task = tokio::spawn(async move { scheduler(&scheduler_pool, &dispatcher, state_clone).await });
 
     let pub_sub_pool = pool.clone();
-    start_listener(pub_sub_pool, state.clone()).await;
+    let listener_task = start_listener(pub_sub_pool, state.clone()).await;
 
-    let web_build_path = "./web/dist";
-    let serve_dir = ServeDir::new(web_build_path)
-        .not_found_service(ServeFile::new(format!("{}/index.html", web_build_path)));
+    if !config.static_dir.is_dir() {
+        warn!("Static directory {} does not exist, the web UI will not be served", config.static_dir.display());
+    }
+    let serve_dir = ServeDir::new(&config.static_dir)
+        .not_found_service(ServeFile::new(config.static_dir.join("index.html")));
 
     // Build Axum routes
     let api_routes = app_routes(state);
@@ -97,8 +130,60 @@ async fn main() {
         .fallback_service(serve_dir);
 
     // Run the server
-    let addr = SocketAddr::from(([0, 0, 0, 0], 8080));
+    let addr = config.bind_address;
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .unwrap_or_else(|e| panic!("Failed to bind to {}: {}", addr, e));
     info!("Watchdog service listening on {}", addr);
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    let server_task = tokio::spawn(
+        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+            .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+            .into_future()
+    );
+
+    wait_for_shutdown_signal().await;
+    info!("Shutting down: no longer accepting requests, waiting up to {}secs for in-flight work", config.shutdown_timeout_seconds);
+    shutdown.cancel();
+
+    // Notifications are only ever in flight inside a request or a scheduler pass, so both finishing drains them too
+    let drained = tokio::time::timeout(Duration::from_secs(config.shutdown_timeout_seconds), async {
+        let (server_result, _) = tokio::join!(server_task, scheduler_task);
+        if let Ok(Err(e)) = server_result {
+            error!("Server error during shutdown: {}", e);
+        }
+    }).await;
+    if drained.is_err() {
+        warn!("In-flight work did not finish within {}secs, exiting anyway", config.shutdown_timeout_seconds);
+    }
+
+    // The listener only relays notifications, it has nothing to finish
+    listener_task.abort();
+    info!("Watchdog service stopped");
+}
+
+async fn wait_for_shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for ctrl-c: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => { signal.recv().await; }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
 }
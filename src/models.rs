@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveTime, Utc, Weekday};
 use diesel::{Queryable};
 use serde::{Deserialize, Serialize};
 use diesel::prelude::*;
@@ -6,6 +6,7 @@ use diesel_derive_enum::DbEnum;
 use serde_json::Value;
 use strum_macros::{Display, EnumIter};
 use uuid::Uuid;
+use crate::notification::core::AlertType;
 use crate::schema::*;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,6 +14,43 @@ pub struct JobStageConfig {
     pub name: String,
     pub start: Option<u64>,
     pub complete: Option<u64>,
+    /// Attempts the stage gets before a failure fails the run, 1 when absent.
+    #[serde(default)]
+    pub max_attempts: Option<u32>,
+    /// How urgent alerts of the stage are, critical when absent.
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+/// How urgent an alert is. Plugins render it, e.g. as a colour or a subject prefix, and routing rules can match on it.
+#[derive(Display, Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Critical,
+    Warning,
+    Info,
+}
+
+/// A recurring window during which timeouts of the job are recorded but not alerted on, e.g. a weekly maintenance.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BlackoutWindow {
+    /// Cron expression of the window's start, in the job's zone.
+    pub schedule: String,
+    pub duration_seconds: u64,
+}
+
+/// Who owns a job and what to do when it breaks, included in its alerts. Applications carry the same metadata,
+/// filling in what their jobs leave unset.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct JobMetadata {
+    pub owner_team: Option<String>,
+    /// How to reach the owners, e.g. an email address or a chat channel.
+    pub contact: Option<String>,
+    pub tags: Vec<String>,
+    pub runbook_url: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Selectable, AsChangeset)]
@@ -29,6 +67,9 @@ pub struct JobConfig {
     pub channel_ids: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub concurrency_policy: ConcurrencyPolicy,
+    pub blackout_windows: diesel_json::Json<Vec<BlackoutWindow>>,
+    pub metadata: diesel_json::Json<JobMetadata>,
 }
 
 #[derive(Debug, Insertable, Deserialize)]
@@ -41,6 +82,23 @@ pub struct NewJobConfig {
     pub zone_id: Option<String>,
     pub stages: diesel_json::Json<Vec<JobStageConfig>>,
     pub channel_ids: String,
+    pub concurrency_policy: ConcurrencyPolicy,
+    pub blackout_windows: diesel_json::Json<Vec<BlackoutWindow>>,
+    pub metadata: diesel_json::Json<JobMetadata>,
+}
+
+/// What happens when a run of a job starts while earlier runs are still in progress.
+#[derive(Display, Debug, Clone, Default, Serialize, Deserialize, DbEnum, PartialEq)]
+#[db_enum(existing_type_path = "crate::schema::sql_types::ConcurrencyPolicy")]
+#[db_enum(value_style = "snake_case")]
+pub enum ConcurrencyPolicy {
+    /// Runs overlap, each one is tracked against its own schedule occurrence.
+    #[default]
+    Allow,
+    /// Overlapping is alerted on, and runs triggered through the api are rejected.
+    Forbid,
+    /// The runs in progress are marked replaced and no longer tracked.
+    Replace,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, DbEnum, PartialEq)]
@@ -50,6 +108,9 @@ pub enum JobRunStatus {
     InProgress,
     Complete,
     Failed,
+    Replaced,
+    Skipped,
+    Cancelled,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Selectable, AsChangeset)]
@@ -61,26 +122,17 @@ pub struct JobRun {
     pub job_name: String,
     pub triggered_at: DateTime<Utc>,
     pub status: JobRunStatus,
+    /// Derived from the run's events when it is loaded; the column is never updated.
+    #[diesel(skip_update)]
     pub stages: diesel_json::Json<Vec<JobRunStage>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub scheduled_for: Option<DateTime<Utc>>,
+    pub run_key: Option<String>,
+    pub status_reason: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum JobRunStageStatus {
-    Occurred,
-    Failed,
-    Missed,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct JobRunStage {
-    pub name: String,
-    pub start_status: Option<JobRunStageStatus>,
-    pub start_date_time: Option<DateTime<Utc>>,
-    pub complete_status: Option<JobRunStageStatus>,
-    pub complete_date_time: Option<DateTime<Utc>>,
-}
+pub use watchdog_api::job_run::{JobRunStage, JobRunStageStatus};
 
 #[derive(Debug, Insertable, Deserialize)]
 #[diesel(table_name = job_runs)]
@@ -91,6 +143,71 @@ pub struct NewJobRun {
     pub triggered_at: DateTime<Utc>,
     pub status: JobRunStatus,
     pub stages: diesel_json::Json<Vec<JobRunStage>>,
+    pub scheduled_for: Option<DateTime<Utc>>,
+    pub run_key: Option<String>,
+}
+
+#[derive(Display, Debug, Clone, Serialize, Deserialize, DbEnum, PartialEq)]
+#[db_enum(existing_type_path = "crate::schema::sql_types::RunEventType")]
+#[db_enum(value_style = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum RunEventType {
+    RunCreated,
+    StageStarted,
+    StageCompleted,
+    StageFailed,
+    StageRetrying,
+    /// The start of the stage's attempt was not reported in time.
+    StartMissed,
+    /// The completion of the stage's attempt was not reported in time.
+    CompletionMissed,
+    StatusChanged,
+}
+
+/// What an event came from.
+#[derive(Display, Debug, Clone, Copy, Default, Serialize, Deserialize, DbEnum, PartialEq)]
+#[db_enum(existing_type_path = "crate::schema::sql_types::RunEventSource")]
+#[db_enum(value_style = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum RunEventSource {
+    /// Received through the api, or a consequence of a request to it.
+    #[default]
+    Api,
+    /// Synthesized by the scheduler, e.g. a detected timeout.
+    Scheduler,
+}
+
+/// An entry of a run's append-only event timeline.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = job_run_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct JobRunEvent {
+    pub id: i64,
+    pub job_run_id: Uuid,
+    pub event_type: RunEventType,
+    pub stage_name: Option<String>,
+    pub attempt: Option<i32>,
+    pub status: JobRunStatus,
+    pub message: Option<String>,
+    pub source: RunEventSource,
+    pub occurred_at: DateTime<Utc>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Deserialize)]
+#[diesel(table_name = job_run_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewJobRunEvent {
+    pub job_run_id: Uuid,
+    pub event_type: RunEventType,
+    pub stage_name: Option<String>,
+    pub attempt: Option<i32>,
+    pub status: JobRunStatus,
+    pub message: Option<String>,
+    pub source: RunEventSource,
+    pub occurred_at: DateTime<Utc>,
 }
 
 #[derive(Display, Debug, Clone, Serialize, Deserialize, DbEnum, PartialEq, EnumIter, Eq, Hash)]
@@ -99,6 +216,9 @@ pub struct NewJobRun {
 pub enum ProviderType {
     GchatWebhook,
     EmailSmtp,
+    Webhook,
+    TeamsWebhook,
+    Pagerduty,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Selectable, AsChangeset)]
@@ -134,5 +254,218 @@ pub struct Settings {
     pub maintenance_mode: bool,
     pub default_channels: String,
     pub error_channels: String,
-    pub max_stage_duration_hours: i32
-}
\ No newline at end of file
+    pub max_stage_duration_hours: i32,
+    pub audit_retention_days: i32,
+}
+
+#[derive(Display, Debug, Clone, Serialize, Deserialize, DbEnum, PartialEq)]
+#[db_enum(existing_type_path = "crate::schema::sql_types::RevisionChangeType")]
+#[db_enum(value_style = "snake_case")]
+pub enum RevisionChangeType {
+    Initial,
+    Created,
+    Updated,
+    Enabled,
+    RolledBack,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = job_config_revisions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct JobConfigRevision {
+    pub id: i64,
+    pub app_name: String,
+    pub job_name: String,
+    pub revision: i32,
+    pub change_type: RevisionChangeType,
+    pub changed_by: Option<String>,
+    pub snapshot: Value,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Deserialize)]
+#[diesel(table_name = job_config_revisions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewJobConfigRevision {
+    pub app_name: String,
+    pub job_name: String,
+    pub revision: i32,
+    pub change_type: RevisionChangeType,
+    pub changed_by: Option<String>,
+    pub snapshot: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = channel_revisions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ChannelRevision {
+    pub id: i64,
+    pub channel_name: String,
+    pub revision: i32,
+    pub change_type: RevisionChangeType,
+    pub changed_by: Option<String>,
+    pub snapshot: Value,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Deserialize)]
+#[diesel(table_name = channel_revisions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewChannelRevision {
+    pub channel_name: String,
+    pub revision: i32,
+    pub change_type: RevisionChangeType,
+    pub changed_by: Option<String>,
+    pub snapshot: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = audit_logs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AuditLog {
+    pub id: i64,
+    pub occurred_at: DateTime<Utc>,
+    pub actor: Option<String>,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub method: String,
+    pub route: String,
+    pub path: String,
+    pub target: Option<String>,
+    pub request_summary: Option<Value>,
+    pub status_code: i32,
+}
+
+#[derive(Debug, Insertable, Deserialize)]
+#[diesel(table_name = audit_logs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewAuditLog {
+    pub actor: Option<String>,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub method: String,
+    pub route: String,
+    pub path: String,
+    pub target: Option<String>,
+    pub request_summary: Option<Value>,
+    pub status_code: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = job_schedule_checkpoints)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(app_name, job_name))]
+pub struct JobScheduleCheckpoint {
+    pub app_name: String,
+    pub job_name: String,
+    pub evaluated_until: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Deserialize)]
+#[diesel(table_name = job_schedule_checkpoints)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewJobScheduleCheckpoint {
+    pub app_name: String,
+    pub job_name: String,
+    pub evaluated_until: DateTime<Utc>,
+}
+
+/// Alerts of a job, or of every job of the application when `job_name` is absent, are silenced until `snoozed_until`.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = snoozes)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Snooze {
+    pub id: Uuid,
+    pub app_name: String,
+    pub job_name: Option<String>,
+    pub snoozed_until: DateTime<Utc>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Deserialize)]
+#[diesel(table_name = snoozes)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewSnooze {
+    pub app_name: String,
+    pub job_name: Option<String>,
+    pub snoozed_until: DateTime<Utc>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = application_configs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(app_name))]
+pub struct ApplicationConfig {
+    pub app_name: String,
+    pub metadata: diesel_json::Json<JobMetadata>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Deserialize)]
+#[diesel(table_name = application_configs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewApplicationConfig {
+    pub app_name: String,
+    pub metadata: diesel_json::Json<JobMetadata>,
+}
+
+/// Sends the alerts it matches to its channels. Enabled rules are evaluated by ascending priority, a match ending the
+/// evaluation unless the rule has `continue_matching`. Alerts no rule matches go to the job's own channels.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Selectable, AsChangeset)]
+#[diesel(table_name = routing_rules)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(name))]
+pub struct RoutingRule {
+    pub name: String,
+    pub priority: i32,
+    pub enabled: bool,
+    pub matcher: diesel_json::Json<RoutingMatcher>,
+    pub channel_ids: String,
+    pub continue_matching: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Deserialize)]
+#[diesel(table_name = routing_rules)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewRoutingRule {
+    pub name: String,
+    pub priority: i32,
+    pub enabled: bool,
+    pub matcher: diesel_json::Json<RoutingMatcher>,
+    pub channel_ids: String,
+    pub continue_matching: bool,
+}
+
+/// What an alert has to be about for a rule to match it; every condition set has to hold, unset ones match anything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RoutingMatcher {
+    /// Glob patterns, `*` matching any number of characters and `?` exactly one.
+    pub app_name: Option<String>,
+    pub job_name: Option<String>,
+    /// Matches jobs having any of the tags, their own or their application's.
+    pub tags: Vec<String>,
+    pub alert_types: Vec<AlertType>,
+    pub severities: Vec<Severity>,
+    pub stage_names: Vec<String>,
+    pub time_window: Option<TimeWindow>,
+}
+
+/// A daily window, e.g. business hours. Windows ending before they start run past midnight.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TimeWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    /// The zone of `start` and `end`, UTC when absent.
+    #[serde(default)]
+    pub zone_id: Option<String>,
+    /// The days windows start on, every day when empty.
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+}
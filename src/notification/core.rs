@@ -1,33 +1,132 @@
 use serde::{Deserialize, Serialize};
-use tracing::error;
+use tracing::{error, info};
 use crate::errors::AppError;
-use crate::models::{JobConfig, JobRun};
+use crate::models::{JobConfig, JobMetadata, JobRun, Severity};
 use crate::notification::core::AlertType::{Error, Failed, Timeout};
 use crate::notification::dispatcher::NotificationDispatcher;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum AlertType {
     Error,
     Timeout,
     Failed,
+    Test,
 }
 
-pub async fn send_timeout(dispatcher: &NotificationDispatcher, job_config: &JobConfig, job_run: &JobRun, stage_name: &str) -> Result<(), AppError>  {
-    dispatcher.dispatch(&job_config.app_name, &job_config.job_name, Some(job_run.id.to_string()), stage_name, &job_config.channel_ids, None, Timeout).await
+/// An alert about a stage of a job, as dispatched to its channels and handed to their plugins.
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub app_name: String,
+    pub job_name: String,
+    pub run_id: Option<String>,
+    pub stage_name: String,
+    pub alert_type: AlertType,
+    pub severity: Severity,
+    pub message: Option<String>,
 }
 
-pub async fn send_failed(dispatcher: &NotificationDispatcher, job_config: &JobConfig, job_run: &JobRun, stage_name: &str, message: &String, channel_ids_str: &str) -> Result<(), AppError> {
-    dispatcher.dispatch(&job_config.app_name, &job_config.job_name, Some(job_run.id.to_string()), stage_name, channel_ids_str, Some(message.to_string()), Failed).await
+/// The metadata shown in alerts as label and value, leaving out what isn't set.
+pub fn get_metadata_fields(metadata: &JobMetadata) -> Vec<(&'static str, String)> {
+    let mut fields = vec![];
+    if let Some(owner_team) = &metadata.owner_team {
+        fields.push(("Owner Team", owner_team.clone()));
+    }
+    if let Some(contact) = &metadata.contact {
+        fields.push(("Contact", contact.clone()));
+    }
+    if !metadata.tags.is_empty() {
+        fields.push(("Tags", metadata.tags.join(", ")));
+    }
+    if let Some(runbook_url) = &metadata.runbook_url {
+        fields.push(("Runbook", runbook_url.clone()));
+    }
+    fields
+}
+
+/// Prefix of alert titles telling their severity, e.g. `[WARNING] `. Test alerts go without.
+pub fn get_severity_prefix(alert_type: &AlertType, severity: Severity) -> String {
+    match alert_type {
+        AlertType::Test => String::new(),
+        _ => format!("[{}] ", severity.to_string().to_uppercase()),
+    }
+}
+
+/// The severity of alerts about the stage; alerts not about a configured stage, e.g. of overlapping runs, are critical.
+pub fn get_stage_severity(job_config: &JobConfig, stage_name: &str) -> Severity {
+    job_config.stages.iter()
+        .find(|stage| stage.name == stage_name)
+        .map(|stage| stage.severity)
+        .unwrap_or_default()
+}
+
+pub async fn send_timeout(dispatcher: &NotificationDispatcher, job_config: &JobConfig, job_run: &JobRun, stage_name: &str, message_opt: Option<String>) -> Result<(), AppError>  {
+    if is_silenced(dispatcher, job_config, stage_name, &Timeout).await {
+        return Ok(());
+    }
+    let alert = Alert {
+        app_name: job_config.app_name.clone(),
+        job_name: job_config.job_name.clone(),
+        run_id: Some(job_run.id.to_string()),
+        stage_name: stage_name.to_string(),
+        alert_type: Timeout,
+        severity: get_stage_severity(job_config, stage_name),
+        message: message_opt,
+    };
+    dispatcher.dispatch(alert, &job_config.channel_ids).await
+}
+
+pub async fn send_failed(dispatcher: &NotificationDispatcher, job_config: &JobConfig, job_run: &JobRun, stage_name: &str, message: &str, channel_ids_str: &str) -> Result<(), AppError> {
+    if is_silenced(dispatcher, job_config, stage_name, &Failed).await {
+        return Ok(());
+    }
+    let alert = Alert {
+        app_name: job_config.app_name.clone(),
+        job_name: job_config.job_name.clone(),
+        run_id: Some(job_run.id.to_string()),
+        stage_name: stage_name.to_string(),
+        alert_type: Failed,
+        severity: get_stage_severity(job_config, stage_name),
+        message: Some(message.to_string()),
+    };
+    dispatcher.dispatch(alert, channel_ids_str).await
+}
+
+// Alerts are sent when the silences can't be looked up, a missed alert costs more than an extra one
+async fn is_silenced(dispatcher: &NotificationDispatcher, job_config: &JobConfig, stage_name: &str, alert_type: &AlertType) -> bool {
+    match dispatcher.get_silence_reason(job_config).await {
+        Ok(Some(reason)) => {
+            info!("suppressed {:?} alert of {}-{} stage {}, {}", alert_type, job_config.app_name, job_config.job_name, stage_name, reason);
+            true
+        }
+        Ok(None) => false,
+        Err(e) => {
+            error!("failed to look up silences of {}-{}, alerting anyway: {}", job_config.app_name, job_config.job_name, e.to_string());
+            false
+        }
+    }
+}
+
+pub async fn send_resolved(dispatcher: &NotificationDispatcher, job_config: &JobConfig, job_run: &JobRun) -> Result<(), AppError> {
+    dispatcher.dispatch_resolve(&job_config.app_name, &job_config.job_name, &job_run.id.to_string(), &job_config.channel_ids).await
 }
 
-pub async fn send_error(dispatcher: &NotificationDispatcher, app_name: &String, job_name: &String, job_run_id_opt: Option<String>, stage_name: &str, message: &String, channel_ids_str: &str)  -> Result<(), AppError> {
-    dispatcher.dispatch(app_name, job_name, job_run_id_opt, stage_name, channel_ids_str, Some(message.to_string()), Error).await
+pub async fn send_error(dispatcher: &NotificationDispatcher, app_name: &str, job_name: &str, job_run_id_opt: Option<String>, stage_name: &str, message: &str, channel_ids_str: &str)  -> Result<(), AppError> {
+    let alert = Alert {
+        app_name: app_name.to_string(),
+        job_name: job_name.to_string(),
+        run_id: job_run_id_opt,
+        stage_name: stage_name.to_string(),
+        alert_type: Error,
+        severity: Severity::Warning,
+        message: Some(message.to_string()),
+    };
+    dispatcher.dispatch(alert, channel_ids_str).await
 }
 
-pub async fn _handle_error(dispatcher: &NotificationDispatcher, app_name_and_job_name_option: Option<(String, String)>, job_run_id_opt: Option<String>, stage_name: &str, message: &String, channel_ids_str: &str) {
+pub async fn _handle_error(dispatcher: &NotificationDispatcher, app_name_and_job_name_option: Option<(String, String)>, job_run_id_opt: Option<String>, stage_name: &str, message: &str, channel_ids_str: &str) {
     let (app_name, job_name) = app_name_and_job_name_option.unwrap_or_else(|| ("NA".to_string(), "NA".to_string()));
     let job_run_id = job_run_id_opt.clone().unwrap_or_else(|| "NA".to_string());
-    let res = send_error(dispatcher, &app_name, &job_name, job_run_id_opt, &stage_name, message, channel_ids_str).await;
+    let res = send_error(dispatcher, &app_name, &job_name, job_run_id_opt, stage_name, message, channel_ids_str).await;
     if let Err(e) = res {
         error!("failed to send error notification: {} - {} - {} - {} - {}", app_name, job_name, stage_name, job_run_id, e.to_string());
     }
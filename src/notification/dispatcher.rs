@@ -1,27 +1,96 @@
 use serde_json::Value;
 use tracing::{error, info};
+use crate::core::alert_routing::{get_resolve_channel_ids, route_alert, RoutedAlert};
+use crate::core::alert_silencing::get_silence_reason;
+use crate::core::job_metadata::get_job_metadata;
 use crate::db::channel_repository::get_channel_by_name;
 use crate::db::connection::PgPool;
+use crate::db::routing_rule_repository::get_enabled_routing_rules;
 use crate::errors::AppError;
-use crate::models::{ProviderType};
-use crate::notification::core::{AlertType};
-use crate::notification::plugin_registry::PluginRegistry;
+use crate::models::{Channel, JobConfig, JobMetadata, ProviderType, Severity};
+use crate::notification::core::{Alert, AlertType};
+use crate::notification::plugin_registry::{DeliveryReport, PluginRegistry};
+use crate::secrets::{check_env_refs, has_plaintext_secrets, keep_omitted_secrets, redact, resolve_env_refs, SecretCipher, ENV_REF_PREFIX};
+use crate::time_utils::get_utc_now;
+
+const TEST_APP_NAME: &str = "watchdog";
+const TEST_JOB_NAME: &str = "channel-test";
 
 #[derive(Clone)]
 pub struct NotificationDispatcher {
     db: PgPool,
     registry: PluginRegistry,
+    cipher: SecretCipher,
+    allow_plaintext_secrets: bool,
 }
 
 impl NotificationDispatcher {
     pub fn new(
         db: PgPool,
         registry: PluginRegistry,
+        cipher: SecretCipher,
+        allow_plaintext_secrets: bool,
     ) -> Self {
         Self {
             db,
             registry,
+            cipher,
+            allow_plaintext_secrets,
+        }
+    }
+
+    pub fn encrypts_secrets(&self) -> bool {
+        self.cipher.is_enabled()
+    }
+
+    pub fn secret_fields(&self, _provider_type: &ProviderType) -> &'static [&'static str] {
+        self.registry.get(_provider_type).map(|plugin| plugin.secret_fields()).unwrap_or_default()
+    }
+
+    /// Validates a submitted channel configuration and returns it ready to store, with its secrets encrypted.
+    /// Secrets the submission leaves out, or sends back redacted, are kept from `existing`.
+    /// Without a secret key, secrets must be given as `${ENV_VAR}` references unless plaintext secrets are allowed.
+    pub async fn prepare_config(&self, _provider_type: &ProviderType, mut config: Value, existing: Option<&Channel>) -> Result<Value, AppError> {
+        let secret_fields = self.secret_fields(_provider_type);
+        if let Some(existing) = existing.filter(|channel| channel.provider_type == *_provider_type) {
+            keep_omitted_secrets(&mut config, &existing.configuration, secret_fields);
+        }
+
+        let plaintext = self.cipher.unseal(&config, secret_fields)?;
+        check_env_refs(&plaintext, secret_fields)?;
+        if !self.cipher.is_enabled() && !self.allow_plaintext_secrets && has_plaintext_secrets(&plaintext, secret_fields) {
+            return Err(AppError::BadRequest(format!(
+                "no secret key is configured to encrypt {}, reference environment variables starting with {} instead",
+                secret_fields.join(", "), ENV_REF_PREFIX
+            )));
         }
+        self.validate(_provider_type, &resolve_env_refs(&plaintext, secret_fields)?).await?;
+
+        self.cipher.seal(&plaintext, secret_fields)
+    }
+
+    /// Encrypts any secrets of a stored configuration that are still in plaintext.
+    pub fn seal_config(&self, _provider_type: &ProviderType, config: &Value) -> Result<Value, AppError> {
+        self.cipher.seal(config, self.secret_fields(_provider_type))
+    }
+
+    /// Decrypts a stored configuration, keeping `${ENV_VAR}` references unresolved.
+    pub fn unseal_config(&self, _provider_type: &ProviderType, config: &Value) -> Result<Value, AppError> {
+        self.cipher.unseal(config, self.secret_fields(_provider_type))
+    }
+
+    pub fn redact_config(&self, _provider_type: &ProviderType, config: &Value) -> Value {
+        redact(config, self.secret_fields(_provider_type))
+    }
+
+    pub fn redact_channel(&self, mut channel: Channel) -> Channel {
+        channel.configuration = self.redact_config(&channel.provider_type, &channel.configuration);
+        channel
+    }
+
+    /// The configuration handed to plugins: decrypted, with `${ENV_VAR}` references of secrets resolved.
+    fn plaintext_config(&self, channel: &Channel) -> Result<Value, AppError> {
+        resolve_env_refs(&self.unseal_config(&channel.provider_type, &channel.configuration)?, self.secret_fields(&channel.provider_type))
     }
 
     pub async fn validate(&self, _provider_type: &ProviderType, config: &Value) -> Result<(), AppError> {
@@ -32,39 +101,68 @@ impl NotificationDispatcher {
         }
     }
 
-    pub async fn dispatch(&self, app_name: &String, job_name: &String, run_id_opt: Option<String>, stage_name: &str, channel_ids_str: &str, message_opt: Option<String>, alert_type: AlertType) -> Result<(), AppError> {
+    /// Why alerts of the job are silenced right now, see [`get_silence_reason`].
+    pub async fn get_silence_reason(&self, job_config: &JobConfig) -> Result<Option<String>, AppError> {
+        let mut conn = self.db.get().await?;
+        get_silence_reason(&mut conn, job_config, &get_utc_now()).await
+    }
+
+    pub async fn dispatch(&self, alert: Alert, channel_ids_str: &str) -> Result<(), AppError> {
         let mut join_handles = vec![];
+        let (app_name, job_name) = (&alert.app_name, &alert.job_name);
 
         let mut conn = self.db.get().await?;
 
-        let channel_ids: Vec<String> = channel_ids_str.split(',')
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty())
-            .map(|s| s.to_string())
-            .collect();
+        // Alerts still go out when the metadata can't be looked up, just without it
+        let metadata = get_job_metadata(&mut conn, app_name, job_name).await.unwrap_or_else(|e| {
+            error!("failed to look up metadata of {}-{}: {}", app_name, job_name, e.message());
+            JobMetadata::default()
+        });
+
+        // Without the rules, alerts go to the channels they were sent for
+        let rules = get_enabled_routing_rules(&mut conn).await.unwrap_or_else(|e| {
+            error!("failed to look up routing rules for {}-{}: {}", app_name, job_name, e.message());
+            vec![]
+        });
+        let routed_alert = RoutedAlert {
+            app_name,
+            job_name,
+            stage_name: &alert.stage_name,
+            alert_type: &alert.alert_type,
+            severity: alert.severity,
+            metadata: &metadata,
+            at: get_utc_now(),
+        };
+        let routing = route_alert(&rules, &routed_alert, channel_ids_str);
+        if !routing.is_default {
+            info!("routing {} {:?} alert of {}-{} by rules {:?} to {:?}", alert.severity, alert.alert_type, app_name, job_name, routing.matched_rules, routing.channel_ids);
+        }
 
-        for channel_id in channel_ids {
+        for channel_id in routing.channel_ids {
             // 1. Simulate fetching channel config from DB based on ID
             if let Some(channel_cfg) = get_channel_by_name(&mut conn, &channel_id).await? {
                 // 2. Look up the plugin implementation in the registry based on type string
                 if let Some(plugin) = self.registry.get(&channel_cfg.provider_type) {
                     // Prepare data for async move
-                    let config_clone = channel_cfg.configuration.clone();
+                    let config_clone = match self.plaintext_config(&channel_cfg) {
+                        Ok(config) => config,
+                        Err(e) => {
+                            error!("Failed to read configuration of channel '{}': {}", channel_id, e.message());
+                            continue;
+                        }
+                    };
                     // Clone the Arc pointer to the plugin implementation
                     let plugin_ref = plugin.clone();
                     let channel_name = channel_cfg.name.clone();
-                    let job_run_opt_clone = run_id_opt.clone();
-                    let app_name_clone = app_name.clone();
-                    let job_name_clone = job_name.clone();
-                    let stage_name_clone = stage_name.to_string();
-                    let message_opt_clone = message_opt.clone();
-                    let alert_type_clone = alert_type.clone();
+                    let alert_clone = alert.clone();
+                    let metadata_clone = metadata.clone();
 
                     // 3. Spawn an async task for execution so channels don't block each other.
                     let handle = tokio::spawn(async move {
                         // info!("-> Sending via channel2: '{}'", channel_name);
-                        match plugin_ref.send(&app_name_clone, &job_name_clone, job_run_opt_clone, &stage_name_clone, message_opt_clone, &config_clone, alert_type_clone).await {
-                            Ok(_) => info!("Successfully sent via '{}'", channel_name),
+                        match plugin_ref.send(&alert_clone, &metadata_clone, &config_clone).await {
+                            Ok(report) if report.success => info!("Successfully sent via '{}'", channel_name),
+                            Ok(report) => error!("Failed to send via '{}': provider responded with {:?}: {}", channel_name, report.status_code, report.response.unwrap_or_default()),
                             Err(e) => error!("Failed to send via '{}': {}", channel_name, e),
                         }
                     });
@@ -89,5 +187,61 @@ impl NotificationDispatcher {
         Ok(())
     }
 
+    /// Sends a clearly labelled test alert through the channel and waits for the provider's answer.
+    pub async fn send_test(&self, channel: &Channel) -> Result<DeliveryReport, AppError> {
+        let plugin = self.registry.get(&channel.provider_type).ok_or_else(|| {
+            AppError::BadRequest(format!("No plugin registered for type '{}' found in channel config.", channel.provider_type))
+        })?;
+
+        let message = format!("Test alert for channel '{}' sent from watchdog to verify its configuration.", channel.name);
+        let config = self.plaintext_config(channel)?;
+        let alert = Alert {
+            app_name: TEST_APP_NAME.to_string(),
+            job_name: TEST_JOB_NAME.to_string(),
+            run_id: None,
+            stage_name: channel.name.clone(),
+            alert_type: AlertType::Test,
+            severity: Severity::Info,
+            message: Some(message),
+        };
+        plugin.send(&alert, &JobMetadata::default(), &config).await
+    }
+
+    pub async fn dispatch_resolve(&self, app_name: &str, job_name: &str, run_id: &str, channel_ids_str: &str) -> Result<(), AppError> {
+        let mut conn = self.db.get().await?;
+
+        // Alerts of the run may have been routed anywhere a rule sends alerts of the job
+        let metadata = get_job_metadata(&mut conn, app_name, job_name).await.unwrap_or_else(|e| {
+            error!("failed to look up metadata of {}-{}: {}", app_name, job_name, e.message());
+            JobMetadata::default()
+        });
+        let rules = get_enabled_routing_rules(&mut conn).await.unwrap_or_else(|e| {
+            error!("failed to look up routing rules for {}-{}: {}", app_name, job_name, e.message());
+            vec![]
+        });
+        let channel_ids = get_resolve_channel_ids(&rules, app_name, job_name, &metadata, channel_ids_str);
+
+        for channel_id in channel_ids.iter() {
+            let Some(channel_cfg) = get_channel_by_name(&mut conn, channel_id).await? else {
+                error!("Error: Channel ID '{}' not found in database.", channel_id);
+                continue;
+            };
+            if let Some(plugin) = self.registry.get(&channel_cfg.provider_type) {
+                let config = match self.plaintext_config(&channel_cfg) {
+                    Ok(config) => config,
+                    Err(e) => {
+                        error!("Failed to read configuration of channel '{}': {}", channel_id, e.message());
+                        continue;
+                    }
+                };
+                match plugin.resolve(app_name, job_name, run_id, &config).await {
+                    Ok(_) => info!("Successfully resolved via '{}'", channel_cfg.name),
+                    Err(e) => error!("Failed to resolve via '{}': {}", channel_cfg.name, e),
+                }
+            }
+        }
+        Ok(())
+    }
+
 }
 
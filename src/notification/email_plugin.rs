@@ -7,10 +7,10 @@ use serde_json::Value;
 use tracing::info;
 use validator::Validate;
 use crate::errors::AppError;
-use crate::models::{ProviderType};
+use crate::models::{JobMetadata, ProviderType};
 use crate::models::ProviderType::EmailSmtp;
-use crate::notification::core::{AlertType};
-use crate::notification::plugin_registry::NotificationPlugin;
+use crate::notification::core::{get_metadata_fields, get_severity_prefix, Alert, AlertType};
+use crate::notification::plugin_registry::{DeliveryReport, NotificationPlugin};
 
 pub struct EmailPlugin;
 
@@ -35,20 +35,22 @@ impl NotificationPlugin for EmailPlugin {
         EmailSmtp
     }
 
+    fn secret_fields(&self) -> &'static [&'static str] {
+        &["password"]
+    }
+
     fn validate_config(&self, config: &Value) -> Result<(), AppError> {
         let _config: Config = serde_json::from_value(config.clone()).map_err(|e| {
             AppError::BadRequest(format!("invalid config provided {}", e))
         })?;
         _config.validate()?;
 
-        print!("{:?}", config);
-
         Ok(())
     }
 
 
-    async fn send(&self, app_name: &String, job_name: &String, run_id_opt: Option<String>, stage_name: &String, message_opt: Option<String>, config: &Value, alert_type: AlertType) -> Result<(), AppError> {
-        info!("sending email notification: {:?} with config: {:?}", alert_type, config);
+    async fn send(&self, alert: &Alert, metadata: &JobMetadata, config: &Value) -> Result<DeliveryReport, AppError> {
+        info!("sending email notification: {:?}", alert.alert_type);
 
         let _config: Config = serde_json::from_value(config.clone()).map_err(|e| {
             AppError::BadRequest(format!("invalid config provided {}", e))
@@ -57,7 +59,9 @@ impl NotificationPlugin for EmailPlugin {
         let mailer = build_transport(&_config)
             .map_err(|e| AppError::InternalError(format!("unable to connect to email server {}", e)))?;
 
-        let (subject, body) = render_message(alert_type, app_name, job_name, run_id_opt,stage_name, message_opt);
+        let severity_prefix = get_severity_prefix(&alert.alert_type, alert.severity);
+        let (subject, body) = render_message(alert.alert_type.clone(), &alert.app_name, &alert.job_name, alert.run_id.clone(), &alert.stage_name, alert.message.clone());
+        let (subject, body) = (severity_prefix + &subject, body + &render_metadata(metadata));
 
         info!("sending subject: {}\nbody: {}", subject, body);
 
@@ -68,12 +72,15 @@ impl NotificationPlugin for EmailPlugin {
             .body(body)
             .unwrap();
 
-        match mailer.send(&email) {
-            Ok(_) => info!("Email sent successfully!"),
-            Err(e) => panic!("Could not send email: {:?}", e),
-        }
+        let response = mailer.send(&email)
+            .map_err(|e| AppError::InternalError(format!("Could not send email: {}", e)))?;
+        info!("Email sent successfully!");
 
-        Ok(())
+        Ok(DeliveryReport {
+            success: response.is_positive(),
+            status_code: response.code().to_string().parse().ok(),
+            response: Some(response.message().collect::<Vec<&str>>().join("\n")),
+        })
     }
 }
 
@@ -101,7 +108,7 @@ fn build_transport(_config: &Config) -> Result<SmtpTransport, lettre::transport:
     Ok(builder.build())
 }
 
-fn render_message(alert_type: AlertType, app_name: &String, job_name: &String, run_id_opt: Option<String>, stage: &str, message_opt: Option<String>) -> (String, String) {
+fn render_message(alert_type: AlertType, app_name: &str, job_name: &str, run_id_opt: Option<String>, stage: &str, message_opt: Option<String>) -> (String, String) {
     match alert_type {
         AlertType::Error => (
             "[watchdog]: [{app_name}] [{job_name}] [{stage}]: Runtime Error Occurred"
@@ -117,11 +124,12 @@ fn render_message(alert_type: AlertType, app_name: &String, job_name: &String, r
         ),
         AlertType::Timeout => (
             "[{app_name}]: [{job_name}] Dag Timeout Alert from Watchdog".replace("{app_name}", app_name).replace("{job_name}", job_name),
-            "Airflow Stage Timeout Alert\nApplication: {app_name}\nJob Name: {job_name} \nStage Name: {stage}\nRun Id: {run_id}"
+            "Airflow Stage Timeout Alert\nApplication: {app_name}\nJob Name: {job_name} \nStage Name: {stage}\nRun Id: {run_id}{message}"
                 .replace("{app_name}", app_name)
                 .replace("{job_name}", job_name)
                 .replace("{stage}", stage)
                 .replace("{run_id}", &run_id_opt.unwrap_or("NA".to_string()))
+                .replace("{message}", &message_opt.map(|m| format!("\nMessage: {}", m)).unwrap_or_default())
         ),
         AlertType::Failed => (
             "[{app_name}]: [{job_name}] Job Failed Alert from Watchdog".replace("{app_name}", app_name).replace("{job_name}", job_name),
@@ -132,5 +140,16 @@ fn render_message(alert_type: AlertType, app_name: &String, job_name: &String, r
                 .replace("{run_id}", &run_id_opt.unwrap_or("NA".to_string()))
                 .replace("{message}", &message_opt.unwrap_or("".to_string()))
         ),
+        AlertType::Test => (
+            "[watchdog]: Test Alert, no action is needed".to_string(),
+            "Watchdog Test Alert\nThis is a test notification, no action is needed.\nMessage: {message}"
+                .replace("{message}", &message_opt.unwrap_or("".to_string()))
+        ),
     }
 }
+
+fn render_metadata(metadata: &JobMetadata) -> String {
+    get_metadata_fields(metadata).into_iter()
+        .map(|(label, value)| format!("\n{}: {}", label, value))
+        .collect()
+}
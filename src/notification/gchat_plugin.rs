@@ -6,10 +6,10 @@ use serde_json::{json, Value};
 use tracing::info;
 use validator::Validate;
 use crate::errors::AppError;
-use crate::models::{ProviderType};
+use crate::models::{JobMetadata, ProviderType};
 use crate::models::ProviderType::GchatWebhook;
-use crate::notification::core::{AlertType};
-use crate::notification::plugin_registry::NotificationPlugin;
+use crate::notification::core::{get_metadata_fields, get_severity_prefix, Alert, AlertType};
+use crate::notification::plugin_registry::{DeliveryReport, NotificationPlugin};
 
 pub struct GchatPlugin;
 
@@ -25,22 +25,26 @@ impl NotificationPlugin for GchatPlugin {
         GchatWebhook
     }
 
+    fn secret_fields(&self) -> &'static [&'static str] {
+        &["webhook_url"]
+    }
+
     fn validate_config(&self, config: &Value) -> Result<(), AppError> {
         let _config: Config = serde_json::from_value(config.clone()).map_err(|e| {
             AppError::BadRequest(format!("invalid config provided {}", e))
         })?;
         _config.validate()?;
 
-        print!("{:?}", config);
-
         Ok(())
     }
 
-    async fn send(&self, app_name: &String, job_name: &String, run_id_opt: Option<String>, stage_name: &String, message_opt: Option<String>, config: &Value, alert_type: AlertType) -> Result<(), AppError> {
-        info!("sending gchat notification: {:?} with config: {:?}", alert_type, config);
+    async fn send(&self, alert: &Alert, metadata: &JobMetadata, config: &Value) -> Result<DeliveryReport, AppError> {
+        info!("sending gchat notification: {:?}", alert.alert_type);
 
         let webhook_url = config["webhook_url"].as_str().unwrap(); // Safe due to validation
-        let message = render_message(alert_type, app_name, job_name, run_id_opt, stage_name, message_opt);
+        let message = get_severity_prefix(&alert.alert_type, alert.severity)
+            + &render_message(alert.alert_type.clone(), &alert.app_name, &alert.job_name, alert.run_id.clone(), &alert.stage_name, alert.message.clone())
+            + &render_metadata(metadata);
 
         let client = Client::new();
 
@@ -57,11 +61,11 @@ impl NotificationPlugin for GchatPlugin {
 
         info!("notification status: {:?}", res);
 
-        Ok(())
+        Ok(DeliveryReport::from_http_response(res).await)
     }
 }
 
-fn render_message(alert_type: AlertType, app_name: &String, job_name: &String, run_id_opt: Option<String>, stage: &str, message_opt: Option<String>) -> String {
+fn render_message(alert_type: AlertType, app_name: &str, job_name: &str, run_id_opt: Option<String>, stage: &str, message_opt: Option<String>) -> String {
     match alert_type {
         AlertType::Error =>
             "🕵️ *Watchdog Error* 🕵️\n*Application*: {app_name}\n*Job Name*: {job_name}\n*Stage Name*: {stage}\n*Run Id*: {run_id}\n*Message*: {message}"
@@ -72,11 +76,12 @@ fn render_message(alert_type: AlertType, app_name: &String, job_name: &String, r
                 .replace("{message}", &message_opt.unwrap_or("".to_string()))
         ,
         AlertType::Timeout =>
-            "⏳ Job Timeout ⏳\n*Application*: {app_name}\n*Job Name*: {job_name}\n*Stage Name*: {stage}\n*Run Id*: {run_id}"
+            "⏳ Job Timeout ⏳\n*Application*: {app_name}\n*Job Name*: {job_name}\n*Stage Name*: {stage}\n*Run Id*: {run_id}{message}"
                 .replace("{app_name}", app_name)
                 .replace("{job_name}", job_name)
                 .replace("{stage}", stage)
                 .replace("{run_id}", &run_id_opt.unwrap_or("NA".to_string()))
+                .replace("{message}", &message_opt.map(|m| format!("\n*Message*: {}", m)).unwrap_or_default())
         ,
         AlertType::Failed =>
             "🚨 Job Failed 🚨\n*Application*: {app_name}\n*Job Name*: {job_name}\n*Stage Name*: {stage}\n*Run Id*: {run_id}\n*Message*: {message}"
@@ -86,7 +91,15 @@ fn render_message(alert_type: AlertType, app_name: &String, job_name: &String, r
                 .replace("{run_id}", &run_id_opt.unwrap_or("NA".to_string()))
                 .replace("{message}", &message_opt.unwrap_or("".to_string()))
         ,
+        AlertType::Test =>
+            "🧪 *Watchdog Test Alert* 🧪\nThis is a test notification, no action is needed.\n*Message*: {message}"
+                .replace("{message}", &message_opt.unwrap_or("".to_string()))
+        ,
     }
 }
 
-
+fn render_metadata(metadata: &JobMetadata) -> String {
+    get_metadata_fields(metadata).into_iter()
+        .map(|(label, value)| format!("\n*{}*: {}", label, value))
+        .collect()
+}
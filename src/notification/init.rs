@@ -1,17 +1,33 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use crate::config::Config;
 use crate::db::connection::PgPool;
-use crate::models::ProviderType::{EmailSmtp, GchatWebhook};
+use crate::models::ProviderType::{EmailSmtp, GchatWebhook, Pagerduty, TeamsWebhook, Webhook};
 use crate::notification::dispatcher::NotificationDispatcher;
 use crate::notification::email_plugin::EmailPlugin;
 use crate::notification::gchat_plugin::GchatPlugin;
+use crate::notification::pagerduty_plugin::PagerdutyPlugin;
 use crate::notification::plugin_registry::PluginRegistry;
+use crate::notification::teams_plugin::TeamsPlugin;
+use crate::notification::webhook_plugin::WebhookPlugin;
+use crate::secrets::SecretCipher;
+use tracing::warn;
 
-pub async fn init_notification(db: PgPool) -> NotificationDispatcher {
+pub async fn init_notification(db: PgPool, config: &Config) -> NotificationDispatcher {
 
     let mut registry: PluginRegistry = HashMap::new();
     registry.insert(GchatWebhook, Arc::new(GchatPlugin));
     registry.insert(EmailSmtp, Arc::new(EmailPlugin));
+    registry.insert(Webhook, Arc::new(WebhookPlugin));
+    registry.insert(TeamsWebhook, Arc::new(TeamsPlugin));
+    registry.insert(Pagerduty, Arc::new(PagerdutyPlugin));
 
-    NotificationDispatcher::new(db, registry)
+    let cipher = SecretCipher::new(config.secret_key.as_deref()).expect("Invalid WATCHDOG_SECRET_KEY");
+    if !cipher.is_enabled() && config.allow_plaintext_secrets {
+        warn!("WATCHDOG_SECRET_KEY is not set, channel secrets are stored unencrypted");
+    } else if !cipher.is_enabled() {
+        warn!("WATCHDOG_SECRET_KEY is not set, channel secrets can only reference environment variables");
+    }
+
+    NotificationDispatcher::new(db, registry, cipher, config.allow_plaintext_secrets)
 }
@@ -3,4 +3,9 @@ pub mod email_plugin;
 pub mod core;
 pub mod init;
 pub mod plugin_registry;
-pub mod gchat_plugin;
\ No newline at end of file
+pub mod gchat_plugin;
+pub mod webhook_plugin;
+pub mod teams_plugin;
+pub mod pagerduty_plugin;
+#[cfg(test)]
+mod stub_server;
\ No newline at end of file
@@ -0,0 +1,260 @@
+use std::time::Duration;
+use crate::validations::validate_url;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::info;
+use validator::Validate;
+use crate::errors::AppError;
+use crate::models::{JobMetadata, ProviderType};
+use crate::models::ProviderType::Pagerduty;
+use crate::notification::core::{Alert, AlertType};
+use crate::notification::plugin_registry::{DeliveryReport, NotificationPlugin};
+
+const DEFAULT_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+const DEFAULT_TIMEOUT_SECONDS: u64 = 10;
+
+pub struct PagerdutyPlugin;
+
+#[derive(Debug, Validate, Deserialize)]
+struct Config {
+    #[validate(length(min = 1, message = "routing_key must not be empty"))]
+    pub routing_key: String,
+    /// Overrides the Events API v2 endpoint, e.g. to point at a local mock.
+    #[validate(custom(function = "validate_url"))]
+    pub events_url: Option<String>,
+}
+
+#[async_trait]
+impl NotificationPlugin for PagerdutyPlugin {
+    fn provider_type(&self) -> ProviderType {
+        Pagerduty
+    }
+
+    fn secret_fields(&self) -> &'static [&'static str] {
+        &["routing_key"]
+    }
+
+    fn validate_config(&self, config: &Value) -> Result<(), AppError> {
+        let _config = parse_config(config)?;
+        _config.validate()?;
+
+        Ok(())
+    }
+
+    async fn send(&self, alert: &Alert, metadata: &JobMetadata, config: &Value) -> Result<DeliveryReport, AppError> {
+        info!("sending pagerduty trigger: {:?}", alert.alert_type);
+
+        let _config = parse_config(config)?;
+        let (app_name, job_name, stage_name) = (&alert.app_name, &alert.job_name, &alert.stage_name);
+
+        let summary = match alert.alert_type {
+            AlertType::Error => format!("Watchdog error for {}/{} at stage {}", app_name, job_name, stage_name),
+            AlertType::Timeout => format!("Job {}/{} timed out at stage {}", app_name, job_name, stage_name),
+            AlertType::Failed => format!("Job {}/{} failed at stage {}", app_name, job_name, stage_name),
+            AlertType::Test => format!("Watchdog test alert for channel {}, no action is needed", stage_name),
+        };
+        let severity = match alert.alert_type {
+            AlertType::Error => "error".to_string(),
+            AlertType::Timeout | AlertType::Failed => alert.severity.to_string(),
+            AlertType::Test => "info".to_string(),
+        };
+        let dedup_key = dedup_key(app_name, job_name, alert.run_id.as_deref());
+        let is_test = matches!(alert.alert_type, AlertType::Test);
+
+        // PagerDuty shows links next to the incident, so the runbook is one click away
+        let links: Vec<Value> = metadata.runbook_url.iter()
+            .map(|runbook_url| json!({ "href": runbook_url, "text": "Runbook" }))
+            .collect();
+
+        let event = json!({
+            "routing_key": _config.routing_key,
+            "event_action": "trigger",
+            "dedup_key": dedup_key,
+            "links": links,
+            "payload": {
+                "summary": summary,
+                "source": "watchdog",
+                "severity": severity,
+                "component": app_name,
+                "group": job_name,
+                "class": format!("{:?}", alert.alert_type),
+                "custom_details": {
+                    "app_name": app_name,
+                    "job_name": job_name,
+                    "stage_name": stage_name,
+                    "run_id": alert.run_id,
+                    "message": alert.message,
+                    "owner_team": metadata.owner_team,
+                    "contact": metadata.contact,
+                    "tags": metadata.tags,
+                    "runbook_url": metadata.runbook_url,
+                }
+            }
+        });
+
+        let report = post_event(&_config, &event).await?;
+
+        // A test must not leave an open incident behind
+        if is_test && report.success {
+            post_event(&_config, &json!({
+                "routing_key": _config.routing_key,
+                "event_action": "resolve",
+                "dedup_key": dedup_key,
+            })).await?;
+        }
+
+        Ok(report)
+    }
+
+    async fn resolve(&self, app_name: &str, job_name: &str, run_id: &str, config: &Value) -> Result<(), AppError> {
+        info!("sending pagerduty resolve for run: {}", run_id);
+
+        let _config = parse_config(config)?;
+
+        let event = json!({
+            "routing_key": _config.routing_key,
+            "event_action": "resolve",
+            "dedup_key": dedup_key(app_name, job_name, Some(run_id)),
+        });
+
+        let report = post_event(&_config, &event).await?;
+        if !report.success {
+            return Err(AppError::InternalError(format!("pagerduty rejected resolve with {:?}: {}", report.status_code, report.response.unwrap_or_default())));
+        }
+        Ok(())
+    }
+}
+
+fn parse_config(config: &Value) -> Result<Config, AppError> {
+    serde_json::from_value(config.clone()).map_err(|e| {
+        AppError::BadRequest(format!("invalid config provided {}", e))
+    })
+}
+
+/// Alerts for the same run share one incident, so a later resolve clears all of them.
+fn dedup_key(app_name: &str, job_name: &str, run_id_opt: Option<&str>) -> String {
+    match run_id_opt {
+        Some(run_id) => format!("watchdog/{}/{}/{}", app_name, job_name, run_id),
+        None => format!("watchdog/{}/{}", app_name, job_name),
+    }
+}
+
+async fn post_event(config: &Config, event: &Value) -> Result<DeliveryReport, AppError> {
+    let events_url = config.events_url.as_deref().unwrap_or(DEFAULT_EVENTS_URL);
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECONDS))
+        .build()
+        .map_err(|e| AppError::InternalError(format!("Failed to build http client: {}", e)))?;
+
+    let res = client.post(events_url)
+        .json(event)
+        .send()
+        .await
+        .map_err(|e| AppError::InternalError(format!("Failed to send pagerduty event: {}", e)))?;
+
+    let report = DeliveryReport::from_http_response(res).await;
+    info!("notification status: {:?}", report.status_code);
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Severity;
+    use crate::notification::stub_server::start_stub_server;
+
+    fn alert(alert_type: AlertType, stage_name: &str) -> Alert {
+        Alert {
+            app_name: "app1".to_string(),
+            job_name: "job1".to_string(),
+            run_id: Some("run1".to_string()),
+            stage_name: stage_name.to_string(),
+            alert_type,
+            severity: Severity::Critical,
+            message: Some("exit code 1".to_string()),
+        }
+    }
+
+    fn config(base_url: &str) -> Value {
+        json!({ "routing_key": "key1", "events_url": format!("{}/v2/enqueue", base_url) })
+    }
+
+    #[tokio::test]
+    async fn sends_trigger_event() {
+        let (base_url, requests) = start_stub_server().await;
+        let metadata = JobMetadata { runbook_url: Some("https://wiki/runbook".to_string()), ..JobMetadata::default() };
+
+        let report = PagerdutyPlugin.send(&alert(AlertType::Failed, "load"), &metadata, &config(&base_url)).await.unwrap();
+        assert!(report.success);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        let (path, event) = (&requests[0].path, &requests[0].body);
+        assert_eq!(path, "/v2/enqueue");
+        assert_eq!(event["routing_key"], "key1");
+        assert_eq!(event["event_action"], "trigger");
+        assert_eq!(event["dedup_key"], "watchdog/app1/job1/run1");
+        assert_eq!(event["links"], json!([{ "href": "https://wiki/runbook", "text": "Runbook" }]));
+        assert_eq!(event["payload"]["summary"], "Job app1/job1 failed at stage load");
+        assert_eq!(event["payload"]["source"], "watchdog");
+        assert_eq!(event["payload"]["severity"], "critical");
+        assert_eq!(event["payload"]["component"], "app1");
+        assert_eq!(event["payload"]["group"], "job1");
+        assert_eq!(event["payload"]["class"], "Failed");
+        assert_eq!(event["payload"]["custom_details"]["stage_name"], "load");
+        assert_eq!(event["payload"]["custom_details"]["message"], "exit code 1");
+    }
+
+    #[tokio::test]
+    async fn alerts_of_a_run_share_the_dedup_key() {
+        let (base_url, requests) = start_stub_server().await;
+
+        PagerdutyPlugin.send(&alert(AlertType::Timeout, "extract"), &JobMetadata::default(), &config(&base_url)).await.unwrap();
+        PagerdutyPlugin.send(&alert(AlertType::Failed, "load"), &JobMetadata::default(), &config(&base_url)).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        let dedup_keys: Vec<&Value> = requests.iter().map(|request| &request.body["dedup_key"]).collect();
+        assert_eq!(dedup_keys, vec!["watchdog/app1/job1/run1", "watchdog/app1/job1/run1"]);
+        assert_eq!(dedup_key("app1", "job1", None), "watchdog/app1/job1");
+    }
+
+    #[tokio::test]
+    async fn resolves_the_incident_of_the_run() {
+        let (base_url, requests) = start_stub_server().await;
+
+        PagerdutyPlugin.resolve("app1", "job1", "run1", &config(&base_url)).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].body, json!({
+            "routing_key": "key1",
+            "event_action": "resolve",
+            "dedup_key": "watchdog/app1/job1/run1",
+        }));
+    }
+
+    #[tokio::test]
+    async fn resolves_test_alerts_right_away() {
+        let (base_url, requests) = start_stub_server().await;
+        let mut test_alert = alert(AlertType::Test, "pagerduty1");
+        test_alert.run_id = None;
+
+        PagerdutyPlugin.send(&test_alert, &JobMetadata::default(), &config(&base_url)).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        let actions: Vec<&Value> = requests.iter().map(|request| &request.body["event_action"]).collect();
+        assert_eq!(actions, vec!["trigger", "resolve"]);
+        assert_eq!(requests[1].body["dedup_key"], "watchdog/app1/job1");
+    }
+
+    #[test]
+    fn validates_config() {
+        assert!(PagerdutyPlugin.validate_config(&json!({ "routing_key": "key1" })).is_ok());
+        assert!(PagerdutyPlugin.validate_config(&json!({ "routing_key": "" })).is_err());
+        assert!(PagerdutyPlugin.validate_config(&json!({ "routing_key": "key1", "events_url": "localhost" })).is_err());
+    }
+}
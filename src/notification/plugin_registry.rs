@@ -1,22 +1,55 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use async_trait::async_trait;
+use serde::Serialize;
 use serde_json::Value;
 use crate::errors::AppError;
-use crate::models::{ProviderType};
-use crate::notification::core::{AlertType};
+use crate::models::{JobMetadata, ProviderType};
+use crate::notification::core::Alert;
+
+/// What the provider answered when a notification was handed over.
+/// A provider that answered but rejected the alert (e.g. a non-2xx status) reports `success: false`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DeliveryReport {
+    pub success: bool,
+    /// The HTTP status for webhook based providers, or the SMTP reply code for email.
+    pub status_code: Option<u16>,
+    pub response: Option<String>,
+}
+
+impl DeliveryReport {
+    pub async fn from_http_response(res: reqwest::Response) -> Self {
+        let status = res.status();
+        Self {
+            success: status.is_success(),
+            status_code: Some(status.as_u16()),
+            response: Some(res.text().await.unwrap_or_default()),
+        }
+    }
+}
 
 #[async_trait]
 pub trait NotificationPlugin: Send + Sync {
     /// Returns the unique string identifier for this plugin type (e.g., "slack", "email").
     fn provider_type(&self) -> ProviderType;
 
+    /// Top level configuration fields holding credentials. They are encrypted at rest and redacted in responses.
+    fn secret_fields(&self) -> &'static [&'static str] {
+        &[]
+    }
+
     /// Validates arbitrary JSON configuration before saving it to the DB.
     fn validate_config(&self, config: &Value) -> Result<(), AppError>;
 
     /// The core logic to execute the notification.
-    /// It takes the generic alert and the provider-specific JSON config.
-    async fn send(&self, app_name: &String, job_name: &String, run_id_opt: Option<String>, stage_name: &String, message_opt: Option<String>, config: &Value, alert_type: AlertType) -> Result<(), AppError>;
+    /// It takes the generic alert, the metadata of the job and the provider-specific JSON config.
+    async fn send(&self, alert: &Alert, metadata: &JobMetadata, config: &Value) -> Result<DeliveryReport, AppError>;
+
+    /// Clears the alerts previously sent for a run once it completes.
+    /// Only providers that track incidents need this; the default does nothing.
+    async fn resolve(&self, _app_name: &str, _job_name: &str, _run_id: &str, _config: &Value) -> Result<(), AppError> {
+        Ok(())
+    }
 }
 
 pub(crate) type PluginRegistry = HashMap<ProviderType, Arc<dyn NotificationPlugin>>;
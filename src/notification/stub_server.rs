@@ -0,0 +1,41 @@
+//! A local HTTP server standing in for the providers in the plugin tests.
+
+use std::sync::{Arc, Mutex};
+use axum::extract::State;
+use axum::http::{HeaderMap, Uri};
+use axum::Json;
+use axum::Router;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+
+/// The requests received by the stub server, in the order they came in.
+pub type Requests = Arc<Mutex<Vec<StubRequest>>>;
+
+#[derive(Debug, Clone)]
+pub struct StubRequest {
+    pub path: String,
+    pub headers: HeaderMap,
+    /// The body as sent, e.g. to check a signature over it.
+    pub raw_body: String,
+    pub body: Value,
+}
+
+/// Starts a server accepting JSON posts on any path, returning its base url and the requests it received.
+pub async fn start_stub_server() -> (String, Requests) {
+    let requests: Requests = Arc::new(Mutex::new(vec![]));
+    let app = Router::new()
+        .fallback(record_request)
+        .with_state(requests.clone());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    (format!("http://{}", addr), requests)
+}
+
+async fn record_request(State(requests): State<Requests>, uri: Uri, headers: HeaderMap, raw_body: String) -> Json<Value> {
+    let body = serde_json::from_str(&raw_body).unwrap_or(Value::Null);
+    requests.lock().unwrap().push(StubRequest { path: uri.path().to_string(), headers, raw_body, body });
+    Json(json!({ "status": "success" }))
+}
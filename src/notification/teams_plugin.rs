@@ -0,0 +1,219 @@
+use std::time::Duration;
+use crate::validations::validate_url;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::info;
+use validator::Validate;
+use crate::errors::AppError;
+use crate::models::{JobMetadata, ProviderType, Severity};
+use crate::models::ProviderType::TeamsWebhook;
+use crate::notification::core::{get_metadata_fields, get_severity_prefix, Alert, AlertType};
+use crate::notification::plugin_registry::{DeliveryReport, NotificationPlugin};
+
+pub struct TeamsPlugin;
+
+const DEFAULT_TIMEOUT_SECONDS: u64 = 10;
+
+#[derive(Debug, Validate, Deserialize)]
+struct Config {
+    #[validate(custom(function = "validate_url"))]
+    pub webhook_url: String,
+}
+
+#[async_trait]
+impl NotificationPlugin for TeamsPlugin {
+    fn provider_type(&self) -> ProviderType {
+        TeamsWebhook
+    }
+
+    fn secret_fields(&self) -> &'static [&'static str] {
+        &["webhook_url"]
+    }
+
+    fn validate_config(&self, config: &Value) -> Result<(), AppError> {
+        let _config: Config = serde_json::from_value(config.clone()).map_err(|e| {
+            AppError::BadRequest(format!("invalid config provided {}", e))
+        })?;
+        _config.validate()?;
+
+        Ok(())
+    }
+
+    async fn send(&self, alert: &Alert, metadata: &JobMetadata, config: &Value) -> Result<DeliveryReport, AppError> {
+        info!("sending teams notification: {:?}", alert.alert_type);
+
+        let _config: Config = serde_json::from_value(config.clone()).map_err(|e| {
+            AppError::BadRequest(format!("invalid config provided {}", e))
+        })?;
+
+        let card = render_card(alert, metadata);
+
+        // Incoming webhooks and workflow triggers both accept a message wrapping the adaptive card
+        let payload = json!({
+            "type": "message",
+            "attachments": [{
+                "contentType": "application/vnd.microsoft.card.adaptive",
+                "contentUrl": null,
+                "content": card
+            }]
+        });
+        info!("sending payload: {}", payload);
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECONDS))
+            .build()
+            .map_err(|e| AppError::InternalError(format!("Failed to build http client: {}", e)))?;
+
+        let res = client.post(&_config.webhook_url)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| AppError::InternalError(format!("Failed to send teams request: {}", e)))?;
+
+        let report = DeliveryReport::from_http_response(res).await;
+        info!("notification status: {:?}", report.status_code);
+
+        Ok(report)
+    }
+}
+
+fn render_card(alert: &Alert, metadata: &JobMetadata) -> Value {
+    let severity_color = match alert.severity {
+        Severity::Critical => "Attention",
+        Severity::Warning => "Warning",
+        Severity::Info => "Accent",
+    };
+    let (title, color) = match alert.alert_type {
+        AlertType::Error => ("🕵️ Watchdog Error", "Warning"),
+        AlertType::Timeout => ("⏳ Job Timeout", severity_color),
+        AlertType::Failed => ("🚨 Job Failed", severity_color),
+        AlertType::Test => ("🧪 Watchdog Test Alert, no action is needed", "Good"),
+    };
+    let title = get_severity_prefix(&alert.alert_type, alert.severity) + title;
+
+    let mut facts = vec![
+        json!({ "title": "Application", "value": alert.app_name }),
+        json!({ "title": "Job Name", "value": alert.job_name }),
+        json!({ "title": "Stage Name", "value": alert.stage_name }),
+        json!({ "title": "Run Id", "value": alert.run_id.as_deref().unwrap_or("NA") }),
+        json!({ "title": "Severity", "value": alert.severity.to_string() }),
+    ];
+    if let Some(message) = alert.message.as_ref().filter(|m| !m.is_empty()) {
+        facts.push(json!({ "title": "Message", "value": message }));
+    }
+    for (label, value) in get_metadata_fields(metadata) {
+        facts.push(json!({ "title": label, "value": value }));
+    }
+
+    json!({
+        "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+        "type": "AdaptiveCard",
+        "version": "1.4",
+        "body": [
+            {
+                "type": "TextBlock",
+                "text": title,
+                "weight": "Bolder",
+                "size": "Medium",
+                "color": color,
+                "wrap": true
+            },
+            {
+                "type": "FactSet",
+                "facts": facts
+            }
+        ]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notification::stub_server::start_stub_server;
+
+    fn alert(alert_type: AlertType, severity: Severity, message: Option<&str>) -> Alert {
+        Alert {
+            app_name: "app1".to_string(),
+            job_name: "job1".to_string(),
+            run_id: Some("run1".to_string()),
+            stage_name: "load".to_string(),
+            alert_type,
+            severity,
+            message: message.map(str::to_string),
+        }
+    }
+
+    /// Sends the alert to a stub webhook and returns the adaptive card it received.
+    async fn send_card(alert: &Alert) -> Value {
+        let (base_url, requests) = start_stub_server().await;
+        let config = json!({ "webhook_url": format!("{}/teams", base_url) });
+
+        let report = TeamsPlugin.send(alert, &JobMetadata::default(), &config).await.unwrap();
+        assert!(report.success);
+        assert_eq!(report.status_code, Some(200));
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        let (path, payload) = (&requests[0].path, &requests[0].body);
+        assert_eq!(path, "/teams");
+        assert_eq!(payload["type"], "message");
+        let attachment = &payload["attachments"][0];
+        assert_eq!(attachment["contentType"], "application/vnd.microsoft.card.adaptive");
+        assert_eq!(attachment["content"]["type"], "AdaptiveCard");
+        attachment["content"].clone()
+    }
+
+    fn facts(card: &Value) -> Vec<(String, String)> {
+        card["body"][1]["facts"].as_array().unwrap().iter()
+            .map(|fact| (fact["title"].as_str().unwrap().to_string(), fact["value"].as_str().unwrap().to_string()))
+            .collect()
+    }
+
+    fn fact(title: &str, value: &str) -> (String, String) {
+        (title.to_string(), value.to_string())
+    }
+
+    #[tokio::test]
+    async fn sends_error_card() {
+        let card = send_card(&alert(AlertType::Error, Severity::Warning, Some("run not found"))).await;
+
+        assert_eq!(card["body"][0]["text"], "[WARNING] 🕵️ Watchdog Error");
+        assert_eq!(card["body"][0]["color"], "Warning");
+        assert_eq!(facts(&card), vec![
+            fact("Application", "app1"),
+            fact("Job Name", "job1"),
+            fact("Stage Name", "load"),
+            fact("Run Id", "run1"),
+            fact("Severity", "warning"),
+            fact("Message", "run not found"),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn sends_timeout_card() {
+        let card = send_card(&alert(AlertType::Timeout, Severity::Critical, None)).await;
+
+        assert_eq!(card["body"][0]["text"], "[CRITICAL] ⏳ Job Timeout");
+        assert_eq!(card["body"][0]["color"], "Attention");
+        assert!(!facts(&card).iter().any(|(title, _)| title == "Message"));
+    }
+
+    #[tokio::test]
+    async fn sends_failed_card() {
+        let card = send_card(&alert(AlertType::Failed, Severity::Info, Some("exit code 1"))).await;
+
+        assert_eq!(card["body"][0]["text"], "[INFO] 🚨 Job Failed");
+        assert_eq!(card["body"][0]["color"], "Accent");
+        assert!(facts(&card).contains(&fact("Message", "exit code 1")));
+    }
+
+    #[test]
+    fn validates_config() {
+        assert!(TeamsPlugin.validate_config(&json!({ "webhook_url": "https://example.webhook.office.com/x" })).is_ok());
+        assert!(TeamsPlugin.validate_config(&json!({ "webhook_url": "example.webhook.office.com/x" })).is_err());
+        assert!(TeamsPlugin.validate_config(&json!({ "webhook_url": 5 })).is_err());
+        assert!(TeamsPlugin.validate_config(&json!({})).is_err());
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use crate::validations::validate_url;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::header::{HeaderName, HeaderValue, CONTENT_TYPE};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use tracing::info;
+use validator::Validate;
+use crate::errors::AppError;
+use crate::models::{JobMetadata, ProviderType, Severity};
+use crate::models::ProviderType::Webhook;
+use crate::notification::core::{Alert, AlertType};
+use crate::notification::plugin_registry::{DeliveryReport, NotificationPlugin};
+use crate::time_utils::get_utc_now;
+
+pub const SIGNATURE_HEADER: &str = "x-watchdog-signature";
+pub const TIMESTAMP_HEADER: &str = "x-watchdog-timestamp";
+const DEFAULT_TIMEOUT_SECONDS: u64 = 10;
+
+pub struct WebhookPlugin;
+
+#[derive(Debug, Validate, Deserialize)]
+struct Config {
+    #[validate(custom(function = "validate_url"))]
+    pub url: String,
+    /// Extra headers sent with every request, e.g. an auth token expected by the receiver.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// When set, every request is signed with HMAC-SHA256 using this secret.
+    #[validate(length(min = 16, message = "secret must be at least 16 characters long"))]
+    pub secret: Option<String>,
+    #[validate(range(min = 1, max = 60, message = "timeout_seconds must be between 1 and 60"))]
+    pub timeout_seconds: Option<u64>,
+}
+
+/// The JSON body POSTed to the webhook url.
+///
+/// When the channel has a secret, the request carries `x-watchdog-timestamp` (unix seconds) and
+/// `x-watchdog-signature: sha256=<hex>`, the HMAC-SHA256 of `{timestamp}.{body}` keyed by the secret.
+#[derive(Debug, Serialize)]
+pub struct WebhookEnvelope {
+    pub alert_type: AlertType,
+    pub severity: Severity,
+    pub app_name: String,
+    pub job_name: String,
+    pub stage_name: String,
+    pub run_id: Option<String>,
+    pub message: Option<String>,
+    pub metadata: JobMetadata,
+    pub sent_at: DateTime<Utc>,
+}
+
+#[async_trait]
+impl NotificationPlugin for WebhookPlugin {
+    fn provider_type(&self) -> ProviderType {
+        Webhook
+    }
+
+    fn secret_fields(&self) -> &'static [&'static str] {
+        &["url", "headers", "secret"]
+    }
+
+    fn validate_config(&self, config: &Value) -> Result<(), AppError> {
+        let _config = parse_config(config)?;
+        _config.validate()?;
+
+        for (name, value) in _config.headers.iter() {
+            HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| AppError::BadRequest(format!("invalid header name '{}'", name)))?;
+            HeaderValue::from_str(value)
+                .map_err(|_| AppError::BadRequest(format!("invalid value for header '{}'", name)))?;
+        }
+
+        Ok(())
+    }
+
+    async fn send(&self, alert: &Alert, metadata: &JobMetadata, config: &Value) -> Result<DeliveryReport, AppError> {
+        info!("sending webhook notification: {:?}", alert.alert_type);
+
+        let _config = parse_config(config)?;
+
+        let sent_at = get_utc_now();
+        let envelope = WebhookEnvelope {
+            alert_type: alert.alert_type.clone(),
+            severity: alert.severity,
+            app_name: alert.app_name.clone(),
+            job_name: alert.job_name.clone(),
+            stage_name: alert.stage_name.clone(),
+            run_id: alert.run_id.clone(),
+            message: alert.message.clone(),
+            metadata: metadata.clone(),
+            sent_at,
+        };
+        let body = serde_json::to_string(&envelope)
+            .map_err(|e| AppError::InternalError(format!("Failed to serialize webhook payload: {}", e)))?;
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(_config.timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECONDS)))
+            .build()
+            .map_err(|e| AppError::InternalError(format!("Failed to build http client: {}", e)))?;
+
+        let mut request = client.post(&_config.url)
+            .header(CONTENT_TYPE, "application/json");
+        for (name, value) in _config.headers.iter() {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        if let Some(secret) = _config.secret.as_ref() {
+            let timestamp = sent_at.timestamp().to_string();
+            request = request
+                .header(TIMESTAMP_HEADER, &timestamp)
+                .header(SIGNATURE_HEADER, format!("sha256={}", sign_payload(secret, &timestamp, &body)));
+        }
+
+        let res = request.body(body)
+            .send()
+            .await
+            .map_err(|e| AppError::InternalError(format!("Failed to send webhook request: {}", e)))?;
+
+        let report = DeliveryReport::from_http_response(res).await;
+        info!("notification status: {:?}", report.status_code);
+
+        Ok(report)
+    }
+}
+
+fn parse_config(config: &Value) -> Result<Config, AppError> {
+    serde_json::from_value(config.clone()).map_err(|e| {
+        AppError::BadRequest(format!("invalid config provided {}", e))
+    })
+}
+
+/// Hex encoded HMAC-SHA256 of `{timestamp}.{body}`; binding the timestamp lets receivers reject replays.
+pub fn sign_payload(secret: &str, timestamp: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+    use crate::notification::stub_server::{start_stub_server, StubRequest};
+
+    const SECRET: &str = "0123456789abcdef0123";
+
+    fn alert() -> Alert {
+        Alert {
+            app_name: "app1".to_string(),
+            job_name: "job1".to_string(),
+            run_id: Some("run1".to_string()),
+            stage_name: "load".to_string(),
+            alert_type: AlertType::Failed,
+            severity: Severity::Critical,
+            message: Some("exit code 1".to_string()),
+        }
+    }
+
+    /// Sends the alert to a stub receiver and returns the request it got.
+    async fn send(config: impl FnOnce(String) -> Value) -> StubRequest {
+        let (base_url, requests) = start_stub_server().await;
+        let config = config(format!("{}/hook", base_url));
+        WebhookPlugin.validate_config(&config).unwrap();
+
+        let report = WebhookPlugin.send(&alert(), &JobMetadata::default(), &config).await.unwrap();
+        assert!(report.success);
+        assert_eq!(report.status_code, Some(200));
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/hook");
+        requests[0].clone()
+    }
+
+    fn header<'a>(request: &'a StubRequest, name: &str) -> Option<&'a str> {
+        request.headers.get(name).map(|value| value.to_str().unwrap())
+    }
+
+    #[tokio::test]
+    async fn posts_the_envelope() {
+        let request = send(|url| json!({ "url": url })).await;
+
+        assert_eq!(header(&request, "content-type"), Some("application/json"));
+        assert_eq!(request.body["alert_type"], "Failed");
+        assert_eq!(request.body["severity"], "critical");
+        assert_eq!(request.body["app_name"], "app1");
+        assert_eq!(request.body["job_name"], "job1");
+        assert_eq!(request.body["stage_name"], "load");
+        assert_eq!(request.body["run_id"], "run1");
+        assert_eq!(request.body["message"], "exit code 1");
+        assert!(request.body["sent_at"].is_string());
+    }
+
+    #[tokio::test]
+    async fn unsigned_without_a_secret() {
+        let request = send(|url| json!({ "url": url })).await;
+
+        assert_eq!(header(&request, SIGNATURE_HEADER), None);
+        assert_eq!(header(&request, TIMESTAMP_HEADER), None);
+    }
+
+    #[tokio::test]
+    async fn signs_the_timestamp_and_body() {
+        let request = send(|url| json!({ "url": url, "secret": SECRET })).await;
+
+        let timestamp = header(&request, TIMESTAMP_HEADER).unwrap();
+        let sent_at = DateTime::parse_from_rfc3339(request.body["sent_at"].as_str().unwrap()).unwrap();
+        assert_eq!(timestamp, sent_at.timestamp().to_string());
+
+        let signature = header(&request, SIGNATURE_HEADER).unwrap();
+        assert_eq!(signature, format!("sha256={}", sign_payload(SECRET, timestamp, &request.raw_body)));
+
+        // What a receiver does: recompute the HMAC over `{timestamp}.{body}` with the shared secret
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+        mac.update(format!("{}.{}", timestamp, request.raw_body).as_bytes());
+        let expected = hex::decode(signature.strip_prefix("sha256=").unwrap()).unwrap();
+        assert!(mac.verify_slice(&expected).is_ok());
+    }
+
+    #[test]
+    fn signature_depends_on_secret_timestamp_and_body() {
+        let signature = sign_payload(SECRET, "1700000000", "{}");
+        assert_eq!(signature.len(), 64);
+        assert_ne!(signature, sign_payload("another secret value", "1700000000", "{}"));
+        assert_ne!(signature, sign_payload(SECRET, "1700000001", "{}"));
+        assert_ne!(signature, sign_payload(SECRET, "1700000000", "{ }"));
+    }
+
+    #[tokio::test]
+    async fn sends_the_configured_headers() {
+        let request = send(|url| json!({
+            "url": url,
+            "headers": { "Authorization": "Bearer token", "X-Team": "data" },
+        })).await;
+
+        assert_eq!(header(&request, "authorization"), Some("Bearer token"));
+        assert_eq!(header(&request, "x-team"), Some("data"));
+    }
+
+    #[test]
+    fn rejects_invalid_headers() {
+        let invalid_name = json!({ "url": "https://example.com/hook", "headers": { "bad header": "value" } });
+        assert!(WebhookPlugin.validate_config(&invalid_name).is_err());
+
+        let invalid_value = json!({ "url": "https://example.com/hook", "headers": { "X-Team": "line\nbreak" } });
+        assert!(WebhookPlugin.validate_config(&invalid_value).is_err());
+    }
+
+    #[test]
+    fn bounds_the_timeout_and_secret_length() {
+        let config = |extra: Value| {
+            let mut config = json!({ "url": "https://example.com/hook" });
+            config.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+            config
+        };
+        assert!(WebhookPlugin.validate_config(&config(json!({ "timeout_seconds": 1 }))).is_ok());
+        assert!(WebhookPlugin.validate_config(&config(json!({ "timeout_seconds": 60 }))).is_ok());
+        assert!(WebhookPlugin.validate_config(&config(json!({ "timeout_seconds": 0 }))).is_err());
+        assert!(WebhookPlugin.validate_config(&config(json!({ "timeout_seconds": 61 }))).is_err());
+        assert!(WebhookPlugin.validate_config(&config(json!({ "secret": "too short" }))).is_err());
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_a_receiver_that_does_not_answer() {
+        // Accepts connections but never responds
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut connections = vec![];
+            while let Ok((connection, _)) = listener.accept().await {
+                connections.push(connection);
+            }
+        });
+
+        let started = std::time::Instant::now();
+        let result = WebhookPlugin.send(&alert(), &JobMetadata::default(), &json!({ "url": url, "timeout_seconds": 1 })).await;
+        assert!(result.is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
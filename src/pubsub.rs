@@ -1,10 +1,40 @@
+use std::time::Duration;
+use diesel::sql_types::Text;
 use diesel_async::RunQueryDsl;
 use futures::StreamExt;
-use crate::db::connection::PgPool;
+use tokio::task::JoinHandle;
+use tracing::error;
+use crate::db::connection::{DbConnection, PgPool};
+use crate::dtos::run_event::RunEvent;
 use crate::models::Settings;
 use crate::SharedState;
 
-pub async fn start_listener(pool: PgPool, state: SharedState) {
+const SETTINGS_CHANNEL: &str = "settings_update";
+const RUN_EVENTS_CHANNEL: &str = "run_events";
+const LISTENER_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Publishes a run event to every watchdog instance, including this one, through `pg_notify`, once the transaction
+/// recording it with the change it is about committed. Best effort: failures are logged and never fail the caller.
+pub async fn publish_run_event(conn: &mut DbConnection<'_>, event: RunEvent) {
+    let payload = match serde_json::to_string(&event) {
+        Ok(payload) => payload,
+        Err(e) => {
+            error!("Failed to serialize run event {:?}: {}", event, e);
+            return;
+        }
+    };
+
+    if let Err(e) = diesel::sql_query("SELECT pg_notify($1, $2)")
+        .bind::<Text, _>(RUN_EVENTS_CHANNEL)
+        .bind::<Text, _>(payload)
+        .execute(conn)
+        .await
+    {
+        error!("Failed to publish run event {:?}: {}", event, e);
+    }
+}
+
+pub async fn start_listener(pool: PgPool, state: SharedState) -> JoinHandle<()> {
     tokio::spawn(async move {
         loop {
             // We handle the connection result manually because tokio::spawn
@@ -14,25 +44,44 @@ pub async fn start_listener(pool: PgPool, state: SharedState) {
                 Err(e) => {
                     eprintln!("❌ Failed to get connection from pool: {}", e);
                     // eprintln!("📡 Listener error: {}. Retrying in 5s...", e);
-                    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                    state.health.write().expect("Lock poisoned").record_listener_disconnected(e.to_string());
+                    tokio::time::sleep(LISTENER_RETRY_DELAY).await;
                     continue;
                 }
             };
 
             // Now you can proceed with the listener logic
-            if let Err(e) = diesel::sql_query("LISTEN settings_update")
-                .execute(&mut connection)
-                .await
-            {
-                eprintln!("❌ Failed to LISTEN: {}", e);
-                return;
+            let mut listen_error = None;
+            for channel in [SETTINGS_CHANNEL, RUN_EVENTS_CHANNEL] {
+                if let Err(e) = diesel::sql_query(format!("LISTEN {}", channel))
+                    .execute(&mut connection)
+                    .await
+                {
+                    eprintln!("❌ Failed to LISTEN on {}: {}", channel, e);
+                    listen_error = Some(format!("failed to listen on {}: {}", channel, e));
+                    break;
+                }
+            }
+            if let Some(e) = listen_error {
+                state.health.write().expect("Lock poisoned").record_listener_disconnected(e);
+                tokio::time::sleep(LISTENER_RETRY_DELAY).await;
+                continue;
             }
+            state.health.write().expect("Lock poisoned").record_listener_connected();
 
             let stream = connection.notifications_stream();
             tokio::pin!(stream);
 
+            let mut disconnect_reason = "notification stream ended".to_string();
             while let Some(notification_result) = stream.next().await {
                 match notification_result {
+                    Ok(notification) if notification.channel == RUN_EVENTS_CHANNEL => {
+                        match serde_json::from_str::<RunEvent>(&notification.payload) {
+                            // Sending only fails when nobody is subscribed, which is fine
+                            Ok(event) => { let _ = state.run_events.send(event); }
+                            Err(e) => eprintln!("❌ Failed to parse run event payload: {}", e),
+                        }
+                    }
                     Ok(notification) => {
                         // DESERIALIZE the JSON payload
                         let raw_payload = &notification.payload;
@@ -56,10 +105,14 @@ pub async fn start_listener(pool: PgPool, state: SharedState) {
                     }
                     Err(e) => {
                         eprintln!("❌ Notification stream error: {}", e);
+                        disconnect_reason = format!("notification stream error: {}", e);
                         break;
                     }
                 }
             }
+
+            state.health.write().expect("Lock poisoned").record_listener_disconnected(disconnect_reason);
+            tokio::time::sleep(LISTENER_RETRY_DELAY).await;
         }
-    });
+    })
 }
@@ -1,35 +1,67 @@
-use crate::api::channel_handler::{create_channel_handler, get_all_channel_providers_handler, get_all_channels_handler, get_channel_by_id_handler, update_channel_handler};
-use crate::api::config_handler::{create_config_handler, get_all_applications_handler, get_all_configs_handler, get_config_by_app_name_and_job_name_handler, list_jobs_by_app_handler, update_config_handler};
-use crate::api::run_handler::{get_all_runs_handler, get_run_by_id_handler, trigger_job_handler, update_stage_by_context_handler, update_stage_by_id_handler};
+use crate::api::channel_handler::{create_channel_handler, get_all_channel_providers_handler, get_all_channels_handler, get_channel_by_id_handler, test_channel_handler, update_channel_handler};
+use crate::api::config_handler::{create_config_handler, get_all_applications_handler, get_all_configs_handler, get_config_by_app_name_and_job_name_handler, list_jobs_by_app_handler, preview_config_handler, update_config_handler};
+use crate::api::run_handler::{cancel_occurrence_handler, cancel_run_handler, get_all_runs_handler, get_run_by_id_handler, get_run_by_key_handler, skip_occurrence_handler, skip_run_handler, trigger_job_handler, update_stage_by_context_handler, update_stage_by_id_handler};
 
 use axum::{
-    routing::{get, post},
+    middleware,
+    routing::{get, post, put},
     Router,
 };
 use tower_http::cors::{Any, CorsLayer};
+use crate::api::application_handler::{get_application_handler, update_application_handler};
+use crate::api::audit_handler::get_audit_logs_handler;
 use crate::api::health_handler::health_check_handler;
+use crate::api::run_event_handler::{get_run_events_handler, stream_run_events_handler};
+use crate::api::routing_rule_handler::{create_routing_rule_handler, delete_routing_rule_handler, get_all_routing_rules_handler, get_routing_rule_handler, test_routing_handler, update_routing_rule_handler};
+use crate::api::revision_handler::{diff_channel_revisions_handler, diff_config_revisions_handler, get_channel_revision_handler, get_channel_revisions_handler, get_config_revision_handler, get_config_revisions_handler, rollback_channel_handler, rollback_config_handler};
+use crate::api::schedule_handler::get_schedule_forecast_handler;
+use crate::api::snooze_handler::{snooze_application_handler, snooze_job_handler, unsnooze_application_handler, unsnooze_job_handler};
 use crate::{SharedState};
+use crate::audit::audit_middleware;
 use crate::api::settings_handler::{get_settings_handler, update_settings_handler};
 
 pub fn app_routes(state: SharedState) -> Router {
     let channel_routes = Router::new()
         .route("/", get(get_all_channels_handler).post(create_channel_handler))
         .route("/providers", get(get_all_channel_providers_handler))
-        .route("/{id}", get(get_channel_by_id_handler).put(update_channel_handler));
+        .route("/{id}", get(get_channel_by_id_handler).put(update_channel_handler))
+        .route("/{id}/test", post(test_channel_handler))
+        .route("/{id}/revisions", get(get_channel_revisions_handler))
+        .route("/{id}/revisions/diff", get(diff_channel_revisions_handler))
+        .route("/{id}/revisions/{revision}", get(get_channel_revision_handler))
+        .route("/{id}/revisions/{revision}/rollback", post(rollback_channel_handler));
 
     let config_routes = Router::new()
         .route("/", get(get_all_configs_handler).post(create_config_handler))
+        .route("/schedule-preview", post(preview_config_handler))
         .route("/{app_name}", get(list_jobs_by_app_handler))
-        .route("/{app_name}/{job_name}", get(get_config_by_app_name_and_job_name_handler).put(update_config_handler));
+        .route("/{app_name}/{job_name}", get(get_config_by_app_name_and_job_name_handler).put(update_config_handler))
+        .route("/{app_name}/{job_name}/revisions", get(get_config_revisions_handler))
+        .route("/{app_name}/{job_name}/revisions/diff", get(diff_config_revisions_handler))
+        .route("/{app_name}/{job_name}/revisions/{revision}", get(get_config_revision_handler))
+        .route("/{app_name}/{job_name}/revisions/{revision}/rollback", post(rollback_config_handler));
 
     let app_job_routes = Router::new()
         .route("/trigger", post(trigger_job_handler))
-        .route("/stage-update", post(update_stage_by_context_handler));
+        .route("/stage-update", post(update_stage_by_context_handler))
+        .route("/runs/{run_key}", get(get_run_by_key_handler))
+        .route("/occurrences/skip", post(skip_occurrence_handler))
+        .route("/occurrences/cancel", post(cancel_occurrence_handler))
+        .route("/snooze", put(snooze_job_handler).delete(unsnooze_job_handler));
 
     let run_id_routes = Router::new()
         .route("/", get(get_all_runs_handler))
+        .route("/events", get(stream_run_events_handler))
         .route("/{job_run_id}", get(get_run_by_id_handler))
-        .route("/{job_run_id}/stage-update", post(update_stage_by_id_handler));
+        .route("/{job_run_id}/events", get(get_run_events_handler))
+        .route("/{job_run_id}/stage-update", post(update_stage_by_id_handler))
+        .route("/{job_run_id}/skip", post(skip_run_handler))
+        .route("/{job_run_id}/cancel", post(cancel_run_handler));
+
+    let routing_rule_routes = Router::new()
+        .route("/", get(get_all_routing_rules_handler).post(create_routing_rule_handler))
+        .route("/test", post(test_routing_handler))
+        .route("/{name}", get(get_routing_rule_handler).put(update_routing_rule_handler).delete(delete_routing_rule_handler));
 
     let settings_routes = Router::new()
         .route("/", get(get_settings_handler).put(update_settings_handler));
@@ -42,11 +74,17 @@ pub fn app_routes(state: SharedState) -> Router {
     Router::new()
         .route("/health", get(health_check_handler))
         .route("/applications", get(get_all_applications_handler))
+        .route("/schedule", get(get_schedule_forecast_handler))
+        .route("/audit-logs", get(get_audit_logs_handler))
+        .route("/applications/{app_name}", get(get_application_handler).put(update_application_handler))
+        .route("/applications/{app_name}/snooze", put(snooze_application_handler).delete(unsnooze_application_handler))
         .nest("/settings", settings_routes)
         .nest("/channels", channel_routes)
+        .nest("/routing-rules", routing_rule_routes)
         .nest("/job-configs", config_routes)
         .nest("/applications/{app_name}/jobs/{job_name}", app_job_routes)
         .nest("/job-runs", run_id_routes)
+        .route_layer(middleware::from_fn_with_state(state.clone(), audit_middleware))
         .layer(cors)
         .with_state(state)
 }
@@ -1,17 +1,26 @@
 use std::time::Duration;
-use tracing::info;
+use tracing::{error, info, warn};
 use crate::core::process_timeouts::check_all_timeouts;
+use crate::core::retention::purge_expired_audit_logs;
 use crate::db::connection::PgPool;
+use crate::notification::core::_handle_error;
 use crate::notification::dispatcher::NotificationDispatcher;
+use crate::time_utils::get_utc_now;
 use crate::{SharedState};
 
+const SCHEDULER_APP_NAME: &str = "watchdog";
+const SCHEDULER_JOB_NAME: &str = "scheduler";
+const SCHEDULER_STAGE_NAME: &str = "timeout-check";
+
 pub async fn scheduler(db: &PgPool, notification_dispatcher: &NotificationDispatcher, state: SharedState) {
 
     info!("Starting scheduler. Waiting for initial delay...");
 
     let config = state.config.clone();
 
-    tokio::time::sleep(Duration::from_secs(config.scheduler_initial_delay_seconds)).await;
+    if !wait_unless_shutdown(&state, Duration::from_secs(config.scheduler_initial_delay_seconds)).await {
+        return;
+    }
 
     info!("Initial delay complete. Starting scheduled task loop.");
 
@@ -22,10 +31,69 @@ pub async fn scheduler(db: &PgPool, notification_dispatcher: &NotificationDispat
             _settings.clone()
         };
 
-        check_all_timeouts(db, notification_dispatcher, &config, current_settings.clone()).await.expect("scheduler died");
+        // Each pass runs in its own task, so a panic fails the pass instead of stopping the loop
+        let started_at = get_utc_now();
+        let pass = {
+            let (db, dispatcher, config, settings) = (db.clone(), notification_dispatcher.clone(), config.clone(), current_settings.clone());
+            tokio::spawn(async move { check_all_timeouts(&db, &dispatcher, &config, settings).await })
+        };
+        let result = match pass.await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(format!("timeout check failed: {}", e.message())),
+            Err(e) => Err(format!("timeout check panicked: {}", e)),
+        };
+
+        let delay = match result {
+            Ok(()) => {
+                let finished_at = get_utc_now();
+                state.health.write().expect("Lock poisoned").record_scheduler_success(finished_at, finished_at - started_at);
+
+                if let Err(e) = purge_expired_audit_logs(db, &current_settings).await {
+                    error!("Failed to purge expired audit logs: {:?}", e);
+                }
+
+                info!("Task completed. Waiting for fixed delay of {}secs...", config.scheduler_fixed_delay_seconds);
+                config.scheduler_fixed_delay_seconds
+            }
+            Err(message) => {
+                let failures = state.health.write().expect("Lock poisoned").record_scheduler_failure(message.clone());
+                error!("{} ({} in a row)", message, failures);
 
-        info!("Task completed. Waiting for fixed delay of {}secs...", config.scheduler_fixed_delay_seconds);
+                // Alert once per outage rather than on every retry
+                if failures == 1 {
+                    _handle_error(
+                        notification_dispatcher,
+                        Some((SCHEDULER_APP_NAME.to_string(), SCHEDULER_JOB_NAME.to_string())),
+                        None,
+                        SCHEDULER_STAGE_NAME,
+                        &message,
+                        &current_settings.error_channels,
+                    ).await;
+                }
 
-        tokio::time::sleep(Duration::from_secs(config.scheduler_fixed_delay_seconds)).await;
+                let backoff = backoff_seconds(failures, config.scheduler_fixed_delay_seconds);
+                warn!("Retrying timeout check in {}secs...", backoff);
+                backoff
+            }
+        };
+
+        if !wait_unless_shutdown(&state, Duration::from_secs(delay)).await {
+            info!("Scheduler stopped.");
+            return;
+        }
     }
 }
+
+/// Sleeps for `delay`, returning false instead when shutdown starts. A running pass is never interrupted,
+/// shutdown only takes effect between passes.
+async fn wait_unless_shutdown(state: &SharedState, delay: Duration) -> bool {
+    tokio::select! {
+        _ = tokio::time::sleep(delay) => true,
+        _ = state.shutdown.cancelled() => false,
+    }
+}
+
+/// Doubles from one second with every failure, never waiting longer than the regular cadence.
+fn backoff_seconds(failures: u32, fixed_delay_seconds: u64) -> u64 {
+    2u64.saturating_pow(failures.saturating_sub(1)).min(fixed_delay_seconds)
+}
@@ -1,6 +1,10 @@
 // @generated automatically by Diesel CLI.
 
 pub mod sql_types {
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "concurrency_policy"))]
+    pub struct ConcurrencyPolicy;
+
     #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "job_run_status"))]
     pub struct JobRunStatus;
@@ -8,6 +12,60 @@ pub mod sql_types {
     #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "provider_type"))]
     pub struct ProviderType;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "revision_change_type"))]
+    pub struct RevisionChangeType;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "run_event_source"))]
+    pub struct RunEventSource;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "run_event_type"))]
+    pub struct RunEventType;
+}
+
+diesel::table! {
+    application_configs (app_name) {
+        #[max_length = 255]
+        app_name -> Varchar,
+        metadata -> Jsonb,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    audit_logs (id) {
+        id -> Int8,
+        occurred_at -> Timestamptz,
+        actor -> Nullable<Varchar>,
+        client_ip -> Nullable<Varchar>,
+        user_agent -> Nullable<Varchar>,
+        #[max_length = 10]
+        method -> Varchar,
+        route -> Varchar,
+        path -> Varchar,
+        target -> Nullable<Varchar>,
+        request_summary -> Nullable<Jsonb>,
+        status_code -> Int4,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::RevisionChangeType;
+
+    channel_revisions (id) {
+        id -> Int8,
+        channel_name -> Varchar,
+        revision -> Int4,
+        change_type -> RevisionChangeType,
+        changed_by -> Nullable<Varchar>,
+        snapshot -> Jsonb,
+        created_at -> Timestamptz,
+    }
 }
 
 diesel::table! {
@@ -32,10 +90,32 @@ diesel::table! {
         default_channels -> Text,
         error_channels -> Text,
         max_stage_duration_hours -> Int4,
+        audit_retention_days -> Int4,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::RevisionChangeType;
+
+    job_config_revisions (id) {
+        id -> Int8,
+        #[max_length = 255]
+        app_name -> Varchar,
+        #[max_length = 255]
+        job_name -> Varchar,
+        revision -> Int4,
+        change_type -> RevisionChangeType,
+        changed_by -> Nullable<Varchar>,
+        snapshot -> Jsonb,
+        created_at -> Timestamptz,
     }
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ConcurrencyPolicy;
+
     job_configs (app_name, job_name) {
         #[max_length = 255]
         app_name -> Varchar,
@@ -50,6 +130,30 @@ diesel::table! {
         channel_ids -> Varchar,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        concurrency_policy -> ConcurrencyPolicy,
+        blackout_windows -> Jsonb,
+        metadata -> Jsonb,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::RunEventType;
+    use super::sql_types::JobRunStatus;
+    use super::sql_types::RunEventSource;
+
+    job_run_events (id) {
+        id -> Int8,
+        job_run_id -> Uuid,
+        event_type -> RunEventType,
+        #[max_length = 255]
+        stage_name -> Nullable<Varchar>,
+        attempt -> Nullable<Int4>,
+        status -> JobRunStatus,
+        message -> Nullable<Text>,
+        source -> RunEventSource,
+        occurred_at -> Timestamptz,
+        recorded_at -> Timestamptz,
     }
 }
 
@@ -66,7 +170,64 @@ diesel::table! {
         stages -> Jsonb,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        scheduled_for -> Nullable<Timestamptz>,
+        #[max_length = 255]
+        run_key -> Nullable<Varchar>,
+        status_reason -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    job_schedule_checkpoints (app_name, job_name) {
+        #[max_length = 255]
+        app_name -> Varchar,
+        #[max_length = 255]
+        job_name -> Varchar,
+        evaluated_until -> Timestamptz,
+        updated_at -> Timestamptz,
     }
 }
 
-diesel::allow_tables_to_appear_in_same_query!(channels, global_settings, job_configs, job_runs,);
+diesel::table! {
+    routing_rules (name) {
+        #[max_length = 255]
+        name -> Varchar,
+        priority -> Int4,
+        enabled -> Bool,
+        matcher -> Jsonb,
+        channel_ids -> Varchar,
+        continue_matching -> Bool,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    snoozes (id) {
+        id -> Uuid,
+        #[max_length = 255]
+        app_name -> Varchar,
+        #[max_length = 255]
+        job_name -> Nullable<Varchar>,
+        snoozed_until -> Timestamptz,
+        reason -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::joinable!(job_run_events -> job_runs (job_run_id));
+
+diesel::allow_tables_to_appear_in_same_query!(
+    application_configs,
+    audit_logs,
+    channel_revisions,
+    channels,
+    global_settings,
+    job_config_revisions,
+    job_configs,
+    job_run_events,
+    job_runs,
+    job_schedule_checkpoints,
+    routing_rules,
+    snoozes,
+);
@@ -0,0 +1,194 @@
+use std::env;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde_json::Value;
+use crate::errors::AppError;
+
+/// Shown in place of secret values in API responses. Sending it back in an update keeps the stored secret.
+pub const REDACTED: &str = "********";
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+const NONCE_LENGTH: usize = 12;
+/// Prefix of the environment variables channel secrets may reference.
+pub const ENV_REF_PREFIX: &str = "WATCHDOG_CHANNEL_";
+
+/// Encrypts the secret fields of channel configurations with AES-256-GCM.
+/// Without a key, secrets are kept as given; they are still redacted in API responses.
+#[derive(Clone)]
+pub struct SecretCipher {
+    cipher: Option<Aes256Gcm>,
+}
+
+impl SecretCipher {
+    /// Builds the cipher from a base64 encoded 32 byte key.
+    pub fn new(key_base64: Option<&str>) -> Result<Self, String> {
+        let cipher = match key_base64 {
+            Some(encoded) => {
+                let key = STANDARD.decode(encoded.trim())
+                    .map_err(|e| format!("secret key is not valid base64: {}", e))?;
+                if key.len() != 32 {
+                    return Err(format!("secret key must be 32 bytes, got {}", key.len()));
+                }
+                Some(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+            }
+            None => None,
+        };
+        Ok(Self { cipher })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    /// Encrypts the plaintext secret fields of `config`. Encrypted values and `${ENV_VAR}` references are left as they are.
+    pub fn seal(&self, config: &Value, secret_fields: &[&str]) -> Result<Value, AppError> {
+        let Some(cipher) = self.cipher.as_ref() else {
+            return Ok(config.clone());
+        };
+
+        let mut sealed = config.clone();
+        for (_, value) in secret_values_mut(&mut sealed, secret_fields) {
+            if is_encrypted(value) || is_env_ref(value) {
+                continue;
+            }
+            let plaintext = serde_json::to_vec(value)
+                .map_err(|e| AppError::InternalError(format!("failed to serialize secret: {}", e)))?;
+            let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+            let mut payload = nonce.to_vec();
+            payload.extend(cipher.encrypt(&nonce, plaintext.as_ref())
+                .map_err(|e| AppError::InternalError(format!("failed to encrypt secret: {}", e)))?);
+            *value = Value::String(format!("{}{}", ENCRYPTED_PREFIX, STANDARD.encode(payload)));
+        }
+        Ok(sealed)
+    }
+
+    /// Decrypts the encrypted secret fields of `config`.
+    pub fn unseal(&self, config: &Value, secret_fields: &[&str]) -> Result<Value, AppError> {
+        let mut unsealed = config.clone();
+        for (field, value) in secret_values_mut(&mut unsealed, secret_fields) {
+            let Some(encoded) = value.as_str().and_then(|s| s.strip_prefix(ENCRYPTED_PREFIX)) else {
+                continue;
+            };
+            let cipher = self.cipher.as_ref().ok_or_else(|| {
+                AppError::InternalError(format!("'{}' is encrypted but no secret key is configured", field))
+            })?;
+            let payload = STANDARD.decode(encoded)
+                .map_err(|e| AppError::InternalError(format!("invalid encrypted value for '{}': {}", field, e)))?;
+            if payload.len() < NONCE_LENGTH {
+                return Err(AppError::InternalError(format!("invalid encrypted value for '{}'", field)));
+            }
+            let (nonce, ciphertext) = payload.split_at(NONCE_LENGTH);
+            let plaintext = cipher.decrypt(Nonce::from_slice(nonce), ciphertext)
+                .map_err(|_| AppError::InternalError(format!("failed to decrypt '{}', was the secret key changed?", field)))?;
+            *value = serde_json::from_slice(&plaintext)
+                .map_err(|e| AppError::InternalError(format!("invalid decrypted value for '{}': {}", field, e)))?;
+        }
+        Ok(unsealed)
+    }
+}
+
+/// Replaces secret values with [`REDACTED`]. `${ENV_VAR}` references are not secrets and stay visible.
+pub fn redact(config: &Value, secret_fields: &[&str]) -> Value {
+    let mut redacted = config.clone();
+    for (_, value) in secret_values_mut(&mut redacted, secret_fields) {
+        if !value.is_null() && !is_env_ref(value) {
+            *value = Value::String(REDACTED.to_string());
+        }
+    }
+    redacted
+}
+
+/// Copies secrets the update left out, or sent back redacted, from the stored configuration.
+pub fn keep_omitted_secrets(config: &mut Value, existing: &Value, secret_fields: &[&str]) {
+    let Some(config_map) = config.as_object_mut() else {
+        return;
+    };
+    for field in secret_fields {
+        let omitted = config_map.get(*field).is_none_or(|value| value.as_str() == Some(REDACTED));
+        if let Some(existing_value) = existing.get(*field).filter(|_| omitted) {
+            config_map.insert(field.to_string(), existing_value.clone());
+        }
+    }
+}
+
+/// Substitutes secret values of the form `${ENV_VAR}` with the variable's value, see [`check_env_refs`].
+pub fn resolve_env_refs(config: &Value, secret_fields: &[&str]) -> Result<Value, AppError> {
+    let mut resolved = config.clone();
+    for (field, value) in secret_values_mut(&mut resolved, secret_fields) {
+        let Some(name) = value.as_str().filter(|_| is_env_ref(value)).map(env_ref_name) else {
+            continue;
+        };
+        check_env_ref_name(field, name)?;
+        *value = env::var(name)
+            .map(Value::String)
+            .map_err(|_| AppError::BadRequest(format!("environment variable '{}' is not set", name)))?;
+    }
+    Ok(resolved)
+}
+
+/// Only secret fields may reference environment variables, and only those prefixed with [`ENV_REF_PREFIX`],
+/// so a channel can not read other settings of the server such as `DATABASE_URL`.
+pub fn check_env_refs(config: &Value, secret_fields: &[&str]) -> Result<(), AppError> {
+    let Some(config_map) = config.as_object() else {
+        return Ok(());
+    };
+    for (field, value) in config_map {
+        if secret_fields.contains(&field.as_str()) && is_env_ref(value) {
+            check_env_ref_name(field, env_ref_name(value.as_str().unwrap_or_default()))?;
+        } else if let Some(reference) = find_env_ref(value) {
+            return Err(AppError::BadRequest(format!("'{}' can not reference environment variable {}, only secret fields can", field, reference)));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `config` holds secrets in plaintext, rather than encrypted or as `${ENV_VAR}` references.
+pub fn has_plaintext_secrets(config: &Value, secret_fields: &[&str]) -> bool {
+    config.as_object().is_some_and(|config_map| {
+        config_map.iter()
+            .filter(|(field, _)| secret_fields.contains(&field.as_str()))
+            .any(|(_, value)| !value.is_null() && !is_env_ref(value) && !is_encrypted(value))
+    })
+}
+
+fn check_env_ref_name(field: &str, name: &str) -> Result<(), AppError> {
+    if !name.starts_with(ENV_REF_PREFIX) {
+        return Err(AppError::BadRequest(format!("'{}' can only reference environment variables starting with {}, got '{}'", field, ENV_REF_PREFIX, name)));
+    }
+    Ok(())
+}
+
+fn find_env_ref(value: &Value) -> Option<&str> {
+    match value {
+        Value::String(reference) if is_env_ref(value) => Some(reference),
+        Value::Object(map) => map.values().find_map(find_env_ref),
+        Value::Array(items) => items.iter().find_map(find_env_ref),
+        _ => None,
+    }
+}
+
+fn secret_values_mut<'a>(config: &'a mut Value, secret_fields: &'a [&str]) -> impl Iterator<Item = (&'a str, &'a mut Value)> {
+    config.as_object_mut()
+        .into_iter()
+        .flat_map(|map| map.iter_mut())
+        .filter(|(key, _)| secret_fields.contains(&key.as_str()))
+        .map(|(key, value)| (key.as_str(), value))
+}
+
+fn is_encrypted(value: &Value) -> bool {
+    value.as_str().is_some_and(|s| s.starts_with(ENCRYPTED_PREFIX))
+}
+
+fn is_env_ref(value: &Value) -> bool {
+    value.as_str().is_some_and(|s| {
+        s.starts_with("${") && s.ends_with('}') && {
+            let name = env_ref_name(s);
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+    })
+}
+
+fn env_ref_name(reference: &str) -> &str {
+    reference.get(2..reference.len().saturating_sub(1)).unwrap_or_default()
+}
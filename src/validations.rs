@@ -1,6 +1,11 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+use chrono_tz::Tz;
+use cron::Schedule;
 use serde_json::Value;
 use validator::ValidationError;
 use validify::validate_email;
+use crate::models::{BlackoutWindow, JobMetadata, JobStageConfig, RoutingMatcher};
 
 pub fn validate_name(name: &str) -> Result<(), ValidationError> {
     if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') && (4..=32).contains(&name.len()) {
@@ -56,4 +61,165 @@ pub fn validate_config_json(config_str: &str) -> Result<(), ValidationError> {
     // }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn validate_schedule(schedule: &str) -> Result<(), ValidationError> {
+    let parsed = Schedule::from_str(schedule).map_err(|e| ValidationError {
+        code: "invalid_schedule".into(),
+        message: Some(format!("Invalid cron expression '{}': {}", schedule, e).into()),
+        params: std::collections::HashMap::new(),
+    })?;
+
+    // A schedule that never fires again would silently stop all timeout detection
+    if parsed.upcoming(chrono::Utc).next().is_none() {
+        return Err(ValidationError {
+            code: "invalid_schedule".into(),
+            message: Some(format!("Cron expression '{}' has no upcoming executions", schedule).into()),
+            params: std::collections::HashMap::new(),
+        });
+    }
+
+    Ok(())
+}
+
+pub fn validate_zone_id(zone_id: &str) -> Result<(), ValidationError> {
+    if zone_id.parse::<Tz>().is_err() {
+        return Err(ValidationError {
+            code: "invalid_zone_id".into(),
+            message: Some(format!("Invalid timezone provided: {}", zone_id).into()),
+            params: std::collections::HashMap::new(),
+        });
+    }
+    Ok(())
+}
+
+/// Longest blackout window, 7 days; longer silences should disable the job instead.
+const MAX_BLACKOUT_DURATION_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+pub fn validate_blackout_windows(windows: &[BlackoutWindow]) -> Result<(), ValidationError> {
+    for (index, window) in windows.iter().enumerate() {
+        let error = |message: String| ValidationError {
+            code: "invalid_blackout_windows".into(),
+            message: Some(format!("Invalid blackout window at index {}: {}", index, message).into()),
+            params: std::collections::HashMap::new(),
+        };
+
+        validate_schedule(&window.schedule).map_err(|e| error(e.message.map(|m| m.to_string()).unwrap_or_default()))?;
+        if window.duration_seconds == 0 || window.duration_seconds > MAX_BLACKOUT_DURATION_SECONDS {
+            return Err(error(format!("duration_seconds must be between 1 and {}", MAX_BLACKOUT_DURATION_SECONDS)));
+        }
+    }
+    Ok(())
+}
+
+const MAX_METADATA_VALUE_LENGTH: usize = 255;
+const MAX_TAGS: usize = 32;
+
+pub fn validate_job_metadata(metadata: &JobMetadata) -> Result<(), ValidationError> {
+    let error = |message: String| ValidationError {
+        code: "invalid_metadata".into(),
+        message: Some(message.into()),
+        params: std::collections::HashMap::new(),
+    };
+
+    for (field, value) in [("owner_team", &metadata.owner_team), ("contact", &metadata.contact)] {
+        if value.as_ref().is_some_and(|value| value.trim().is_empty() || value.len() > MAX_METADATA_VALUE_LENGTH) {
+            return Err(error(format!("{} must be between 1 and {} characters", field, MAX_METADATA_VALUE_LENGTH)));
+        }
+    }
+
+    if metadata.tags.len() > MAX_TAGS {
+        return Err(error(format!("At most {} tags can be set", MAX_TAGS)));
+    }
+    let mut tags = HashSet::new();
+    for (index, tag) in metadata.tags.iter().enumerate() {
+        if tag.trim().is_empty() || tag.len() > MAX_METADATA_VALUE_LENGTH || tag.contains(',') {
+            return Err(error(format!("Invalid tag at index {}: must be between 1 and {} characters without commas", index, MAX_METADATA_VALUE_LENGTH)));
+        }
+        if !tags.insert(tag) {
+            return Err(error(format!("Duplicate tag '{}'", tag)));
+        }
+    }
+
+    if let Some(runbook_url) = &metadata.runbook_url {
+        validate_url(runbook_url).map_err(|_| error(format!("Invalid runbook_url: {}", runbook_url)))?;
+    }
+    Ok(())
+}
+
+pub fn validate_channel_ids(channel_ids: &str) -> Result<(), ValidationError> {
+    if channel_ids.split(',').all(|channel_id| channel_id.trim().is_empty()) {
+        return Err(ValidationError {
+            code: "invalid_channel_ids".into(),
+            message: Some("At least one channel must be configured".into()),
+            params: std::collections::HashMap::new(),
+        });
+    }
+    Ok(())
+}
+
+pub fn validate_routing_matcher(matcher: &RoutingMatcher) -> Result<(), ValidationError> {
+    let error = |message: String| ValidationError {
+        code: "invalid_matcher".into(),
+        message: Some(message.into()),
+        params: std::collections::HashMap::new(),
+    };
+
+    for (field, pattern) in [("app_name", &matcher.app_name), ("job_name", &matcher.job_name)] {
+        if pattern.as_ref().is_some_and(|pattern| pattern.is_empty() || pattern.len() > MAX_METADATA_VALUE_LENGTH) {
+            return Err(error(format!("{} must be between 1 and {} characters", field, MAX_METADATA_VALUE_LENGTH)));
+        }
+    }
+    for (field, values) in [("tags", &matcher.tags), ("stage_names", &matcher.stage_names)] {
+        if values.iter().any(|value| value.trim().is_empty()) {
+            return Err(error(format!("{} must not contain empty values", field)));
+        }
+    }
+
+    if let Some(time_window) = &matcher.time_window {
+        if time_window.start == time_window.end {
+            return Err(error("time_window must not start and end at the same time".to_string()));
+        }
+        if let Some(zone_id) = &time_window.zone_id {
+            validate_zone_id(zone_id).map_err(|e| error(e.message.map(|m| m.to_string()).unwrap_or_default()))?;
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_stages(stages: &[JobStageConfig]) -> Result<(), ValidationError> {
+    if stages.is_empty() {
+        return Err(ValidationError {
+            code: "invalid_stages".into(),
+            message: Some("At least one stage must be configured".into()),
+            params: std::collections::HashMap::new(),
+        });
+    }
+
+    let mut names = HashSet::new();
+    for (index, stage) in stages.iter().enumerate() {
+        let error = |message: String| ValidationError {
+            code: "invalid_stages".into(),
+            message: Some(format!("Invalid stage at index {}: {}", index, message).into()),
+            params: std::collections::HashMap::new(),
+        };
+
+        if stage.name.trim().is_empty() {
+            return Err(error("name must not be empty".to_string()));
+        }
+        if !names.insert(stage.name.as_str()) {
+            return Err(error(format!("duplicate stage name '{}'", stage.name)));
+        }
+        if stage.start.is_none() && stage.complete.is_none() {
+            return Err(error(format!("stage '{}' must define start or complete", stage.name)));
+        }
+        if let (Some(start), Some(complete)) = (stage.start, stage.complete) && start > complete {
+            return Err(error(format!("start ({}) of stage '{}' is after its complete ({})", start, stage.name, complete)));
+        }
+        if stage.max_attempts == Some(0) {
+            return Err(error(format!("max_attempts of stage '{}' must be at least 1", stage.name)));
+        }
+    }
+
+    Ok(())
+}